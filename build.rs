@@ -0,0 +1,4 @@
+fn main() {
+    // exposes the `Py_LIMITED_API`, `PyPy` and `GraalPy` cfgs for interpreter-specific code
+    pyo3_build_config::use_pyo3_cfgs();
+}
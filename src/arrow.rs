@@ -0,0 +1,65 @@
+//! Conversions between Arrow record batches and `pyarrow`
+
+use arrow::pyarrow::{FromPyArrow, ToPyArrow};
+use arrow::record_batch::RecordBatch;
+use pyo3::{Bound, PyAny, Python};
+
+use crate::error::Result;
+
+/// Convert a `RecordBatch` into a `pyarrow.RecordBatch`
+///
+/// The columns are handed to pyarrow through the Arrow C data interface, so their
+/// buffers are shared rather than converted into Python objects.
+pub fn pythonize_record_batch<'py>(
+    py: Python<'py>,
+    batch: &RecordBatch,
+) -> Result<Bound<'py, PyAny>> {
+    Ok(batch.to_pyarrow(py)?.into_bound(py))
+}
+
+/// Convert a `pyarrow.RecordBatch`, or any other object which exports a struct array
+/// through the Arrow PyCapsule interface, into a `RecordBatch`
+///
+/// To convert the rows of a `pyarrow.RecordBatch` or `pyarrow.Table` into Rust structs
+/// instead, pass it to [`depythonize`](crate::depythonize) as a sequence.
+pub fn depythonize_record_batch(obj: &Bound<'_, PyAny>) -> Result<RecordBatch> {
+    Ok(RecordBatch::from_pyarrow_bound(obj)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Float64Array, StringArray};
+    use arrow::record_batch::RecordBatch;
+    use pyo3::prelude::*;
+
+    use super::{depythonize_record_batch, pythonize_record_batch};
+
+    #[test]
+    #[ignore = "needs the pyarrow Python package"]
+    fn test_record_batch_roundtrip() {
+        Python::with_gil(|py| {
+            let batch = RecordBatch::try_from_iter([
+                (
+                    "name",
+                    Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef,
+                ),
+                (
+                    "score",
+                    Arc::new(Float64Array::from(vec![0.5, 1.5])) as ArrayRef,
+                ),
+            ])
+            .unwrap();
+            let obj = pythonize_record_batch(py, &batch).unwrap();
+            assert_eq!(obj.get_type().qualname().unwrap(), "RecordBatch");
+            assert_eq!(
+                obj.call_method0("to_pylist").unwrap().to_string(),
+                "[{'name': 'a', 'score': 0.5}, {'name': None, 'score': 1.5}]"
+            );
+
+            let actual = depythonize_record_batch(&obj).unwrap();
+            assert_eq!(actual, batch);
+        })
+    }
+}
@@ -0,0 +1,366 @@
+//! Conversions between Burn tensor data and PyTorch tensors through the DLPack protocol
+
+use std::ffi::c_void;
+use std::slice;
+
+use burn_tensor::{DType, TensorData};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyCapsule};
+use pyo3::{ffi, Bound, PyAny, PyErr, PyObject, Python};
+
+use crate::error::{PythonizeError, Result};
+use crate::native::import;
+
+static FROM_DLPACK: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// The capsule name of an unconsumed `DLManagedTensor`
+const DLTENSOR: &[u8] = b"dltensor\0";
+/// The capsule name once a consumer has taken ownership of the `DLManagedTensor`
+const USED_DLTENSOR: &[u8] = b"used_dltensor\0";
+
+/// `DLDeviceType::kDLCPU`
+const CPU: i32 = 1;
+
+// `DLDataTypeCode`
+const INT: u8 = 0;
+const UINT: u8 = 1;
+const FLOAT: u8 = 2;
+const BFLOAT: u8 = 4;
+const BOOL: u8 = 6;
+
+#[repr(C)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+/// Owns the memory an exported `DLManagedTensor` points into
+struct Context {
+    data: TensorData,
+    shape: Vec<i64>,
+}
+
+fn to_dlpack_dtype(dtype: DType) -> Option<DLDataType> {
+    let (code, bits) = match dtype {
+        DType::F64 => (FLOAT, 64),
+        DType::F32 | DType::Flex32 => (FLOAT, 32),
+        DType::F16 => (FLOAT, 16),
+        DType::BF16 => (BFLOAT, 16),
+        DType::I64 => (INT, 64),
+        DType::I32 => (INT, 32),
+        DType::I16 => (INT, 16),
+        DType::I8 => (INT, 8),
+        DType::U64 => (UINT, 64),
+        DType::U32 => (UINT, 32),
+        DType::U16 => (UINT, 16),
+        DType::U8 => (UINT, 8),
+        DType::Bool => (BOOL, 8),
+        DType::QFloat(_) => return None,
+    };
+    Some(DLDataType {
+        code,
+        bits,
+        lanes: 1,
+    })
+}
+
+fn from_dlpack_dtype(dtype: DLDataType) -> Option<DType> {
+    Some(match (dtype.code, dtype.bits, dtype.lanes) {
+        (FLOAT, 64, 1) => DType::F64,
+        (FLOAT, 32, 1) => DType::F32,
+        (FLOAT, 16, 1) => DType::F16,
+        (BFLOAT, 16, 1) => DType::BF16,
+        (INT, 64, 1) => DType::I64,
+        (INT, 32, 1) => DType::I32,
+        (INT, 16, 1) => DType::I16,
+        (INT, 8, 1) => DType::I8,
+        (UINT, 64, 1) => DType::U64,
+        (UINT, 32, 1) => DType::U32,
+        (UINT, 16, 1) => DType::U16,
+        (UINT, 8, 1) => DType::U8,
+        (BOOL, 8, 1) => DType::Bool,
+        _ => return None,
+    })
+}
+
+unsafe extern "C" fn delete_tensor(managed: *mut DLManagedTensor) {
+    let managed = Box::from_raw(managed);
+    drop(Box::from_raw(managed.manager_ctx.cast::<Context>()));
+}
+
+unsafe extern "C" fn destroy_capsule(capsule: *mut ffi::PyObject) {
+    // a consumer renames the capsule once it has taken ownership of the tensor
+    if ffi::PyCapsule_IsValid(capsule, DLTENSOR.as_ptr().cast()) == 1 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, DLTENSOR.as_ptr().cast());
+        delete_tensor(managed.cast());
+    }
+}
+
+/// Wrap `data` in a DLPack capsule which owns it until it is consumed
+fn tensor_capsule(py: Python<'_>, data: TensorData) -> Result<Bound<'_, PyAny>> {
+    let dtype = to_dlpack_dtype(data.dtype)
+        .ok_or_else(|| PythonizeError::unsupported_type(format!("{:?} tensor data", data.dtype)))?;
+    let mut context = Box::new(Context {
+        shape: data.shape.iter().map(|&len| len as i64).collect(),
+        data,
+    });
+    let managed = Box::into_raw(Box::new(DLManagedTensor {
+        dl_tensor: DLTensor {
+            data: context.data.bytes.as_mut_ptr().cast(),
+            device: DLDevice {
+                device_type: CPU,
+                device_id: 0,
+            },
+            ndim: context.shape.len() as i32,
+            dtype,
+            shape: context.shape.as_mut_ptr(),
+            // null strides mean the tensor is contiguous in row-major order
+            strides: std::ptr::null_mut(),
+            byte_offset: 0,
+        },
+        manager_ctx: Box::into_raw(context).cast(),
+        deleter: Some(delete_tensor),
+    }));
+    unsafe {
+        let capsule = ffi::PyCapsule_New(
+            managed.cast(),
+            DLTENSOR.as_ptr().cast(),
+            Some(destroy_capsule),
+        );
+        if capsule.is_null() {
+            delete_tensor(managed);
+        }
+        Ok(Bound::from_owned_ptr_or_err(py, capsule)?)
+    }
+}
+
+/// Copy the elements of a CPU tensor into `TensorData`, in row-major order
+unsafe fn read_tensor(tensor: &DLTensor) -> Result<TensorData> {
+    if tensor.device.device_type != CPU {
+        return Err(PythonizeError::msg(
+            "only tensors in CPU memory can be converted",
+        ));
+    }
+    let dtype = from_dlpack_dtype(tensor.dtype).ok_or_else(|| {
+        PythonizeError::unsupported_type(format!("DLPack data type {:?}", tensor.dtype))
+    })?;
+    let ndim = tensor.ndim as usize;
+    let shape = match ndim {
+        0 => &[],
+        _ => slice::from_raw_parts(tensor.shape, ndim),
+    };
+    let mut contiguous = vec![1; ndim];
+    for axis in (1..ndim).rev() {
+        contiguous[axis - 1] = contiguous[axis] * shape[axis];
+    }
+    let strides = match ndim {
+        0 => &[],
+        _ if tensor.strides.is_null() => &contiguous[..],
+        _ => slice::from_raw_parts(tensor.strides, ndim),
+    };
+
+    let itemsize = dtype.size();
+    let len = shape.iter().product::<i64>() as usize;
+    let base = (tensor.data as *const u8).add(tensor.byte_offset as usize);
+    let mut bytes = Vec::with_capacity(len * itemsize);
+    if len == 0 {
+        // the data pointer of an empty tensor may be null
+    } else if strides == &contiguous[..] {
+        bytes.extend_from_slice(slice::from_raw_parts(base, len * itemsize));
+    } else {
+        let mut index = vec![0; ndim];
+        for _ in 0..len {
+            let offset: i64 = index
+                .iter()
+                .zip(strides)
+                .map(|(i, stride)| i * stride)
+                .sum();
+            let element = base.offset(offset as isize * itemsize as isize);
+            bytes.extend_from_slice(slice::from_raw_parts(element, itemsize));
+            for axis in (0..ndim).rev() {
+                index[axis] += 1;
+                if index[axis] < shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+    }
+    let shape: Vec<usize> = shape.iter().map(|&len| len as usize).collect();
+    Ok(TensorData::from_bytes(bytes, shape, dtype))
+}
+
+/// Convert `TensorData` into a `torch.Tensor`
+///
+/// The data is handed to `torch.from_dlpack` as a DLPack capsule, so the tensor shares
+/// its memory rather than copying it. Quantized data is not supported, so dequantize it
+/// first. A Burn `Tensor` can be converted with `Tensor::into_data`.
+pub fn pythonize_tensor(py: Python<'_>, data: TensorData) -> Result<Bound<'_, PyAny>> {
+    let capsule = tensor_capsule(py, data)?;
+    Ok(import(&FROM_DLPACK, py, "torch", "from_dlpack")?.call1((capsule,))?)
+}
+
+/// Convert a `torch.Tensor`, or any other object in CPU memory which implements the
+/// DLPack protocol such as a `numpy.ndarray`, into `TensorData`
+///
+/// The elements are copied out of the tensor in a single pass, without converting them
+/// into Python objects. A Burn `Tensor` can be created with `Tensor::from_data`.
+pub fn depythonize_tensor(obj: &Bound<'_, PyAny>) -> Result<TensorData> {
+    let capsule = obj.call_method0("__dlpack__")?;
+    let capsule = capsule.downcast::<PyCapsule>()?;
+    unsafe {
+        let managed = ffi::PyCapsule_GetPointer(capsule.as_ptr(), DLTENSOR.as_ptr().cast())
+            .cast::<DLManagedTensor>();
+        if managed.is_null() {
+            return Err(PyErr::fetch(obj.py()).into());
+        }
+        let data = read_tensor(&(*managed).dl_tensor);
+        // take ownership of the tensor, so that the capsule no longer deletes it
+        if ffi::PyCapsule_SetName(capsule.as_ptr(), USED_DLTENSOR.as_ptr().cast()) != 0 {
+            return Err(PyErr::fetch(obj.py()).into());
+        }
+        if let Some(deleter) = (*managed).deleter {
+            deleter(managed);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use burn_tensor::{DType, TensorData};
+    use pyo3::prelude::*;
+
+    use super::{
+        depythonize_tensor, pythonize_tensor, read_tensor, tensor_capsule, DLDataType, DLDevice,
+        DLTensor, CPU, FLOAT,
+    };
+
+    /// A minimal DLPack producer, for testing without PyTorch
+    fn producer<'py>(py: Python<'py>, data: TensorData) -> Bound<'py, PyAny> {
+        let locals = pyo3::types::PyDict::new_bound(py);
+        py.run_bound(
+            "class Producer:\n    def __init__(self, capsule):\n        self.capsule = capsule\n    def __dlpack__(self):\n        return self.capsule\n",
+            None,
+            Some(&locals),
+        )
+        .unwrap();
+        let capsule = tensor_capsule(py, data).unwrap();
+        locals
+            .get_item("Producer")
+            .unwrap()
+            .unwrap()
+            .call1((capsule,))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tensor_capsule() {
+        Python::with_gil(|py| {
+            for data in [
+                TensorData::new(vec![1.5f32, -2.0, 3.25, 0.0, 8.0, 9.5], [2, 3]),
+                TensorData::new(vec![-7i64], [1, 1]),
+                TensorData::new(vec![true, false, true], [3]),
+                TensorData::new(Vec::<u8>::new(), [0, 4]),
+                TensorData::new(vec![2.5f64], Vec::new()),
+            ] {
+                let actual = depythonize_tensor(&producer(py, data.clone())).unwrap();
+                assert_eq!(actual, data);
+            }
+
+            // an unconsumed capsule deletes its tensor when it is dropped
+            drop(tensor_capsule(py, TensorData::new(vec![1u16, 2], [2])).unwrap());
+
+            // a capsule can only be consumed once
+            let obj = producer(py, TensorData::new(vec![1i32], [1]));
+            depythonize_tensor(&obj).unwrap();
+            assert!(depythonize_tensor(&obj).is_err());
+        })
+    }
+
+    #[test]
+    fn test_read_strided_tensor() {
+        // the transpose of [[1, 2, 3], [4, 5, 6]], offset by one element
+        let mut elements = [0.0f32, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let mut shape = [3i64, 2];
+        let mut strides = [1i64, 3];
+        let tensor = DLTensor {
+            data: elements.as_mut_ptr().cast(),
+            device: DLDevice {
+                device_type: CPU,
+                device_id: 0,
+            },
+            ndim: 2,
+            dtype: DLDataType {
+                code: FLOAT,
+                bits: 32,
+                lanes: 1,
+            },
+            shape: shape.as_mut_ptr(),
+            strides: strides.as_mut_ptr(),
+            byte_offset: 4,
+        };
+        let actual = unsafe { read_tensor(&tensor) }.unwrap();
+        let expected = TensorData::new(vec![1.0f32, 4.0, 2.0, 5.0, 3.0, 6.0], [3, 2]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    #[ignore = "needs the torch Python package"]
+    fn test_torch_roundtrip() {
+        Python::with_gil(|py| {
+            let data = TensorData::new(vec![1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0], [2, 3]);
+            let tensor = pythonize_tensor(py, data.clone()).unwrap();
+            assert_eq!(
+                tensor.getattr("shape").unwrap().to_string(),
+                "torch.Size([2, 3])"
+            );
+            assert_eq!(
+                tensor
+                    .call_method0("sum")
+                    .unwrap()
+                    .call_method0("item")
+                    .unwrap()
+                    .to_string(),
+                "21.0"
+            );
+            assert_eq!(depythonize_tensor(&tensor).unwrap(), data);
+
+            // non-contiguous tensors are copied in row-major order
+            let transposed = tensor.getattr("T").unwrap();
+            let actual = depythonize_tensor(&transposed).unwrap();
+            assert_eq!(actual.shape, [3, 2]);
+            assert_eq!(actual.dtype, DType::F64);
+            assert_eq!(
+                actual.to_vec::<f64>().unwrap(),
+                [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]
+            );
+        })
+    }
+}
@@ -1,8 +1,21 @@
-use pyo3::{types::*, Bound};
+use std::any::type_name;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use pyo3::exceptions::PyOverflowError;
+use pyo3::pybacked::{PyBackedBytes, PyBackedStr};
+use pyo3::{types::*, Bound, FromPyObject, PyResult};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
 use serde::de::{self, DeserializeOwned, IntoDeserializer};
-use serde::Deserialize;
+use serde::{forward_to_deserialize_any, Deserialize};
 
 use crate::error::{ErrorImpl, PythonizeError, Result};
+use crate::native::{
+    self,
+    numpy::{ArrayContents, Elements},
+};
+use crate::ser;
 
 /// Attempt to convert a Python object to an instance of `T`
 pub fn depythonize<'a, 'py, T>(obj: &'a Bound<'py, PyAny>) -> Result<T>
@@ -12,6 +25,76 @@ where
     T::deserialize(&mut Depythonizer::from_object(obj))
 }
 
+/// Attempt to convert a Python object to an instance of `T`, which may borrow strings
+/// from the Python object
+///
+/// Fields such as `&str` and `#[serde(borrow)] Cow<str>` refer to the contents of
+/// Python strings instead of copying them, and likewise `&[u8]` fields refer to the
+/// contents of `bytes`. The objects are kept alive by `arena` for as long as the
+/// returned value borrows from it.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use pyo3::prelude::*;
+/// use pythonize::{depythonize_borrowed, BorrowArena};
+///
+/// Python::with_gil(|py| {
+///     let obj = py.eval_bound("{'user': 'alice', 'role': 'admin'}", None, None).unwrap();
+///     let arena = BorrowArena::new();
+///     let map: HashMap<&str, &str> = depythonize_borrowed(&obj, &arena).unwrap();
+///     assert_eq!(map["user"], "alice");
+/// })
+/// ```
+pub fn depythonize_borrowed<'de, 'py, T>(
+    obj: &Bound<'py, PyAny>,
+    arena: &'de BorrowArena,
+) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    let mut de = Depythonizer::from_object(obj);
+    de.items.arena = Some(arena);
+    T::deserialize(&mut de)
+}
+
+/// Keeps the Python objects which values deserialized by [`depythonize_borrowed`]
+/// borrow from alive
+///
+/// An arena can be used for any number of values, and frees its objects when it is
+/// dropped.
+#[derive(Default)]
+pub struct BorrowArena {
+    strings: RefCell<Vec<PyBackedStr>>,
+    bytes: RefCell<Vec<PyBackedBytes>>,
+}
+
+impl BorrowArena {
+    /// Creates an empty arena
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps `s` alive for as long as the arena
+    fn alloc_str(&self, s: PyBackedStr) -> &str {
+        let data: *const str = &*s;
+        self.strings.borrow_mut().push(s);
+        // SAFETY: the data of a `PyBackedStr` belongs to the Python object, so it stays
+        // in place when the `PyBackedStr` moves, and the arena never drops its strings
+        // while it is borrowed
+        unsafe { &*data }
+    }
+
+    /// Keeps `b` alive for as long as the arena
+    fn alloc_bytes(&self, b: PyBackedBytes) -> &[u8] {
+        let data: *const [u8] = &*b;
+        self.bytes.borrow_mut().push(b);
+        // SAFETY: as for strings, as long as `b` was made from `bytes` rather than a
+        // copied `bytearray`
+        unsafe { &*data }
+    }
+}
+
 /// Attempt to convert a Python object to an instance of `T`
 #[deprecated(since = "0.22.0", note = "use `depythonize` instead")]
 pub fn depythonize_bound<'py, T>(obj: Bound<'py, PyAny>) -> Result<T>
@@ -21,35 +104,346 @@ where
     T::deserialize(&mut Depythonizer::from_object(&obj))
 }
 
+/// How [`Depythonizer`] treats floats where an integer is expected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatToIntPolicy {
+    /// An error, even if the float has an integral value
+    #[default]
+    Error,
+    /// Accepted if the float has an integral value, such as `3.0`, and otherwise an error
+    Integral,
+    /// Truncated towards zero, so that `3.7` becomes `3`
+    Truncate,
+    /// Rounded to the nearest integer, with halves away from zero, so that `3.5` becomes
+    /// `4`
+    Round,
+}
+
+/// How [`Depythonizer`] treats keys of dicts which aren't fields of the struct they
+/// are deserialized into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownFieldPolicy {
+    /// As the struct declares, so ignored unless it has
+    /// `#[serde(deny_unknown_fields)]`
+    #[default]
+    Declared,
+    /// An error, which names all of the unknown keys
+    Deny,
+    /// Ignored, even if the struct has `#[serde(deny_unknown_fields)]`
+    Ignore,
+}
+
+/// How [`Depythonizer`] matches keys of dicts to the fields of structs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldMatching {
+    /// Keys must be the same as the names of the fields
+    #[default]
+    Exact,
+    /// Keys may differ from the names of the fields in case, so that `Content_Type`
+    /// matches `content_type`
+    IgnoreCase,
+    /// Keys may differ from the names of the fields in case and in `-` and `_`
+    /// separators, so that `Content-Type` and `contenttype` match `content_type`
+    IgnoreCaseAndSeparators,
+}
+
+impl FieldMatching {
+    /// The form of a key or field name which is compared
+    fn normalize(self, name: &str) -> String {
+        match self {
+            FieldMatching::Exact => name.to_owned(),
+            FieldMatching::IgnoreCase => name.to_lowercase(),
+            FieldMatching::IgnoreCaseAndSeparators => name
+                .chars()
+                .filter(|&c| c != '-' && c != '_')
+                .flat_map(char::to_lowercase)
+                .collect(),
+        }
+    }
+}
+
+/// How [`Depythonizer`] converts `decimal.Decimal`s
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecimalPolicy {
+    /// Deserialized as their digits, like `str(d)`, where any type is expected, and
+    /// rounded to the nearest float where a float is expected
+    #[default]
+    String,
+    /// Rounded to the nearest float, like `float(d)`, where a float or any type is
+    /// expected
+    Float,
+    /// As with `Float`, but an error if the float isn't equal to the decimal
+    ExactFloat,
+}
+
+/// How [`Depythonizer`] converts `fractions.Fraction`s
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FractionPolicy {
+    /// Deserialized as a `(numerator, denominator)` pair, the form of
+    /// `num_rational::Ratio`, where any type or a sequence is expected, and an error
+    /// where a float is expected
+    #[default]
+    Ratio,
+    /// Rounded to the nearest float, like `float(f)`, where a float or any type is
+    /// expected, and still a pair where a sequence is expected
+    Float,
+}
+
+/// How [`Depythonizer`] converts members of `enum.Enum` classes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnumMemberPolicy {
+    /// By their `name` where a Rust enum, a string or any type is expected; members of
+    /// `int` and `str` subclasses, such as `enum.IntEnum`, are still their values where
+    /// a number or a string is expected, unless the subclass policy is
+    /// [`SubclassPolicy::Exact`]
+    #[default]
+    Name,
+    /// By their `value` wherever they are found, so that a Rust enum is deserialized
+    /// from the value as from any other object
+    Value,
+}
+
+/// Whether [`Depythonizer`] takes instances of subclasses of `str`, `int`, `float`,
+/// `dict` and `list` as those types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubclassPolicy {
+    /// Accepted like instances of the types themselves, as by `isinstance`
+    #[default]
+    Accept,
+    /// Only accepted if they are numpy scalars, which are deserialized as the equivalent
+    /// builtin, or members of `enum.Enum` classes such as `enum.IntEnum`, which are
+    /// deserialized as other members are; instances of other subclasses are an error
+    Exact,
+}
+
+/// How [`Depythonizer`] treats `int`s which don't fit in the integer type expected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntOverflowPolicy {
+    /// An error, which names the value and the expected type
+    #[default]
+    Error,
+    /// Passed on as an `i128` or `u128`, for types which accept wider integers than
+    /// they ask for
+    Widen,
+    /// Passed on as an `i128` or `u128`, or as a string of decimal digits if even
+    /// those are too small; `int`s are also accepted where a string is expected
+    String,
+}
+
+/// Runtime options of a [`Depythonizer`], set with a [`DepythonizerBuilder`]
+#[derive(Default)]
+struct Options {
+    max_depth: Option<usize>,
+    strict_bool: bool,
+    float_to_int: FloatToIntPolicy,
+    int_overflow: IntOverflowPolicy,
+    unknown_fields: UnknownFieldPolicy,
+    field_matching: FieldMatching,
+    renamed_fields: HashMap<String, String>,
+    field_case: Option<ser::FieldCase>,
+    decimals: DecimalPolicy,
+    fractions: FractionPolicy,
+    enum_members: EnumMemberPolicy,
+    object_attributes: bool,
+    subclasses: SubclassPolicy,
+    numeric_protocols: bool,
+    fill_defaults: bool,
+    fills: RefCell<Fills>,
+    parse_numbers: bool,
+    nan_as_none: bool,
+    conversion_methods: Vec<String>,
+}
+
+/// The fields which the `fill_defaults` option fills, learned from the fields which
+/// serde rejected in earlier attempts, by the type name of the struct's visitor
+#[derive(Default)]
+struct Fills {
+    fields: HashSet<(&'static str, String)>,
+    /// The first missing or `None` field which serde rejected in the current attempt
+    failed: Option<(&'static str, String)>,
+    /// Whether the current attempt took items from an iterator or called one of the
+    /// `conversion_methods`, so that another attempt would find the iterator exhausted or
+    /// repeat the call
+    unrepeatable: bool,
+}
+
+impl Fills {
+    fn contains(&self, ty: &'static str, field: &str) -> bool {
+        self.fields.contains(&(ty, field.to_owned()))
+    }
+
+    fn fail(&mut self, ty: &'static str, field: &str) {
+        if self.failed.is_none() {
+            self.failed = Some((ty, field.to_owned()));
+        }
+    }
+}
+
 /// A structure that deserializes Python objects into Rust values
 pub struct Depythonizer<'a, 'py> {
     input: &'a Bound<'py, PyAny>,
+    items: Items,
+}
+
+/// The options and nesting depth which a [`Depythonizer`] passes on to the
+/// deserializers of the items of a container
+#[derive(Clone, Default)]
+struct Items {
+    options: Rc<Options>,
+    /// How deeply the object being deserialized is nested in Python containers
+    depth: usize,
+    /// The arena of [`depythonize_borrowed`], which is borrowed for the `'de` lifetime
+    /// of the deserializer
+    arena: Option<*const BorrowArena>,
+}
+
+impl Items {
+    /// Creates a deserializer for an object at this depth
+    fn deserializer<'a, 'py>(&self, input: &'a Bound<'py, PyAny>) -> Depythonizer<'a, 'py> {
+        Depythonizer {
+            input,
+            items: self.clone(),
+        }
+    }
+
+    /// The context for the items of a container at this depth, checking the
+    /// `max_depth` option
+    fn nested(&self) -> Result<Self> {
+        let depth = self.depth + 1;
+        match self.options.max_depth {
+            Some(max_depth) if depth > max_depth => Err(PythonizeError::msg(format!(
+                "maximum depth of {} exceeded",
+                max_depth
+            ))),
+            _ => Ok(Self {
+                depth,
+                ..self.clone()
+            }),
+        }
+    }
 }
 
 impl<'a, 'py> Depythonizer<'a, 'py> {
     /// Create a deserializer from a Python object
     pub fn from_object(input: &'a Bound<'py, PyAny>) -> Self {
-        Depythonizer { input }
+        Depythonizer {
+            input,
+            items: Items::default(),
+        }
+    }
+
+    /// Creates a builder to configure a deserializer of `input` with runtime options
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("[[[1]]]", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj).max_depth(2).build();
+    ///     let err = Vec::<Vec<Vec<u8>>>::deserialize(&mut de).unwrap_err();
+    ///     assert_eq!(err.to_string(), "maximum depth of 2 exceeded");
+    /// })
+    /// ```
+    pub fn builder(input: &'a Bound<'py, PyAny>) -> DepythonizerBuilder<'a, 'py> {
+        DepythonizerBuilder {
+            input,
+            options: Options::default(),
+        }
+    }
+
+    /// Deserialize an instance of `T`, like `T::deserialize(&mut depythonizer)`, but
+    /// with the fields which the [`fill_defaults`](DepythonizerBuilder::fill_defaults)
+    /// option fills
+    ///
+    /// serde only reveals that a field has no `#[serde(default)]` by rejecting it when
+    /// it is missing, so each missing or `None` field which is rejected is filled in
+    /// another attempt, until none is. The fields learned this way are kept, so later
+    /// calls fill them in one attempt. An attempt which took items from an iterator,
+    /// such as a generator, or called one of the
+    /// [`conversion_methods`](DepythonizerBuilder::conversion_methods) is not repeated,
+    /// and its error is returned.
+    pub fn deserialize<'de, T>(&mut self) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        loop {
+            let mut fills = self.items.options.fills.borrow_mut();
+            fills.failed = None;
+            fills.unrepeatable = false;
+            drop(fills);
+            let result = T::deserialize(&mut *self);
+            if result.is_ok() || !self.items.options.fill_defaults {
+                return result;
+            }
+            let mut fills = self.items.options.fills.borrow_mut();
+            if fills.unrepeatable {
+                return result;
+            }
+            let filled = match fills.failed.take() {
+                Some(failed) => fills.fields.insert(failed),
+                None => false,
+            };
+            if !filled {
+                return result;
+            }
+        }
+    }
+
+    /// Creates a deserializer for another object at the same depth, such as the Python
+    /// value standing in for `self.input`
+    fn with_input<'b>(&self, input: &'b Bound<'py, PyAny>) -> Depythonizer<'b, 'py> {
+        self.items.deserializer(input)
     }
 
     fn sequence_access(&self, expected_len: Option<usize>) -> Result<PySequenceAccess<'a, 'py>> {
-        let seq = self.input.downcast::<PySequence>()?;
+        let seq = if let Ok(list) = self.input.downcast::<PyList>() {
+            SequenceItems::List(list)
+        } else if let Ok(tuple) = self.input.downcast::<PyTuple>() {
+            SequenceItems::Tuple(tuple)
+        } else {
+            self.input.downcast::<PySequence>()?;
+            SequenceItems::Other(self.input)
+        };
         let len = self.input.len()?;
 
         match expected_len {
             Some(expected) if expected != len => {
                 Err(PythonizeError::incorrect_sequence_length(expected, len))
             }
-            _ => Ok(PySequenceAccess::new(seq, len)),
+            _ => Ok(PySequenceAccess::new(seq, len, self.items.nested()?)),
+        }
+    }
+
+    /// Access to objects which implement `__len__` and `__getitem__` without being
+    /// registered as sequences, once numpy arrays and pyarrow tables have been ruled out
+    fn protocol_sequence_access(
+        &self,
+        expected_len: Option<usize>,
+    ) -> Result<Option<PySequenceAccess<'a, 'py>>> {
+        if !is_sequence_protocol(self.input)? {
+            return Ok(None);
+        }
+        let len = self.input.len()?;
+        match expected_len {
+            Some(expected) if expected != len => {
+                Err(PythonizeError::incorrect_sequence_length(expected, len))
+            }
+            _ => Ok(Some(PySequenceAccess::new(
+                SequenceItems::Other(self.input),
+                len,
+                self.items.nested()?,
+            ))),
         }
     }
 
     fn set_access(&self) -> Result<PySetAsSequence<'py>> {
         match self.input.downcast::<PySet>() {
-            Ok(set) => Ok(PySetAsSequence::from_set(&set)),
+            Ok(set) => Ok(PySetAsSequence::from_set(set, self.items.nested()?)),
             Err(e) => {
                 if let Ok(f) = self.input.downcast::<PyFrozenSet>() {
-                    Ok(PySetAsSequence::from_frozenset(&f))
+                    Ok(PySetAsSequence::from_frozenset(f, self.items.nested()?))
                 } else {
                     Err(e.into())
                 }
@@ -57,11 +451,491 @@ impl<'a, 'py> Depythonizer<'a, 'py> {
         }
     }
 
+    /// The name or value of the input according to the `enum_members` option, if it is a
+    /// member of an `enum.Enum` class
+    fn enum_member(&self) -> Result<Option<Bound<'py, PyAny>>> {
+        Ok(match self.items.options.enum_members {
+            EnumMemberPolicy::Name => {
+                native::enum_member::enum_member_name(self.input)?.map(Bound::into_any)
+            }
+            EnumMemberPolicy::Value => native::enum_member::enum_member_value(self.input)?,
+        })
+    }
+
+    /// The value of the input if it is a member of an `enum.Enum` class and the
+    /// `enum_members` option is `Value`, for targets which names can't stand for
+    fn enum_value(&self) -> Result<Option<Bound<'py, PyAny>>> {
+        match self.items.options.enum_members {
+            EnumMemberPolicy::Name => Ok(None),
+            EnumMemberPolicy::Value => Ok(native::enum_member::enum_member_value(self.input)?),
+        }
+    }
+
+    /// What to deserialize in place of the input under `SubclassPolicy::Exact`, if it is
+    /// an instance of a subclass of `str`, `int`, `float`, `dict` or `list`
+    fn subclass_value(&self) -> Result<Option<Bound<'py, PyAny>>> {
+        if self.items.options.subclasses == SubclassPolicy::Accept {
+            return Ok(None);
+        }
+        let input = self.input;
+        let base = if input.is_exact_instance_of::<PyString>()
+            || input.is_exact_instance_of::<PyInt>()
+            || input.is_exact_instance_of::<PyBool>()
+            || input.is_exact_instance_of::<PyFloat>()
+            || input.is_exact_instance_of::<PyDict>()
+            || input.is_exact_instance_of::<PyList>()
+        {
+            return Ok(None);
+        } else if input.is_instance_of::<PyString>() {
+            "str"
+        } else if input.is_instance_of::<PyInt>() {
+            "int"
+        } else if input.is_instance_of::<PyFloat>() {
+            "float"
+        } else if input.is_instance_of::<PyDict>() {
+            "dict"
+        } else if input.is_instance_of::<PyList>() {
+            "list"
+        } else {
+            return Ok(None);
+        };
+        if let Some(item) = native::numpy::scalar_to_builtin(input)? {
+            return Ok(Some(item));
+        }
+        if let Some(member) = self.enum_member()? {
+            return Ok(Some(member));
+        }
+        Err(PythonizeError::msg(format!(
+            "expected an exact '{}', got an instance of its subclass '{}'",
+            base,
+            input.get_type().qualname()?
+        )))
+    }
+
+    /// The input converted by its `__index__` method, or else by its `__float__` method,
+    /// if it is neither an `int` nor a `float` and the `numeric_protocols` option is set
+    fn numeric_value(&self) -> Result<Option<Bound<'py, PyAny>>> {
+        let input = self.input;
+        if !self.items.options.numeric_protocols
+            || input.is_instance_of::<PyInt>()
+            || input.is_instance_of::<PyFloat>()
+        {
+            return Ok(None);
+        }
+        let py = input.py();
+        if input.hasattr(pyo3::intern!(py, "__index__"))? {
+            return Ok(Some(input.call_method0(pyo3::intern!(py, "__index__"))?));
+        }
+        if input.hasattr(pyo3::intern!(py, "__float__"))? {
+            let x: f64 = input
+                .call_method0(pyo3::intern!(py, "__float__"))?
+                .extract()?;
+            return Ok(Some(PyFloat::new_bound(py, x).into_any()));
+        }
+        Ok(None)
+    }
+
+    /// The result of calling the first of the `conversion_methods` which the input has,
+    /// for objects which can't be converted otherwise
+    fn converted(&self) -> Result<Option<Bound<'py, PyAny>>> {
+        let methods = &self.items.options.conversion_methods;
+        if methods.is_empty() || self.input.is_instance_of::<PyType>() {
+            return Ok(None);
+        }
+        for name in methods {
+            if self.input.hasattr(name.as_str())? {
+                self.items.options.fills.borrow_mut().unrepeatable = true;
+                return Ok(Some(self.input.call_method0(name.as_str())?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The fields of the input if it is a record, such as a dataclass instance, or any
+    /// object with attributes if the `object_attributes` option is set
+    fn record_fields(&self) -> Result<Option<Bound<'py, PyDict>>> {
+        if let Some(fields) = native::records::record_fields(self.input)? {
+            return Ok(Some(fields));
+        }
+        if self.items.options.object_attributes {
+            return Ok(native::records::attribute_fields(self.input)?);
+        }
+        Ok(None)
+    }
+
+    /// The `(real, imag)` pair of the input if it is a `complex`, or its `(numerator,
+    /// denominator)` pair if it is a `fractions.Fraction`
+    fn number_pair(&self) -> Result<Option<Bound<'py, PyTuple>>> {
+        if let Ok(c) = self.input.downcast::<PyComplex>() {
+            return Ok(Some(PyTuple::new_bound(
+                self.input.py(),
+                [c.real(), c.imag()],
+            )));
+        }
+        if native::text::fraction_from_python(self.input)?.is_none() {
+            return Ok(None);
+        }
+        let numerator = self.input.getattr("numerator")?;
+        let denominator = self.input.getattr("denominator")?;
+        Ok(Some(PyTuple::new_bound(
+            self.input.py(),
+            [numerator, denominator],
+        )))
+    }
+
+    /// Access to the items of the input if it is iterable, but not a mapping, whose
+    /// keys would be iterated
+    fn iterable_access(&self) -> Result<Option<PySetAsSequence<'py>>> {
+        let py = self.input.py();
+        if self.input.downcast::<PyMapping>().is_ok()
+            || self.input.hasattr(pyo3::intern!(py, "keys"))?
+        {
+            return Ok(None);
+        }
+        match PyIterator::from_bound_object(self.input) {
+            Ok(iter) => {
+                // an iterator is its own iterator, and can only be iterated once
+                if iter.is(self.input) {
+                    self.items.options.fills.borrow_mut().unrepeatable = true;
+                }
+                Ok(Some(PySetAsSequence::from_iterator(
+                    iter,
+                    self.items.nested()?,
+                )))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
     fn dict_access(&self) -> Result<PyMappingAccess<'py>> {
-        PyMappingAccess::new(self.input.downcast()?)
+        match self.input.downcast::<PyMapping>() {
+            Ok(map) => PyMappingAccess::new(map, self.items.nested()?),
+            Err(e) => match mapping_protocol_dict(self.input)? {
+                Some(dict) => PyMappingAccess::new(dict.as_mapping(), self.items.nested()?),
+                None => match self.record_fields()? {
+                    Some(fields) => PyMappingAccess::new(fields.as_mapping(), self.items.nested()?),
+                    None => match self.converted()? {
+                        Some(converted) => self.with_input(&converted).dict_access(),
+                        None => Err(e.into()),
+                    },
+                },
+            },
+        }
+    }
+
+    /// Access to the fields of a struct, whose unknown keys are treated according to the
+    /// `unknown_fields` option; `ty` is the type name of the struct's visitor
+    fn struct_access(
+        &self,
+        ty: &'static str,
+        fields: &'static [&'static str],
+    ) -> Result<PyMappingAccess<'py>> {
+        let mut access = self.dict_access()?;
+        let renamed_fields = &self.items.options.renamed_fields;
+        if !renamed_fields.is_empty() {
+            access.rename_keys(|key| {
+                Ok(match key.downcast::<PyString>() {
+                    Ok(key) => renamed_fields.get(&*key.to_cow()?).cloned(),
+                    Err(_) => None,
+                })
+            })?;
+        }
+        if let Some(case) = self.items.options.field_case {
+            let cased: Vec<_> = fields.iter().map(|field| case.apply(field)).collect();
+            access.rename_keys(|key| {
+                let key = match key.downcast::<PyString>() {
+                    Ok(key) => key.to_cow()?,
+                    Err(_) => return Ok(None),
+                };
+                if fields.contains(&&*key) {
+                    return Ok(None);
+                }
+                Ok(cased
+                    .iter()
+                    .position(|field| *field == key)
+                    .map(|index| fields[index].to_owned()))
+            })?;
+        }
+        let matching = self.items.options.field_matching;
+        if matching != FieldMatching::Exact {
+            let normalized: Vec<_> = fields.iter().map(|f| matching.normalize(f)).collect();
+            access.rename_keys(|key| {
+                let key = match key.downcast::<PyString>() {
+                    Ok(key) => key.to_cow()?,
+                    Err(_) => return Ok(None),
+                };
+                if fields.contains(&&*key) {
+                    return Ok(None);
+                }
+                let key = matching.normalize(&key);
+                Ok(normalized
+                    .iter()
+                    .position(|field| *field == key)
+                    .map(|index| fields[index].to_owned()))
+            })?;
+        }
+        let is_field = |key: &Bound<'_, PyAny>| -> Result<bool> {
+            Ok(match key.downcast::<PyString>() {
+                Ok(key) => fields.contains(&&*key.to_cow()?),
+                Err(_) => false,
+            })
+        };
+        match self.items.options.unknown_fields {
+            UnknownFieldPolicy::Declared => {}
+            UnknownFieldPolicy::Deny => {
+                let mut unknown = Vec::new();
+                for key in access.keys.iter()? {
+                    let key = key?;
+                    if !is_field(&key)? {
+                        unknown.push(format!("`{}`", key.str()?.to_cow()?));
+                    }
+                }
+                if !unknown.is_empty() {
+                    return Err(PythonizeError::unknown_fields(&unknown, fields));
+                }
+            }
+            UnknownFieldPolicy::Ignore => access.retain(is_field)?,
+        }
+        if self.items.options.fill_defaults {
+            access.fill_missing(ty, fields)?;
+        }
+        Ok(access)
+    }
+
+    /// Visits the fields of a struct, noting a field which serde found missing for the
+    /// `fill_defaults` option to fill in the next attempt
+    fn visit_struct<'de, V>(
+        &self,
+        fields: &'static [&'static str],
+        access: PyMappingAccess<'py>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let result = visitor.visit_map(access);
+        if let (Err(err), true) = (&result, self.items.options.fill_defaults) {
+            if let Some(field) = err.missing_field().filter(|field| fields.contains(field)) {
+                let mut fills = self.items.options.fills.borrow_mut();
+                fills.fail(type_name::<V>(), field);
+            }
+        }
+        result
+    }
+
+    /// Deserialize the contents of a `numpy.ndarray` as a sequence, checking its length
+    /// against `expected_len` if given; the elements of structured arrays are maps of
+    /// their fields
+    fn deserialize_array<'de, V>(
+        &self,
+        contents: ArrayContents<'_>,
+        expected_len: Option<usize>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (shape, elements) = match contents {
+            ArrayContents::Numeric(shape, elements) => (shape, elements),
+            ArrayContents::List(list) => {
+                let mut de = self.with_input(&list);
+                return match expected_len {
+                    Some(len) => de::Deserializer::deserialize_tuple(&mut de, len, visitor),
+                    None => de::Deserializer::deserialize_seq(&mut de, visitor),
+                };
+            }
+            ArrayContents::Records(len, columns) => {
+                if let Some(expected) = expected_len {
+                    if expected != len {
+                        return Err(PythonizeError::incorrect_sequence_length(expected, len));
+                    }
+                }
+                let items = self.items.nested()?;
+                let rows = (0..len).map(|row| RecordRow::new(&columns, row, &items));
+                let mut seq = SeqDeserializer::new(rows);
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                return Ok(value);
+            }
+        };
+        if let Some(expected) = expected_len {
+            if expected != shape[0] {
+                return Err(PythonizeError::incorrect_sequence_length(
+                    expected, shape[0],
+                ));
+            }
+        }
+        match elements {
+            Elements::Bool(elements) => ArrayElements::new(&shape, &elements).visit(visitor),
+            Elements::Int(elements) => ArrayElements::new(&shape, &elements).visit(visitor),
+            Elements::UInt(elements) => ArrayElements::new(&shape, &elements).visit(visitor),
+            Elements::Float(elements) => ArrayElements::new(&shape, &elements).visit(visitor),
+        }
+    }
+
+    /// The contents of the input if it is an `array.array`, a `memoryview` or a byte
+    /// string, which are copied at once rather than taken item by item like those of
+    /// other sequences
+    fn std_array_contents(&self) -> Result<Option<ArrayContents<'py>>> {
+        if self.input.is_instance_of::<PyList>() || self.input.is_instance_of::<PyTuple>() {
+            return Ok(None);
+        }
+        if let Some(contents) = native::array::buffer_contents(self.input)? {
+            return Ok(Some(contents));
+        }
+        Ok(native::array::array_contents(self.input)?)
+    }
+
+    /// The input as an integer, which is not a `bool` if `strict_bool` is set, converting
+    /// floats according to the `float_to_int` option
+    fn int(&self) -> Result<Bound<'py, PyAny>> {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).int();
+        }
+        if !self.input.is_instance_of::<PyInt>() {
+            if let Some(value) = self.enum_value()? {
+                return self.with_input(&value).int();
+            }
+        }
+        let options = &self.items.options;
+        if let Some(value) = self.numeric_value()? {
+            if options.float_to_int == FloatToIntPolicy::Error {
+                if let Ok(float) = value.downcast::<PyFloat>() {
+                    // the conversion was asked for, so integral values are taken as
+                    // they are with `FloatToIntPolicy::Integral`
+                    let x = float.value();
+                    if x.fract() != 0.0 {
+                        return Err(PythonizeError::msg(format!(
+                            "expected an integral number, got {}",
+                            x
+                        )));
+                    }
+                    return Ok(self.input.py().get_type_bound::<PyInt>().call1((x,))?);
+                }
+            }
+            return self.with_input(&value).int();
+        }
+        if options.parse_numbers {
+            if let Ok(text) = self.input.downcast::<PyString>() {
+                let text = text.to_cow()?;
+                if let Err(err) = text.parse::<i128>() {
+                    if text.parse::<u128>().is_err() {
+                        return Err(PythonizeError::msg(format!(
+                            "invalid integer {:?}: {}",
+                            text, err
+                        )));
+                    }
+                }
+                return Ok(self.input.py().get_type_bound::<PyInt>().call1((text,))?);
+            }
+        }
+        if options.strict_bool && self.input.is_instance_of::<PyBool>() {
+            return Err(PythonizeError::msg("expected an int, got a bool"));
+        }
+        if options.float_to_int != FloatToIntPolicy::Error {
+            if let Ok(float) = self.input.downcast::<PyFloat>() {
+                let x = float.value();
+                let x = match options.float_to_int {
+                    FloatToIntPolicy::Integral if x.fract() != 0.0 => {
+                        return Err(PythonizeError::msg(format!(
+                            "expected an integral float, got {}",
+                            x
+                        )));
+                    }
+                    FloatToIntPolicy::Truncate => x.trunc(),
+                    FloatToIntPolicy::Round => x.round(),
+                    _ => x,
+                };
+                // `int` rejects NaN and infinity
+                return Ok(self.input.py().get_type_bound::<PyInt>().call1((x,))?);
+            }
+        }
+        Ok(self.input.clone())
+    }
+
+    /// The input as a float, rounded to the precision of the type expected by `round`,
+    /// which is an error for decimals that change with the `ExactFloat` policy, and for
+    /// fractions unless the policy is `Float`
+    fn float(&self, round: fn(f64) -> f64) -> Result<f64> {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).float(round);
+        }
+        if self.input.is_instance_of::<PyFloat>() {
+            return Ok(round(self.input.extract()?));
+        }
+        if let Some(value) = self.enum_value()? {
+            return self.with_input(&value).float(round);
+        }
+        if self.items.options.parse_numbers {
+            if let Ok(text) = self.input.downcast::<PyString>() {
+                let text = text.to_cow()?;
+                return match text.parse::<f64>() {
+                    Ok(x) => Ok(round(x)),
+                    Err(err) => Err(PythonizeError::msg(format!(
+                        "invalid float {:?}: {}",
+                        text, err
+                    ))),
+                };
+            }
+        }
+        if self.items.options.fractions != FractionPolicy::Float {
+            if let Some(text) = native::text::fraction_from_python(self.input)? {
+                return Err(PythonizeError::msg(format!(
+                    "fraction {} can't be converted into a float without `FractionPolicy::Float`",
+                    text
+                )));
+            }
+        }
+        let x = round(self.input.extract()?);
+        if self.items.options.decimals != DecimalPolicy::ExactFloat {
+            return Ok(x);
+        }
+        if let Some(digits) = native::text::decimal_from_python(self.input)? {
+            let exact = if x.is_finite() {
+                self.input.get_type().call1((x,))?.eq(self.input)?
+            } else {
+                !self.input.call_method0("is_finite")?.is_truthy()?
+            };
+            if !exact {
+                return Err(PythonizeError::msg(format!(
+                    "decimal {} can't be converted into a float exactly",
+                    digits
+                )));
+            }
+        }
+        Ok(x)
+    }
+
+    /// Deserializes an integer of type `T`, whose `visit` method is passed to the visitor
+    fn deserialize_int<'de, T, V>(
+        &self,
+        visitor: V,
+        visit: fn(V, T) -> Result<V::Value>,
+    ) -> Result<V::Value>
+    where
+        T: for<'b> FromPyObject<'b>,
+        V: de::Visitor<'de>,
+    {
+        let int = self.int()?;
+        match int.extract() {
+            Ok(x) => visit(visitor, x),
+            Err(err) if err.is_instance_of::<PyOverflowError>(int.py()) => {
+                match self.items.options.int_overflow {
+                    IntOverflowPolicy::Error => Err(PythonizeError::msg(format!(
+                        "int {} is out of range for {}",
+                        int,
+                        std::any::type_name::<T>()
+                    ))),
+                    IntOverflowPolicy::Widen | IntOverflowPolicy::String => {
+                        self.deserialize_any_int(&int, visitor)
+                    }
+                }
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 
-    fn deserialize_any_int<'de, V>(&self, int: &Bound<'_, PyInt>, visitor: V) -> Result<V::Value>
+    fn deserialize_any_int<'de, V>(&self, int: &Bound<'_, PyAny>, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
@@ -77,8 +951,7 @@ impl<'a, 'py> Depythonizer<'a, 'py> {
             } else {
                 visitor.visit_u128(x)
             }
-        } else {
-            let x: i128 = int.extract()?;
+        } else if let Ok(x) = int.extract::<i128>() {
             if let Ok(x) = i8::try_from(x) {
                 visitor.visit_i8(x)
             } else if let Ok(x) = i16::try_from(x) {
@@ -90,17 +963,24 @@ impl<'a, 'py> Depythonizer<'a, 'py> {
             } else {
                 visitor.visit_i128(x)
             }
+        } else if self.items.options.int_overflow == IntOverflowPolicy::String {
+            visitor.visit_str(&int.str()?.to_cow()?)
+        } else {
+            Err(PythonizeError::msg(format!(
+                "int {} is out of range for i128 and u128",
+                int
+            )))
         }
     }
 }
 
-macro_rules! deserialize_type {
+macro_rules! deserialize_int {
     ($method:ident => $visit:ident) => {
         fn $method<V>(self, visitor: V) -> Result<V::Value>
         where
             V: de::Visitor<'de>,
         {
-            visitor.$visit(self.input.extract()?)
+            self.deserialize_int(visitor, V::$visit)
         }
     };
 }
@@ -113,6 +993,9 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
         V: de::Visitor<'de>,
     {
         let obj = self.input;
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_any(visitor);
+        }
 
         // First check for cases which are cheap to check due to pointer
         // comparison or bitflag checks
@@ -135,12 +1018,53 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
             self.deserialize_bytes(visitor)
         } else if obj.is_instance_of::<PyFloat>() {
             self.deserialize_f64(visitor)
+        } else if obj.is_instance_of::<PyComplex>() {
+            self.deserialize_tuple(2, visitor)
         } else if obj.is_instance_of::<PyFrozenSet>() || obj.is_instance_of::<PySet>() {
             self.deserialize_seq(visitor)
         } else if obj.downcast::<PySequence>().is_ok() {
             self.deserialize_tuple(obj.len()?, visitor)
         } else if obj.downcast::<PyMapping>().is_ok() {
             self.deserialize_map(visitor)
+        } else if let Some(contents) = native::numpy::array_contents(obj)? {
+            self.deserialize_array(contents, None, visitor)
+        } else if let Some(rows) = native::pyarrow::table_rows(obj)? {
+            self.with_input(&rows).deserialize_any(visitor)
+        } else if let Some(item) = native::numpy::scalar_to_builtin(obj)? {
+            self.with_input(&item).deserialize_any(visitor)
+        } else if native::pandas::is_missing(obj)? {
+            // `pandas.NaT` or `pandas.NA`
+            visitor.visit_unit()
+        } else if let Some(text) = native::text::uuid_text(obj)? {
+            visitor.visit_str(&text)
+        } else if let Some(path) = native::text::path_from_python(obj)? {
+            self.with_input(&path).deserialize_any(visitor)
+        } else if let Some(text) = native::ipaddress::ip_object_text(obj)? {
+            visitor.visit_str(&text.to_cow()?)
+        } else if let Some(member) = self.enum_member()? {
+            self.with_input(&member).deserialize_any(visitor)
+        } else if is_sequence_protocol(obj)? {
+            self.deserialize_tuple(obj.len()?, visitor)
+        } else if obj.downcast::<PyIterator>().is_ok() {
+            self.deserialize_seq(visitor)
+        } else if let Some(dict) = mapping_protocol_dict(obj)? {
+            self.with_input(&dict).deserialize_map(visitor)
+        } else if let Some(fields) = self.record_fields()? {
+            self.with_input(&fields).deserialize_map(visitor)
+        } else if let Some(digits) = native::text::decimal_from_python(obj)? {
+            match self.items.options.decimals {
+                DecimalPolicy::String => visitor.visit_str(&digits.str()?.to_cow()?),
+                DecimalPolicy::Float | DecimalPolicy::ExactFloat => self.deserialize_f64(visitor),
+            }
+        } else if let Some(pair) = self.number_pair()? {
+            match self.items.options.fractions {
+                FractionPolicy::Ratio => self.with_input(&pair).deserialize_tuple(2, visitor),
+                FractionPolicy::Float => self.deserialize_f64(visitor),
+            }
+        } else if let Some(number) = self.numeric_value()? {
+            self.with_input(&number).deserialize_any(visitor)
+        } else if let Some(converted) = self.converted()? {
+            self.with_input(&converted).deserialize_any(visitor)
         } else {
             Err(obj.get_type().qualname().map_or_else(
                 |_| PythonizeError::unsupported_type("unknown"),
@@ -153,6 +1077,14 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
+        if !self.input.is_instance_of::<PyBool>() {
+            if let Some(value) = self.enum_value()? {
+                return self.with_input(&value).deserialize_bool(visitor);
+            }
+        }
+        if self.items.options.strict_bool {
+            return visitor.visit_bool(self.input.downcast::<PyBool>()?.is_true());
+        }
         visitor.visit_bool(self.input.is_truthy()?)
     }
 
@@ -160,6 +1092,9 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_char(visitor);
+        }
         let s = self.input.downcast::<PyString>()?.to_cow()?;
         if s.len() != 1 {
             return Err(PythonizeError::invalid_length_char());
@@ -167,24 +1102,67 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
         visitor.visit_char(s.chars().next().unwrap())
     }
 
-    deserialize_type!(deserialize_i8 => visit_i8);
-    deserialize_type!(deserialize_i16 => visit_i16);
-    deserialize_type!(deserialize_i32 => visit_i32);
-    deserialize_type!(deserialize_i64 => visit_i64);
-    deserialize_type!(deserialize_i128 => visit_i128);
-    deserialize_type!(deserialize_u8 => visit_u8);
-    deserialize_type!(deserialize_u16 => visit_u16);
-    deserialize_type!(deserialize_u32 => visit_u32);
-    deserialize_type!(deserialize_u64 => visit_u64);
-    deserialize_type!(deserialize_u128 => visit_u128);
-    deserialize_type!(deserialize_f32 => visit_f32);
-    deserialize_type!(deserialize_f64 => visit_f64);
+    deserialize_int!(deserialize_i8 => visit_i8);
+    deserialize_int!(deserialize_i16 => visit_i16);
+    deserialize_int!(deserialize_i32 => visit_i32);
+    deserialize_int!(deserialize_i64 => visit_i64);
+    deserialize_int!(deserialize_i128 => visit_i128);
+    deserialize_int!(deserialize_u8 => visit_u8);
+    deserialize_int!(deserialize_u16 => visit_u16);
+    deserialize_int!(deserialize_u32 => visit_u32);
+    deserialize_int!(deserialize_u64 => visit_u64);
+    deserialize_int!(deserialize_u128 => visit_u128);
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f32(self.float(|x| x as f32 as f64)? as f32)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_f64(self.float(|x| x)?)
+    }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if self.items.options.int_overflow == IntOverflowPolicy::String
+            && self.input.is_exact_instance_of::<PyInt>()
+        {
+            return visitor.visit_str(&self.input.str()?.to_cow()?);
+        }
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_str(visitor);
+        }
+        if !self.input.is_instance_of::<PyString>() {
+            if let Some(text) = native::text::uuid_text(self.input)? {
+                return visitor.visit_str(&text);
+            }
+            // `os.fspath` of an `os.PathLike`, such as a `pathlib.Path` or `os.DirEntry`
+            if let Some(path) = native::text::path_from_python(self.input)? {
+                return self.with_input(&path).deserialize_str(visitor);
+            }
+            if let Some(text) = native::ipaddress::ip_object_text(self.input)? {
+                return visitor.visit_str(&text.to_cow()?);
+            }
+            if let Some(member) = self.enum_member()? {
+                return self.with_input(&member).deserialize_str(visitor);
+            }
+            if let Some(digits) = native::text::decimal_from_python(self.input)? {
+                return visitor.visit_str(&digits.str()?.to_cow()?);
+            }
+        }
         let s = self.input.downcast::<PyString>()?;
+        if let Some(arena) = self.items.arena {
+            // SAFETY: only `depythonize_borrowed` sets the arena, which it borrows for
+            // the `'de` lifetime of the value it deserializes
+            let arena: &'de BorrowArena = unsafe { &*arena };
+            return visitor.visit_borrowed_str(arena.alloc_str(s.extract()?));
+        }
         visitor.visit_str(&s.to_cow()?)
     }
 
@@ -199,8 +1177,27 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
-        let b = self.input.downcast::<PyBytes>()?;
-        visitor.visit_bytes(b.as_bytes())
+        if let Ok(b) = self.input.downcast::<PyBytes>() {
+            if let Some(arena) = self.items.arena {
+                // SAFETY: as in `deserialize_str`
+                let arena: &'de BorrowArena = unsafe { &*arena };
+                return visitor.visit_borrowed_bytes(arena.alloc_bytes(b.clone().into()));
+            }
+            return visitor.visit_bytes(b.as_bytes());
+        }
+        if let Ok(b) = self.input.downcast::<PyByteArray>() {
+            return visitor.visit_byte_buf(b.to_vec());
+        }
+        // other objects supporting the buffer protocol, such as `memoryview`s and numpy
+        // arrays, may change once Python code runs, so their contents are copied
+        match buffer_to_vec(self.input) {
+            Ok(b) => visitor.visit_byte_buf(b),
+            Err(_) => match native::text::uuid_value(self.input)? {
+                // the 16 bytes of `uuid.UUID.bytes`, in big-endian order
+                Some(value) => visitor.visit_bytes(&value.to_be_bytes()),
+                None => Err(self.input.downcast::<PyBytes>().unwrap_err().into()),
+            },
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
@@ -214,7 +1211,9 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
-        if self.input.is_none() {
+        let nan = self.items.options.nan_as_none
+            && matches!(self.input.downcast::<PyFloat>(), Ok(float) if float.value().is_nan());
+        if self.input.is_none() || nan || native::pandas::is_missing(self.input)? {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -232,17 +1231,37 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
         }
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        // accept each representation of `UnitStructPolicy`
+        let input = self.input;
+        let is_empty = (input.is_instance_of::<PyTuple>()
+            || input.is_instance_of::<PyList>()
+            || input.downcast::<PyMapping>().is_ok())
+            && input.len()? == 0;
+        if is_empty || ser::is_unit_struct_sentinel(input, name)? {
+            return visitor.visit_unit();
+        }
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        #[cfg(feature = "polars")]
+        if name == native::DATAFRAME_TOKEN || name == native::SERIES_TOKEN {
+            if let Some(frame) = native::polars::frame_from_python(self.input, name)? {
+                return native::polars::with_frame(frame, || visitor.visit_newtype_struct(self));
+            }
+        }
+        if let Some(kind) = native::Kind::from_token(name) {
+            if let Some(payload) = kind.decompose(self.input)? {
+                return visitor.visit_newtype_struct(&mut self.with_input(&payload));
+            }
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -250,14 +1269,38 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_seq(visitor);
+        }
+        if let Some(contents) = self.std_array_contents()? {
+            return self.deserialize_array(contents, None, visitor);
+        }
         match self.sequence_access(None) {
             Ok(seq) => visitor.visit_seq(seq),
             Err(e) => {
-                // we allow sets to be deserialized as sequences, so try that
+                // we allow sets, numpy arrays and the rows of pyarrow tables to be
+                // deserialized as sequences, so try that
                 if matches!(*e.inner, ErrorImpl::UnexpectedType(_)) {
                     if let Ok(set) = self.set_access() {
                         return visitor.visit_seq(set);
                     }
+                    if let Some(contents) = native::numpy::array_contents(self.input)? {
+                        return self.deserialize_array(contents, None, visitor);
+                    }
+                    if let Some(rows) = native::pyarrow::table_rows(self.input)? {
+                        return self.with_input(&rows).deserialize_seq(visitor);
+                    }
+                    if let Some(pair) = self.number_pair()? {
+                        return self.with_input(&pair).deserialize_seq(visitor);
+                    }
+                    if let Some(seq) = self.protocol_sequence_access(None)? {
+                        return visitor.visit_seq(seq);
+                    }
+                    // other iterables, such as generators, are consumed without knowing
+                    // their length
+                    if let Some(iter) = self.iterable_access()? {
+                        return visitor.visit_seq(iter);
+                    }
                 }
                 Err(e)
             }
@@ -268,9 +1311,42 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_seq(self.sequence_access(Some(len))?)
-    }
-
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_tuple(len, visitor);
+        }
+        if let Some(contents) = self.std_array_contents()? {
+            return self.deserialize_array(contents, Some(len), visitor);
+        }
+        match self.sequence_access(Some(len)) {
+            Ok(seq) => visitor.visit_seq(seq),
+            Err(e) => {
+                if matches!(*e.inner, ErrorImpl::UnexpectedType(_)) {
+                    if let Ok(set) = self.set_access() {
+                        // the items are in whatever order the set iterates in
+                        let actual = self.input.len()?;
+                        if actual != len {
+                            return Err(PythonizeError::incorrect_sequence_length(len, actual));
+                        }
+                        return visitor.visit_seq(set);
+                    }
+                    if let Some(contents) = native::numpy::array_contents(self.input)? {
+                        return self.deserialize_array(contents, Some(len), visitor);
+                    }
+                    if let Some(rows) = native::pyarrow::table_rows(self.input)? {
+                        return self.with_input(&rows).deserialize_tuple(len, visitor);
+                    }
+                    if let Some(pair) = self.number_pair()? {
+                        return self.with_input(&pair).deserialize_tuple(len, visitor);
+                    }
+                    if let Some(seq) = self.protocol_sequence_access(Some(len))? {
+                        return visitor.visit_seq(seq);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
@@ -280,39 +1356,68 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_seq(self.sequence_access(Some(len))?)
+        self.deserialize_tuple(len, visitor)
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_map(visitor);
+        }
         visitor.visit_map(self.dict_access()?)
     }
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        if let Some(value) = self.subclass_value()? {
+            return self
+                .with_input(&value)
+                .deserialize_struct(name, fields, visitor);
+        }
+        match self.struct_access(type_name::<V>(), fields) {
+            Ok(access) => self.visit_struct(fields, access, visitor),
+            Err(e) => {
+                // numbers with two parts are deserialized as structs with two fields,
+                // such as `re` and `im`
+                if matches!(*e.inner, ErrorImpl::UnexpectedType(_)) {
+                    if let Some(pair) = self.number_pair()? {
+                        return self.with_input(&pair).deserialize_seq(visitor);
+                    }
+                }
+                Err(e)
+            }
+        }
     }
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if let Some(value) = self.subclass_value()? {
+            return self
+                .with_input(&value)
+                .deserialize_enum(name, variants, visitor);
+        }
         let item = &self.input;
-        if let Ok(s) = item.downcast::<PyString>() {
+        if let Some(member) = self.enum_member()? {
+            // before strings, which members of `str` subclasses such as `enum.StrEnum` are
+            self.with_input(&member)
+                .deserialize_enum(name, variants, visitor)
+        } else if let Ok(s) = item.downcast::<PyString>() {
             visitor.visit_enum(s.to_cow()?.into_deserializer())
         } else if let Ok(m) = item.downcast::<PyMapping>() {
             // Get the enum variant from the mapping key
@@ -325,7 +1430,21 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
                 .downcast_into::<PyString>()
                 .map_err(|_| PythonizeError::dict_key_not_string())?;
             let value = m.get_item(&variant)?;
-            visitor.visit_enum(PyEnumAccess::new(&value, variant))
+            // a unit variant may be mapped to from the name of the enum instead
+            if variant.to_cow()? == name && !variants.contains(&name) {
+                if let Ok(s) = value.downcast::<PyString>() {
+                    return visitor.visit_enum(s.to_cow()?.into_deserializer());
+                }
+            }
+            let de = self.items.nested()?.deserializer(&value);
+            visitor.visit_enum(PyEnumAccess::new(de, variant))
+        } else if item.is_instance_of::<PyBool>() {
+            // `bool`s are `int`s, but `True` isn't meant as the second variant
+            Err(PythonizeError::invalid_enum_type())
+        } else if item.is_instance_of::<PyInt>() {
+            // the index of a unit variant
+            let index = self.int()?;
+            visitor.visit_enum(index.extract::<u32>()?.into_deserializer())
         } else {
             Err(PythonizeError::invalid_enum_type())
         }
@@ -335,6 +1454,9 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     where
         V: de::Visitor<'de>,
     {
+        if let Some(value) = self.subclass_value()? {
+            return self.with_input(&value).deserialize_identifier(visitor);
+        }
         let s = self
             .input
             .downcast::<PyString>()
@@ -350,15 +1472,616 @@ impl<'de> de::Deserializer<'de> for &'_ mut Depythonizer<'_, '_> {
     }
 }
 
+/// A builder for a [`Depythonizer`] configured with runtime options, created by
+/// [`Depythonizer::builder`]
+///
+/// The options apply to the items of containers as well as the object being
+/// deserialized.
+pub struct DepythonizerBuilder<'a, 'py> {
+    input: &'a Bound<'py, PyAny>,
+    options: Options,
+}
+
+impl<'a, 'py> DepythonizerBuilder<'a, 'py> {
+    /// Set how deeply Python containers such as lists, dicts and tuples may be nested,
+    /// unlimited by default
+    ///
+    /// Deeper objects are an error, which guards against running out of stack on
+    /// pathological inputs such as a list which contains itself. Scalars can still be
+    /// deserialized with a maximum depth of 0.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Set whether objects which aren't mappings, sequences or any other supported type
+    /// are deserialized as maps of their attributes, which is off by default
+    ///
+    /// The attributes are those in the object's `__dict__`, as returned by `vars(obj)`,
+    /// and the `__slots__` of its classes, so that instances of plain Python classes can
+    /// be deserialized into structs.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Account {
+    ///     owner: String,
+    ///     balance: i64,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let locals = pyo3::types::PyDict::new_bound(py);
+    ///     py.run_bound(
+    ///         "class Account:\n    def __init__(self):\n        self.owner = 'ann'\n        self.balance = 10",
+    ///         Some(&locals),
+    ///         None,
+    ///     )
+    ///     .unwrap();
+    ///     let obj = py.eval_bound("Account()", Some(&locals), None).unwrap();
+    ///     assert!(Account::deserialize(&mut Depythonizer::from_object(&obj)).is_err());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj).object_attributes(true).build();
+    ///     let account = Account::deserialize(&mut de).unwrap();
+    ///     assert_eq!(account.owner, "ann");
+    /// })
+    /// ```
+    pub fn object_attributes(mut self, object_attributes: bool) -> Self {
+        self.options.object_attributes = object_attributes;
+        self
+    }
+
+    /// Set whether `bool`s and `int`s are kept apart, which is off by default
+    ///
+    /// Python's `bool` is a subclass of `int`, so `True` is normally accepted as the
+    /// integer `1`, and any object is accepted as a `bool` by its truthiness. With this
+    /// option, integers can't be deserialized from `bool`s and `bool`s can only be
+    /// deserialized from `True` and `False`.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("True", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj).strict_bool(true).build();
+    ///     assert!(u8::deserialize(&mut de).is_err());
+    /// })
+    /// ```
+    pub fn strict_bool(mut self, strict_bool: bool) -> Self {
+        self.options.strict_bool = strict_bool;
+        self
+    }
+
+    /// Set how floats are converted where an integer is expected, by default an error
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, FloatToIntPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("3.7", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .float_to_int(FloatToIntPolicy::Round)
+    ///         .build();
+    ///     assert_eq!(i64::deserialize(&mut de).unwrap(), 4);
+    /// })
+    /// ```
+    pub fn float_to_int(mut self, policy: FloatToIntPolicy) -> Self {
+        self.options.float_to_int = policy;
+        self
+    }
+
+    /// Set whether objects which are neither `int`s nor `float`s are converted by their
+    /// `__index__` or `__float__` methods where a number is expected, which is off by
+    /// default
+    ///
+    /// Objects with an `__index__` method are always accepted where an integer or a
+    /// float is expected, and those with a `__float__` method where a float is expected.
+    /// With this option, objects with only a `__float__` method, such as
+    /// `decimal.Decimal`s, are also accepted where an integer is expected if they are
+    /// integral or the [`FloatToIntPolicy`] allows it, and objects of unsupported types
+    /// with either method are deserialized as numbers where any type is expected.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py
+    ///         .eval_bound("__import__('decimal').Decimal('42')", None, None)
+    ///         .unwrap();
+    ///     assert!(u32::deserialize(&mut Depythonizer::from_object(&obj)).is_err());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj).numeric_protocols(true).build();
+    ///     assert_eq!(u32::deserialize(&mut de).unwrap(), 42);
+    /// })
+    /// ```
+    pub fn numeric_protocols(mut self, numeric_protocols: bool) -> Self {
+        self.options.numeric_protocols = numeric_protocols;
+        self
+    }
+
+    /// Set whether the fields of structs which are missing or `None` are filled with the
+    /// value `#[derive(Default)]` would give them, which is off by default
+    ///
+    /// This is zero, `false`, an empty string or collection, or `None`, and a struct of
+    /// such values, so that sparse config dicts can be deserialized without a
+    /// `#[serde(default)]` on every field. Enums have no such value. Only fields which
+    /// serde would reject are filled, so `#[serde(default)]` still applies to the fields
+    /// which have it. serde only reveals which fields those are by rejecting them, so
+    /// they are learned by [`Depythonizer::deserialize`], which deserializes again to
+    /// fill them; `T::deserialize` only fills the fields learned by earlier calls.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Config {
+    ///     name: String,
+    ///     retries: u32,
+    ///     hosts: Vec<String>,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("{'name': 'app', 'hosts': None}", None, None).unwrap();
+    ///     assert!(Config::deserialize(&mut Depythonizer::from_object(&obj)).is_err());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj).fill_defaults(true).build();
+    ///     let config: Config = de.deserialize().unwrap();
+    ///     assert_eq!(config.retries, 0);
+    ///     assert!(config.hosts.is_empty());
+    /// })
+    /// ```
+    pub fn fill_defaults(mut self, fill_defaults: bool) -> Self {
+        self.options.fill_defaults = fill_defaults;
+        self
+    }
+
+    /// Set whether strings are parsed where an integer or a float is expected, which is
+    /// off by default
+    ///
+    /// This suits data which arrives as strings, such as the fields of CSV files and
+    /// environment variables. Parsing is strict: the whole string must be a number as
+    /// Rust's `str::parse` reads it, without surrounding whitespace or `_` separators,
+    /// and a string with a fractional part is not an integer.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Row {
+    ///     id: u32,
+    ///     price: f64,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("{'id': '42', 'price': '9.99'}", None, None).unwrap();
+    ///     assert!(Row::deserialize(&mut Depythonizer::from_object(&obj)).is_err());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj).parse_numbers(true).build();
+    ///     let row = Row::deserialize(&mut de).unwrap();
+    ///     assert_eq!(row, Row { id: 42, price: 9.99 });
+    /// })
+    /// ```
+    pub fn parse_numbers(mut self, parse_numbers: bool) -> Self {
+        self.options.parse_numbers = parse_numbers;
+        self
+    }
+
+    /// Set whether NaN floats are deserialized as `None` where an `Option` is expected,
+    /// which is off by default
+    ///
+    /// pandas marks missing values in float columns as NaN, so this is how they become
+    /// `None` fields, and `depythonize_records` always turns it on. Otherwise a NaN is
+    /// `Some(NaN)`, as it is any other float.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("float('nan')", None, None).unwrap();
+    ///     let mut de = Depythonizer::from_object(&obj);
+    ///     assert!(Option::<f64>::deserialize(&mut de).unwrap().unwrap().is_nan());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj).nan_as_none(true).build();
+    ///     assert_eq!(Option::<f64>::deserialize(&mut de).unwrap(), None);
+    /// })
+    /// ```
+    pub fn nan_as_none(mut self, nan_as_none: bool) -> Self {
+        self.options.nan_as_none = nan_as_none;
+        self
+    }
+
+    /// Add names of methods which convert objects of unsupported types into something
+    /// which can be deserialized, such as `to_dict`, `model_dump` or `_asdict`
+    ///
+    /// The first of the methods which an object has is called without arguments, and
+    /// its result deserialized in place of the object, where a struct, a map or any type
+    /// is expected. Objects of types which are supported otherwise, such as dataclass
+    /// instances and pydantic models, are not converted.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let locals = pyo3::types::PyDict::new_bound(py);
+    ///     py.run_bound(
+    ///         "class Point:\n    def to_dict(self):\n        return {'x': 1, 'y': 2}",
+    ///         Some(&locals),
+    ///         None,
+    ///     )
+    ///     .unwrap();
+    ///     let obj = py.eval_bound("Point()", Some(&locals), None).unwrap();
+    ///     assert!(Point::deserialize(&mut Depythonizer::from_object(&obj)).is_err());
+    ///
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .conversion_methods(["to_dict", "model_dump", "_asdict"])
+    ///         .build();
+    ///     assert_eq!(Point::deserialize(&mut de).unwrap(), Point { x: 1, y: 2 });
+    /// })
+    /// ```
+    pub fn conversion_methods<M>(mut self, methods: impl IntoIterator<Item = M>) -> Self
+    where
+        M: Into<String>,
+    {
+        self.options
+            .conversion_methods
+            .extend(methods.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set how `int`s which don't fit in the integer type expected are treated, by
+    /// default an error
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, IntOverflowPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Account {
+    ///     id: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("{'id': 2 ** 130}", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .int_overflow(IntOverflowPolicy::String)
+    ///         .build();
+    ///     let account = Account::deserialize(&mut de).unwrap();
+    ///     assert_eq!(account.id, "1361129467683753853853498429727072845824");
+    /// })
+    /// ```
+    pub fn int_overflow(mut self, policy: IntOverflowPolicy) -> Self {
+        self.options.int_overflow = policy;
+        self
+    }
+
+    /// Set how keys of dicts which aren't fields of the struct they are deserialized
+    /// into are treated, by default as the struct declares
+    ///
+    /// The option applies to structs and struct variants, but not to structs with
+    /// `#[serde(flatten)]` fields or within untagged and internally tagged enums, which
+    /// serde deserializes as maps.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, UnknownFieldPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Config {
+    ///     name: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py
+    ///         .eval_bound("{'name': 'x', 'nmae': 'y', 'size': 1}", None, None)
+    ///         .unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .unknown_fields(UnknownFieldPolicy::Deny)
+    ///         .build();
+    ///     let err = Config::deserialize(&mut de).unwrap_err();
+    ///     assert_eq!(
+    ///         err.to_string(),
+    ///         "unknown fields `nmae`, `size`, expected `name`"
+    ///     );
+    /// })
+    /// ```
+    pub fn unknown_fields(mut self, policy: UnknownFieldPolicy) -> Self {
+        self.options.unknown_fields = policy;
+        self
+    }
+
+    /// Set how keys of dicts are matched to the fields of structs, by default exactly
+    ///
+    /// Keys which match no field are left as they are, and are treated according to the
+    /// [`unknown_fields`](Self::unknown_fields) option. Like that option, this one
+    /// doesn't apply to structs which serde deserializes as maps.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, FieldMatching};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Headers {
+    ///     content_type: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py
+    ///         .eval_bound("{'Content-Type': 'text/plain'}", None, None)
+    ///         .unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .field_matching(FieldMatching::IgnoreCaseAndSeparators)
+    ///         .build();
+    ///     let headers = Headers::deserialize(&mut de).unwrap();
+    ///     assert_eq!(headers.content_type, "text/plain");
+    /// })
+    /// ```
+    pub fn field_matching(mut self, matching: FieldMatching) -> Self {
+        self.options.field_matching = matching;
+        self
+    }
+
+    /// Set the naming convention of dict keys which are converted into the fields of
+    /// structs, by default none
+    ///
+    /// A key matches a field if it is the name of the field converted into `case`, as
+    /// with [`PythonizerBuilder::field_case`](crate::PythonizerBuilder::field_case), or
+    /// the name itself. Like [`unknown_fields`](Self::unknown_fields), this option doesn't
+    /// apply to structs which serde deserializes as maps.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, FieldCase};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct User {
+    ///     user_name: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("{'userName': 'alice'}", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .field_case(FieldCase::Camel)
+    ///         .build();
+    ///     let user = User::deserialize(&mut de).unwrap();
+    ///     assert_eq!(user.user_name, "alice");
+    /// })
+    /// ```
+    pub fn field_case(mut self, case: ser::FieldCase) -> Self {
+        self.options.field_case = Some(case);
+        self
+    }
+
+    /// Set how `decimal.Decimal`s are converted, by default as strings where any type is
+    /// expected and as the nearest float where a float is expected
+    ///
+    /// Decimals are accepted as strings where a string is expected, whatever the policy.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{DecimalPolicy, Depythonizer};
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py
+    ///         .eval_bound("__import__('decimal').Decimal('0.1')", None, None)
+    ///         .unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .decimals(DecimalPolicy::ExactFloat)
+    ///         .build();
+    ///     assert!(f64::deserialize(&mut de).is_err());
+    /// })
+    /// ```
+    pub fn decimals(mut self, policy: DecimalPolicy) -> Self {
+        self.options.decimals = policy;
+        self
+    }
+
+    /// Set how members of `enum.Enum` classes are converted, by default by their names
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, EnumMemberPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// enum Status {
+    ///     #[serde(rename = "ok")]
+    ///     Ok,
+    ///     #[serde(rename = "failed")]
+    ///     Failed,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let locals = pyo3::types::PyDict::new_bound(py);
+    ///     py.run_bound(
+    ///         "import enum\nclass Status(enum.Enum):\n    OK = 'ok'\n    FAILED = 'failed'",
+    ///         Some(&locals),
+    ///         None,
+    ///     )
+    ///     .unwrap();
+    ///     let obj = py.eval_bound("Status.FAILED", Some(&locals), None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .enum_members(EnumMemberPolicy::Value)
+    ///         .build();
+    ///     assert_eq!(Status::deserialize(&mut de).unwrap(), Status::Failed);
+    /// })
+    /// ```
+    pub fn enum_members(mut self, policy: EnumMemberPolicy) -> Self {
+        self.options.enum_members = policy;
+        self
+    }
+
+    /// Set whether instances of subclasses of `str`, `int`, `float`, `dict` and `list`
+    /// are accepted as those types, which they are by default
+    ///
+    /// With [`SubclassPolicy::Exact`], numpy scalars such as `numpy.float64` are
+    /// deserialized as the equivalent builtin wherever they are found, like
+    /// `numpy.int64`, and members of `enum.IntEnum` and `enum.StrEnum` classes by the
+    /// `enum_members` option, like other members, rather than as their values.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, SubclassPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let locals = pyo3::types::PyDict::new_bound(py);
+    ///     py.run_bound("class Meters(float):\n    pass", Some(&locals), None)
+    ///         .unwrap();
+    ///     let obj = py.eval_bound("Meters(1.5)", Some(&locals), None).unwrap();
+    ///     assert_eq!(f64::deserialize(&mut Depythonizer::from_object(&obj)).unwrap(), 1.5);
+    ///
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .subclasses(SubclassPolicy::Exact)
+    ///         .build();
+    ///     assert!(f64::deserialize(&mut de).is_err());
+    /// })
+    /// ```
+    pub fn subclasses(mut self, policy: SubclassPolicy) -> Self {
+        self.options.subclasses = policy;
+        self
+    }
+
+    /// Set how `fractions.Fraction`s are converted, by default as `(numerator,
+    /// denominator)` pairs, which is how `num_rational::Ratio` is deserialized
+    ///
+    /// Fractions are accepted where a float is expected only with
+    /// [`FractionPolicy::Float`], as the conversion may lose precision.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{Depythonizer, FractionPolicy};
+    /// use serde::Deserialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py
+    ///         .eval_bound("__import__('fractions').Fraction(1, 4)", None, None)
+    ///         .unwrap();
+    ///     let pair = <(i64, i64)>::deserialize(&mut Depythonizer::from_object(&obj)).unwrap();
+    ///     assert_eq!(pair, (1, 4));
+    ///
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .fractions(FractionPolicy::Float)
+    ///         .build();
+    ///     assert_eq!(f64::deserialize(&mut de).unwrap(), 0.25);
+    /// })
+    /// ```
+    pub fn fractions(mut self, policy: FractionPolicy) -> Self {
+        self.options.fractions = policy;
+        self
+    }
+
+    /// Add pairs of dict keys and the struct fields which they are deserialized into
+    ///
+    /// Keys are renamed in every struct, before they are matched to its fields, so a
+    /// struct whose fields have the original names as well is deserialized as if the
+    /// key were missing. Like [`unknown_fields`](Self::unknown_fields), this option
+    /// doesn't apply to structs which serde deserializes as maps.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::Depythonizer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct User {
+    ///     user_name: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let obj = py.eval_bound("{'userName': 'alice'}", None, None).unwrap();
+    ///     let mut de = Depythonizer::builder(&obj)
+    ///         .rename_fields([("userName", "user_name")])
+    ///         .build();
+    ///     let user = User::deserialize(&mut de).unwrap();
+    ///     assert_eq!(user.user_name, "alice");
+    /// })
+    /// ```
+    pub fn rename_fields<K, F>(mut self, renames: impl IntoIterator<Item = (K, F)>) -> Self
+    where
+        K: Into<String>,
+        F: Into<String>,
+    {
+        self.options.renamed_fields.extend(
+            renames
+                .into_iter()
+                .map(|(key, field)| (key.into(), field.into())),
+        );
+        self
+    }
+
+    /// Creates the configured deserializer
+    pub fn build(self) -> Depythonizer<'a, 'py> {
+        Depythonizer {
+            input: self.input,
+            items: Items {
+                options: Rc::new(self.options),
+                ..Items::default()
+            },
+        }
+    }
+}
+
+/// The object a [`PySequenceAccess`] takes items from, with fast paths for lists and
+/// tuples
+enum SequenceItems<'a, 'py> {
+    List(&'a Bound<'py, PyList>),
+    Tuple(&'a Bound<'py, PyTuple>),
+    /// Any other object with `__len__` and `__getitem__`
+    Other(&'a Bound<'py, PyAny>),
+}
+
+impl<'py> SequenceItems<'_, 'py> {
+    fn get_item(&self, index: usize) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            SequenceItems::List(list) => list.get_item(index),
+            SequenceItems::Tuple(tuple) => tuple.get_item(index),
+            SequenceItems::Other(obj) => obj.get_item(index),
+        }
+    }
+}
+
 struct PySequenceAccess<'a, 'py> {
-    seq: &'a Bound<'py, PySequence>,
+    seq: SequenceItems<'a, 'py>,
     index: usize,
     len: usize,
+    items: Items,
 }
 
 impl<'a, 'py> PySequenceAccess<'a, 'py> {
-    fn new(seq: &'a Bound<'py, PySequence>, len: usize) -> Self {
-        Self { seq, index: 0, len }
+    fn new(seq: SequenceItems<'a, 'py>, len: usize, items: Items) -> Self {
+        Self {
+            seq,
+            index: 0,
+            len,
+            items,
+        }
     }
 }
 
@@ -372,7 +2095,7 @@ impl<'de> de::SeqAccess<'de> for PySequenceAccess<'_, '_> {
         if self.index < self.len {
             let item = self.seq.get_item(self.index)?;
             self.index += 1;
-            seed.deserialize(&mut Depythonizer::from_object(&item))
+            seed.deserialize(&mut self.items.deserializer(&item))
                 .map(Some)
         } else {
             Ok(None)
@@ -380,77 +2103,118 @@ impl<'de> de::SeqAccess<'de> for PySequenceAccess<'_, '_> {
     }
 }
 
-struct PySetAsSequence<'py> {
-    iter: Bound<'py, PyIterator>,
+/// Deserializes the elements of a numeric `numpy.ndarray` as nested sequences, one
+/// level per dimension
+struct ArrayElements<'a, T> {
+    shape: &'a [usize],
+    elements: &'a [T],
 }
 
-impl<'py> PySetAsSequence<'py> {
-    fn from_set(set: &Bound<'py, PySet>) -> Self {
-        Self {
-            iter: PyIterator::from_bound_object(&set).expect("set is always iterable"),
-        }
+impl<'a, T> ArrayElements<'a, T> {
+    fn new(shape: &'a [usize], elements: &'a [T]) -> Self {
+        Self { shape, elements }
     }
 
-    fn from_frozenset(set: &Bound<'py, PyFrozenSet>) -> Self {
-        Self {
-            iter: PyIterator::from_bound_object(&set).expect("frozenset is always iterable"),
-        }
+    fn visit<'de, V>(self, visitor: V) -> Result<V::Value>
+    where
+        T: Copy + IntoDeserializer<'de, PythonizeError>,
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_any(self, visitor)
     }
 }
 
-impl<'de> de::SeqAccess<'de> for PySetAsSequence<'_> {
+impl<'de, T> de::Deserializer<'de> for ArrayElements<'_, T>
+where
+    T: Copy + IntoDeserializer<'de, PythonizeError>,
+{
     type Error = PythonizeError;
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
-        T: de::DeserializeSeed<'de>,
+        V: de::Visitor<'de>,
     {
-        match self.iter.next() {
-            Some(item) => seed
-                .deserialize(&mut Depythonizer::from_object(&item?))
-                .map(Some),
-            None => Ok(None),
-        }
+        let (&len, shape) = match self.shape.split_first() {
+            Some(split) => split,
+            None => {
+                return self.elements[0]
+                    .into_deserializer()
+                    .deserialize_any(visitor)
+            }
+        };
+        let stride: usize = shape.iter().product();
+        let rows = (0..len)
+            .map(|i| ArrayElements::new(shape, &self.elements[i * stride..(i + 1) * stride]));
+        let mut seq = SeqDeserializer::new(rows);
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
     }
 }
 
-struct PyMappingAccess<'py> {
-    keys: Bound<'py, PySequence>,
-    values: Bound<'py, PySequence>,
-    key_idx: usize,
-    val_idx: usize,
-    len: usize,
+impl<'de, T> IntoDeserializer<'de, PythonizeError> for ArrayElements<'_, T>
+where
+    T: Copy + IntoDeserializer<'de, PythonizeError>,
+{
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
 }
 
-impl<'py> PyMappingAccess<'py> {
-    fn new(map: &Bound<'py, PyMapping>) -> Result<Self> {
-        let keys = map.keys()?;
-        let values = map.values()?;
-        let len = map.len()?;
-        Ok(Self {
-            keys,
-            values,
-            key_idx: 0,
-            val_idx: 0,
-            len,
-        })
+/// Deserializes an element of a structured `numpy.ndarray` as a map of its fields, whose
+/// values are taken from the contents of each column
+struct RecordRow<'a, 'py> {
+    columns: &'a [(String, ArrayContents<'py>)],
+    row: usize,
+    index: usize,
+    items: &'a Items,
+}
+
+impl<'a, 'py> RecordRow<'a, 'py> {
+    fn new(columns: &'a [(String, ArrayContents<'py>)], row: usize, items: &'a Items) -> Self {
+        Self {
+            columns,
+            row,
+            index: 0,
+            items,
+        }
     }
 }
 
-impl<'de> de::MapAccess<'de> for PyMappingAccess<'_> {
+impl<'de> de::MapAccess<'de> for RecordRow<'_, '_> {
     type Error = PythonizeError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
     where
         K: de::DeserializeSeed<'de>,
     {
-        if self.key_idx < self.len {
-            let item = self.keys.get_item(self.key_idx)?;
-            self.key_idx += 1;
-            seed.deserialize(&mut Depythonizer::from_object(&item))
-                .map(Some)
-        } else {
-            Ok(None)
+        match self.columns.get(self.index) {
+            Some((name, _)) => seed
+                .deserialize(name.as_str().into_deserializer())
+                .map(Some),
+            None => Ok(None),
         }
     }
 
@@ -458,98 +2222,565 @@ impl<'de> de::MapAccess<'de> for PyMappingAccess<'_> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        let item = self.values.get_item(self.val_idx)?;
-        self.val_idx += 1;
-        seed.deserialize(&mut Depythonizer::from_object(&item))
+        let (_, column) = &self.columns[self.index];
+        self.index += 1;
+        let row = self.row;
+        match column {
+            ArrayContents::Numeric(shape, elements) => {
+                // fields may be subarrays, whose shape follows the length of the column
+                let shape = &shape[1..];
+                let stride: usize = shape.iter().product();
+                let range = row * stride..(row + 1) * stride;
+                match elements {
+                    Elements::Bool(e) => seed.deserialize(ArrayElements::new(shape, &e[range])),
+                    Elements::Int(e) => seed.deserialize(ArrayElements::new(shape, &e[range])),
+                    Elements::UInt(e) => seed.deserialize(ArrayElements::new(shape, &e[range])),
+                    Elements::Float(e) => seed.deserialize(ArrayElements::new(shape, &e[range])),
+                }
+            }
+            ArrayContents::List(list) => {
+                seed.deserialize(&mut self.items.deserializer(&list.get_item(row)?))
+            }
+            ArrayContents::Records(_, columns) => {
+                seed.deserialize(RecordRow::new(columns, row, self.items))
+            }
+        }
     }
 }
 
-struct PyEnumAccess<'a, 'py> {
-    de: Depythonizer<'a, 'py>,
-    variant: Bound<'py, PyString>,
+impl<'de> de::Deserializer<'de> for RecordRow<'_, '_> {
+    type Error = PythonizeError;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(&mut self)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
 }
 
-impl<'a, 'py> PyEnumAccess<'a, 'py> {
-    fn new(obj: &'a Bound<'py, PyAny>, variant: Bound<'py, PyString>) -> Self {
-        Self {
-            de: Depythonizer::from_object(obj),
-            variant,
-        }
+impl<'de> IntoDeserializer<'de, PythonizeError> for RecordRow<'_, '_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
     }
 }
 
-impl<'de> de::EnumAccess<'de> for PyEnumAccess<'_, '_> {
+/// Deserializes the value which `#[derive(Default)]` gives each type: zero, `false`, an
+/// empty string or collection, `None`, or a struct of such values
+#[derive(Clone, Copy)]
+struct DefaultValue;
+
+macro_rules! deserialize_default {
+    ($($method:ident => $visit:ident($value:expr)),* $(,)?) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.$visit($value)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for DefaultValue {
     type Error = PythonizeError;
-    type Variant = Self;
 
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
-        V: de::DeserializeSeed<'de>,
+        V: de::Visitor<'de>,
     {
-        let cow = self.variant.to_cow()?;
-        let de: de::value::StrDeserializer<'_, PythonizeError> = cow.as_ref().into_deserializer();
-        let val = seed.deserialize(de)?;
-        Ok((val, self))
+        visitor.visit_unit()
     }
-}
-
-impl<'de> de::VariantAccess<'de> for PyEnumAccess<'_, '_> {
-    type Error = PythonizeError;
 
-    fn unit_variant(self) -> Result<()> {
-        Ok(())
+    deserialize_default! {
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_i128 => visit_i128(0),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_u128 => visit_u128(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char('\0'),
+        deserialize_str => visit_str(""),
+        deserialize_string => visit_str(""),
+        deserialize_bytes => visit_bytes(b""),
+        deserialize_byte_buf => visit_bytes(b""),
     }
 
-    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
-        T: de::DeserializeSeed<'de>,
+        V: de::Visitor<'de>,
     {
-        seed.deserialize(&mut { self.de })
+        visitor.visit_none()
     }
 
-    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_seq(self.de.sequence_access(Some(len))?)
+        visitor.visit_newtype_struct(self)
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_map(self.de.dict_access()?)
+        self.deserialize_tuple(0, visitor)
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::error::ErrorImpl;
-    use maplit::hashmap;
-    use pyo3::{IntoPy, Python};
-    use serde_json::{json, Value as JsonValue};
 
-    fn test_de<T>(code: &str, expected: &T, expected_json: &JsonValue)
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
-        T: de::DeserializeOwned + PartialEq + std::fmt::Debug,
+        V: de::Visitor<'de>,
     {
-        Python::with_gil(|py| {
-            let obj = py.eval_bound(code, None, None).unwrap();
-            let actual: T = depythonize(&obj).unwrap();
-            assert_eq!(&actual, expected);
-            let actual_json: JsonValue = depythonize(&obj).unwrap();
-            assert_eq!(&actual_json, expected_json);
-
-            #[allow(deprecated)]
-            let actual: T = depythonize_bound(obj.clone()).unwrap();
-            assert_eq!(&actual, expected);
-        });
+        let mut seq = SeqDeserializer::new(std::iter::repeat(self).take(len));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
     }
 
-    #[test]
-    fn test_empty_struct() {
-        #[derive(Debug, Deserialize, PartialEq)]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_struct("", &[], visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut map = MapDeserializer::new(fields.iter().map(|&field| (field, self)));
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(PythonizeError::msg(format!(
+            "enum {} has no default to fill a missing value with",
+            name
+        )))
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, PythonizeError> for DefaultValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct PySetAsSequence<'py> {
+    iter: Bound<'py, PyIterator>,
+    items: Items,
+}
+
+impl<'py> PySetAsSequence<'py> {
+    fn from_set(set: &Bound<'py, PySet>, items: Items) -> Self {
+        Self {
+            iter: PyIterator::from_bound_object(set).expect("set is always iterable"),
+            items,
+        }
+    }
+
+    fn from_frozenset(set: &Bound<'py, PyFrozenSet>, items: Items) -> Self {
+        Self {
+            iter: PyIterator::from_bound_object(set).expect("frozenset is always iterable"),
+            items,
+        }
+    }
+
+    /// Takes the items of any other iterable, such as a generator, one at a time
+    fn from_iterator(iter: Bound<'py, PyIterator>, items: Items) -> Self {
+        Self { iter, items }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for PySetAsSequence<'_> {
+    type Error = PythonizeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(item) => seed
+                .deserialize(&mut self.items.deserializer(&item?))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct PyMappingAccess<'py> {
+    keys: Bound<'py, PySequence>,
+    values: Bound<'py, PySequence>,
+    key_idx: usize,
+    val_idx: usize,
+    len: usize,
+    items: Items,
+    /// The type name of the visitor of the struct whose fields the `fill_defaults` option
+    /// fills
+    fill: Option<&'static str>,
+    /// The last key, while `fill` is set
+    key: Option<String>,
+}
+
+impl<'py> PyMappingAccess<'py> {
+    fn new(map: &Bound<'py, PyMapping>, items: Items) -> Result<Self> {
+        let keys = map.keys()?;
+        let values = map.values()?;
+        let len = map.len()?;
+        Ok(Self {
+            keys,
+            values,
+            key_idx: 0,
+            val_idx: 0,
+            len,
+            items,
+            fill: None,
+            key: None,
+        })
+    }
+
+    /// Adds a `None` value for each of `fields` which isn't a key but has been learned to
+    /// be filled, and deserializes the `None` values of such fields as [`DefaultValue`]s
+    fn fill_missing(&mut self, ty: &'static str, fields: &[&str]) -> Result<()> {
+        self.fill = Some(ty);
+        let mut missing = Vec::new();
+        for field in fields {
+            if self.items.options.fills.borrow().contains(ty, field)
+                && !self.keys.contains(field)?
+            {
+                missing.push(*field);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let py = self.keys.py();
+        let keys = PyList::empty_bound(py);
+        let values = PyList::empty_bound(py);
+        for index in 0..self.len {
+            keys.append(self.keys.get_item(index)?)?;
+            values.append(self.values.get_item(index)?)?;
+        }
+        for field in missing {
+            keys.append(field)?;
+            values.append(py.None())?;
+        }
+        self.len = keys.len();
+        self.keys = keys.into_sequence();
+        self.values = values.into_sequence();
+        Ok(())
+    }
+
+    /// Replaces the keys for which `rename` returns a new name
+    fn rename_keys(
+        &mut self,
+        mut rename: impl FnMut(&Bound<'py, PyAny>) -> Result<Option<String>>,
+    ) -> Result<()> {
+        let py = self.keys.py();
+        let keys = PyList::empty_bound(py);
+        for index in 0..self.len {
+            let key = self.keys.get_item(index)?;
+            match rename(&key)? {
+                Some(name) => keys.append(name)?,
+                None => keys.append(key)?,
+            }
+        }
+        self.keys = keys.into_sequence();
+        Ok(())
+    }
+
+    /// Keeps only the items whose key satisfies `keep`
+    fn retain(&mut self, mut keep: impl FnMut(&Bound<'py, PyAny>) -> Result<bool>) -> Result<()> {
+        let py = self.keys.py();
+        let keys = PyList::empty_bound(py);
+        let values = PyList::empty_bound(py);
+        for index in 0..self.len {
+            let key = self.keys.get_item(index)?;
+            if keep(&key)? {
+                keys.append(key)?;
+                values.append(self.values.get_item(index)?)?;
+            }
+        }
+        self.len = keys.len();
+        self.keys = keys.into_sequence();
+        self.values = values.into_sequence();
+        Ok(())
+    }
+}
+
+impl<'de> de::MapAccess<'de> for PyMappingAccess<'_> {
+    type Error = PythonizeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.key_idx < self.len {
+            let item = self.keys.get_item(self.key_idx)?;
+            self.key_idx += 1;
+            if self.fill.is_some() {
+                self.key = match item.downcast::<PyString>() {
+                    Ok(key) => Some(key.to_cow()?.into_owned()),
+                    Err(_) => None,
+                };
+            }
+            seed.deserialize(&mut self.items.deserializer(&item))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let item = self.values.get_item(self.val_idx)?;
+        self.val_idx += 1;
+        let key = self.key.take();
+        if let (Some(ty), Some(field)) = (self.fill, key.filter(|_| item.is_none())) {
+            let fills = &self.items.options.fills;
+            if fills.borrow().contains(ty, &field) {
+                return seed.deserialize(DefaultValue);
+            }
+            let result = seed.deserialize(&mut self.items.deserializer(&item));
+            if result.is_err() {
+                fills.borrow_mut().fail(ty, &field);
+            }
+            return result;
+        }
+        seed.deserialize(&mut self.items.deserializer(&item))
+    }
+}
+
+struct PyEnumAccess<'a, 'py> {
+    de: Depythonizer<'a, 'py>,
+    variant: Bound<'py, PyString>,
+}
+
+impl<'a, 'py> PyEnumAccess<'a, 'py> {
+    fn new(de: Depythonizer<'a, 'py>, variant: Bound<'py, PyString>) -> Self {
+        Self { de, variant }
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for PyEnumAccess<'_, '_> {
+    type Error = PythonizeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let cow = self.variant.to_cow()?;
+        let de: de::value::StrDeserializer<'_, PythonizeError> = cow.as_ref().into_deserializer();
+        let val = seed.deserialize(de)?;
+        Ok((val, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for PyEnumAccess<'_, '_> {
+    type Error = PythonizeError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut { self.de })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self.de.sequence_access(Some(len))?)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let access = self.de.struct_access(type_name::<V>(), fields)?;
+        self.de.visit_struct(fields, access, visitor)
+    }
+}
+
+/// Deserialize each row of a table from the contents of its columns, as the elements of
+/// a structured `numpy.ndarray` are
+#[cfg(feature = "pandas")]
+pub(crate) fn depythonize_columns<T>(
+    len: usize,
+    columns: &[(String, ArrayContents<'_>)],
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    // pandas marks missing floats as NaN
+    let options = Options {
+        nan_as_none: true,
+        ..Options::default()
+    };
+    let items = Items {
+        options: Rc::new(options),
+        ..Items::default()
+    }
+    .nested()?;
+    (0..len)
+        .map(|row| T::deserialize(RecordRow::new(columns, row, &items)))
+        .collect()
+}
+
+/// Whether `obj` isn't a registered `collections.abc.Sequence`, but has the `__len__`
+/// and `__getitem__` methods of the sequence protocol, and no `keys` method which would
+/// make it a mapping
+fn is_sequence_protocol(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let py = obj.py();
+    Ok(!obj.is_instance_of::<PyType>()
+        && obj.hasattr(pyo3::intern!(py, "__len__"))?
+        && obj.hasattr(pyo3::intern!(py, "__getitem__"))?
+        && !obj.hasattr(pyo3::intern!(py, "keys"))?)
+}
+
+/// A `dict` of an object which isn't a registered `collections.abc.Mapping`, but has the
+/// `keys` and `__getitem__` methods which the `dict` constructor takes as a mapping
+fn mapping_protocol_dict<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    if obj.is_instance_of::<PyType>()
+        || !obj.hasattr(pyo3::intern!(py, "keys"))?
+        || !obj.hasattr(pyo3::intern!(py, "__getitem__"))?
+    {
+        return Ok(None);
+    }
+    Ok(Some(
+        py.get_type_bound::<PyDict>()
+            .call1((obj,))?
+            .downcast_into()?,
+    ))
+}
+
+/// Copies the contents of an object which supports the buffer protocol, in C order
+#[cfg(any(Py_3_11, not(Py_LIMITED_API)))]
+fn buffer_to_vec(obj: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    use pyo3::{ffi, PyErr};
+
+    let mut view = std::mem::MaybeUninit::<ffi::Py_buffer>::uninit();
+    // SAFETY: `view` is initialized by `PyObject_GetBuffer` when it succeeds, and is
+    // released once `PyBuffer_ToContiguous` has filled the capacity of `vec`
+    unsafe {
+        if ffi::PyObject_GetBuffer(obj.as_ptr(), view.as_mut_ptr(), ffi::PyBUF_FULL_RO) == -1 {
+            return Err(PyErr::fetch(obj.py()));
+        }
+        let mut view = view.assume_init();
+        let mut vec = Vec::<u8>::with_capacity(view.len as usize);
+        let result =
+            ffi::PyBuffer_ToContiguous(vec.as_mut_ptr().cast(), &view, view.len, b'C' as _);
+        let len = view.len as usize;
+        ffi::PyBuffer_Release(&mut view);
+        if result == -1 {
+            return Err(PyErr::fetch(obj.py()));
+        }
+        vec.set_len(len);
+        Ok(vec)
+    }
+}
+
+/// Copies the contents of an object which supports the buffer protocol, in C order
+#[cfg(not(any(Py_3_11, not(Py_LIMITED_API))))]
+fn buffer_to_vec(obj: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    let bytes = PyMemoryView::from_bound(obj)?.call_method0("tobytes")?;
+    Ok(bytes.downcast_into::<PyBytes>()?.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet};
+    use std::net::{IpAddr, Ipv6Addr};
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::error::ErrorImpl;
+    use maplit::hashmap;
+    use pyo3::{IntoPy, Python};
+    use serde_json::{json, Value as JsonValue};
+
+    fn test_de<T>(code: &str, expected: &T, expected_json: &JsonValue)
+    where
+        T: de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        Python::with_gil(|py| {
+            let obj = py.eval_bound(code, None, None).unwrap();
+            let actual: T = depythonize(&obj).unwrap();
+            assert_eq!(&actual, expected);
+            let actual_json: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(&actual_json, expected_json);
+
+            #[allow(deprecated)]
+            let actual: T = depythonize_bound(obj.clone()).unwrap();
+            assert_eq!(&actual, expected);
+        });
+    }
+
+    #[test]
+    fn test_empty_struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
         struct Empty;
 
         let expected = Empty;
@@ -679,6 +2910,30 @@ mod test {
         test_de(code, &expected, &expected_json);
     }
 
+    #[test]
+    fn test_sets_from_pysets() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("[{3, 1, 2}, frozenset({2, 4})]", None, None)
+                .unwrap();
+            let sets: Vec<HashSet<i32>> = depythonize(&obj).unwrap();
+            assert_eq!(sets, [HashSet::from([1, 2, 3]), HashSet::from([2, 4])]);
+            let sets: Vec<BTreeSet<i32>> = depythonize(&obj).unwrap();
+            assert_eq!(sets, [BTreeSet::from([1, 2, 3]), BTreeSet::from([2, 4])]);
+            let mut vecs: Vec<Vec<i32>> = depythonize(&obj).unwrap();
+            vecs.iter_mut().for_each(|v| v.sort());
+            assert_eq!(vecs, [vec![1, 2, 3], vec![2, 4]]);
+
+            let mut pair: (i32, i32) = depythonize(&obj.get_item(1).unwrap()).unwrap();
+            if pair.0 > pair.1 {
+                pair = (pair.1, pair.0);
+            }
+            assert_eq!(pair, (2, 4));
+            let err = depythonize::<(i32, i32)>(&obj.get_item(0).unwrap()).unwrap_err();
+            assert_eq!(err.to_string(), "expected sequence of length 2, got 3");
+        })
+    }
+
     #[test]
     fn test_vec() {
         let expected = vec![3, 2, 1];
@@ -696,8 +2951,31 @@ mod test {
     }
 
     #[test]
-    fn test_hashmap() {
-        let expected = hashmap! {"foo".to_string() => 4};
+    fn test_vec_from_range() {
+        let expected = vec![3, 1, -1];
+        let expected_json = json!([3, 1, -1]);
+        let code = "range(3, -2, -2)";
+        test_de(code, &expected, &expected_json);
+    }
+
+    #[test]
+    fn test_vec_from_large_range() {
+        Python::with_gil(|py| {
+            // items are taken from the range one at a time, without a `list` of them
+            let obj = py.eval_bound("range(0, 1_000_000)", None, None).unwrap();
+            let actual: Vec<i64> = depythonize(&obj).unwrap();
+            assert_eq!(actual.len(), 1_000_000);
+            assert_eq!(actual[999_999], 999_999);
+
+            let obj = py.eval_bound("range(254, 258)", None, None).unwrap();
+            let err = depythonize::<Vec<u8>>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "int 256 is out of range for u8");
+        })
+    }
+
+    #[test]
+    fn test_hashmap() {
+        let expected = hashmap! {"foo".to_string() => 4};
         let expected_json = json!({"foo": 4 });
         let code = "{'foo': 4}";
         test_de(code, &expected, &expected_json);
@@ -864,6 +3142,15 @@ mod test {
 
             let _: i128 = depythonize(&i128::MAX.into_py(py).into_bound(py)).unwrap();
             let _: i128 = depythonize(&i128::MIN.into_py(py).into_bound(py)).unwrap();
+
+            for value in [u128::MAX, u128::from(u64::MAX) + 1] {
+                let obj = crate::pythonize(py, &value).unwrap();
+                assert_eq!(depythonize::<u128>(&obj).unwrap(), value);
+            }
+            for value in [i128::MIN, i128::from(i64::MIN) - 1] {
+                let obj = crate::pythonize(py, &value).unwrap();
+                assert_eq!(depythonize::<i128>(&obj).unwrap(), value);
+            }
         });
     }
 
@@ -876,6 +3163,60 @@ mod test {
         })
     }
 
+    #[test]
+    fn test_deserialize_bytearray() {
+        Python::with_gil(|py| {
+            let obj = PyByteArray::new_bound(py, "hello".as_bytes());
+            let actual: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(actual, b"hello".as_slice());
+        })
+    }
+
+    #[test]
+    fn test_deserialize_buffer() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("memoryview(b'hello')[1:]", None, None)
+                .unwrap();
+            let actual: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(actual, b"ello".as_slice());
+
+            // non-contiguous buffers are copied in order
+            let obj = py
+                .eval_bound("memoryview(b'hello')[::2]", None, None)
+                .unwrap();
+            let actual: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(actual, b"hlo".as_slice());
+
+            let obj = py
+                .eval_bound("__import__('array').array('H', [1, 2])", None, None)
+                .unwrap();
+            let actual: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(actual, [1u16.to_ne_bytes(), 2u16.to_ne_bytes()].concat());
+
+            let obj = py.eval_bound("'hello'", None, None).unwrap();
+            let err = depythonize::<serde_bytes::ByteBuf>(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "unexpected type: 'str' object cannot be converted to 'PyBytes'"
+            );
+
+            // `bytes` are borrowed with an arena
+            let obj = py
+                .eval_bound("[b'ab', bytearray(b'cd')]", None, None)
+                .unwrap();
+            let arena = BorrowArena::new();
+            let actual: (&[u8], serde_bytes::ByteBuf) = depythonize_borrowed(&obj, &arena).unwrap();
+            assert_eq!(actual.0, b"ab");
+            assert_eq!(actual.1, b"cd".as_slice());
+            let bytes = obj.get_item(0).unwrap();
+            assert_eq!(
+                actual.0.as_ptr(),
+                bytes.downcast::<PyBytes>().unwrap().as_bytes().as_ptr()
+            );
+        })
+    }
+
     #[test]
     fn test_char() {
         let expected = 'a';
@@ -887,18 +3228,1694 @@ mod test {
     #[test]
     fn test_unknown_type() {
         Python::with_gil(|py| {
-            let obj = py
-                .import_bound("decimal")
-                .unwrap()
-                .getattr("Decimal")
-                .unwrap()
-                .call0()
-                .unwrap();
+            let obj = py.eval_bound("object()", None, None).unwrap();
             let err = depythonize::<serde_json::Value>(&obj).unwrap_err();
             assert!(matches!(
                 *err.inner,
-                ErrorImpl::UnsupportedType(name) if name == "Decimal"
+                ErrorImpl::UnsupportedType(name) if name == "object"
             ));
         });
     }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_numpy_scalars() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Scalars {
+            int: i64,
+            unsigned: u64,
+            float: f32,
+            flag: bool,
+        }
+
+        let expected = Scalars {
+            int: -3,
+            unsigned: u64::MAX,
+            float: 0.5,
+            flag: true,
+        };
+        let expected_json = json!({"int": -3, "unsigned": u64::MAX, "float": 0.5, "flag": true});
+        let code = "(lambda np: {'int': np.int64(-3), 'unsigned': np.uint64(2 ** 64 - 1), \
+                    'float': np.float32(0.5), 'flag': np.bool_(True)})(__import__('numpy'))";
+        test_de(code, &expected, &expected_json);
+    }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_numpy_structured_arrays() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reading {
+            id: u32,
+            name: String,
+            position: [f64; 2],
+            valid: bool,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "__import__('numpy').array([(1, 'a', (0.5, 1.5), True), (2, 'b', (2.5, 3.5), False)], \
+                     dtype=[('id', '>u4'), ('name', 'U4'), ('position', 'f8', (2,)), ('valid', '?')])",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let readings: Vec<Reading> = depythonize(&obj).unwrap();
+            assert_eq!(
+                readings,
+                [
+                    Reading {
+                        id: 1,
+                        name: "a".to_string(),
+                        position: [0.5, 1.5],
+                        valid: true,
+                    },
+                    Reading {
+                        id: 2,
+                        name: "b".to_string(),
+                        position: [2.5, 3.5],
+                        valid: false,
+                    },
+                ]
+            );
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(
+                any[1],
+                json!({ "id": 2, "name": "b", "position": [2.5, 3.5], "valid": false })
+            );
+            assert!(depythonize::<[Reading; 3]>(&obj).is_err());
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_numpy_arrays() {
+        let expected = vec![vec![1.5, 2.5, 3.5], vec![4.5, 5.5, 6.5]];
+        let expected_json = json!([[1.5, 2.5, 3.5], [4.5, 5.5, 6.5]]);
+        let code = "__import__('numpy').arange(1.5, 7.5).reshape(2, 3)";
+        test_de(code, &expected, &expected_json);
+
+        // big-endian and integer arrays
+        let expected = vec![-1i64, 0, 1];
+        let code = "__import__('numpy').array([-1, 0, 1], dtype='>i2')";
+        test_de(code, &expected, &json!([-1, 0, 1]));
+
+        let expected = (true, false);
+        let code = "__import__('numpy').array([True, False])";
+        test_de(code, &expected, &json!([true, false]));
+
+        // arrays without a numeric dtype go through `tolist()`
+        let expected = vec!["a".to_string(), "bc".to_string()];
+        let code = "__import__('numpy').array(['a', 'bc'])";
+        test_de(code, &expected, &json!(["a", "bc"]));
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("__import__('numpy').zeros(3)", None, None)
+                .unwrap();
+            let err = depythonize::<(f64, f64)>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "expected sequence of length 2, got 3");
+        });
+    }
+
+    #[test]
+    #[ignore = "needs the pyarrow Python package"]
+    fn test_pyarrow_tables() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Row {
+            name: String,
+            score: Option<f64>,
+        }
+
+        let expected = vec![
+            Row {
+                name: "a".to_string(),
+                score: Some(0.5),
+            },
+            Row {
+                name: "b".to_string(),
+                score: None,
+            },
+        ];
+        let expected_json = json!([{"name": "a", "score": 0.5}, {"name": "b", "score": null}]);
+        let code = "__import__('pyarrow').table({'name': ['a', 'b'], 'score': [0.5, None]})";
+        test_de(code, &expected, &expected_json);
+        let code = "__import__('pyarrow').record_batch({'name': ['a', 'b'], 'score': [0.5, None]})";
+        test_de(code, &expected, &expected_json);
+    }
+
+    #[test]
+    fn test_option_nan_is_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Measurement {
+            value: Option<f64>,
+            error: Option<f64>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'value': float('nan'), 'error': 0.5}", None, None)
+                .unwrap();
+            let measurement: Measurement = depythonize(&obj).unwrap();
+            assert!(measurement.value.unwrap().is_nan());
+
+            let mut de = Depythonizer::builder(&obj).nan_as_none(true).build();
+            assert_eq!(
+                Measurement::deserialize(&mut de).unwrap(),
+                Measurement {
+                    value: None,
+                    error: Some(0.5),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_max_depth() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Shape {
+            Polygon { points: Vec<(u8, u8)> },
+        }
+
+        Python::with_gil(|py| {
+            let obj = py.eval_bound("[[1, 2], {'a': [3]}]", None, None).unwrap();
+            let mut de = Depythonizer::builder(&obj).max_depth(3).build();
+            assert_eq!(
+                JsonValue::deserialize(&mut de).unwrap(),
+                json!([[1, 2], {"a": [3]}])
+            );
+            let mut de = Depythonizer::builder(&obj).max_depth(2).build();
+            let err = JsonValue::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "maximum depth of 2 exceeded");
+
+            // scalars are not nested
+            let obj = py.eval_bound("1", None, None).unwrap();
+            let mut de = Depythonizer::builder(&obj).max_depth(0).build();
+            assert_eq!(u8::deserialize(&mut de).unwrap(), 1);
+
+            // the variant of an enum nests its content, as when serializing
+            let obj = py
+                .eval_bound("{'Polygon': {'points': [(0, 1)]}}", None, None)
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj).max_depth(4).build();
+            assert_eq!(
+                Shape::deserialize(&mut de).unwrap(),
+                Shape::Polygon {
+                    points: vec![(0, 1)]
+                }
+            );
+            let mut de = Depythonizer::builder(&obj).max_depth(3).build();
+            assert!(Shape::deserialize(&mut de).is_err());
+
+            // a list which contains itself
+            let locals = PyDict::new_bound(py);
+            py.run_bound("obj = []\nobj.append(obj)", None, Some(&locals))
+                .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let mut de = Depythonizer::builder(&obj).max_depth(100).build();
+            let err = JsonValue::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "maximum depth of 100 exceeded");
+        })
+    }
+
+    #[test]
+    fn test_strict_bool() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Flags {
+            enabled: bool,
+            count: u8,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'enabled': 1, 'count': True}", None, None)
+                .unwrap();
+            let expected = Flags {
+                enabled: true,
+                count: 1,
+            };
+            assert_eq!(depythonize::<Flags>(&obj).unwrap(), expected);
+
+            let mut de = Depythonizer::builder(&obj).strict_bool(true).build();
+            let err = Flags::deserialize(&mut de).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "unexpected type: 'int' object cannot be converted to 'PyBool'"
+            );
+
+            let obj = py
+                .eval_bound("{'enabled': False, 'count': True}", None, None)
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj).strict_bool(true).build();
+            let err = Flags::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "expected an int, got a bool");
+
+            let obj = py
+                .eval_bound("{'enabled': False, 'count': 2}", None, None)
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj).strict_bool(true).build();
+            assert_eq!(
+                Flags::deserialize(&mut de).unwrap(),
+                Flags {
+                    enabled: false,
+                    count: 2
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_float_to_int() {
+        fn deserialize(code: &str, policy: FloatToIntPolicy) -> Result<Vec<i16>> {
+            Python::with_gil(|py| {
+                let obj = py.eval_bound(code, None, None).unwrap();
+                let mut de = Depythonizer::builder(&obj).float_to_int(policy).build();
+                Vec::<i16>::deserialize(&mut de)
+            })
+        }
+
+        let code = "[3.0, 3.5, -3.7, 2]";
+        assert!(deserialize(code, FloatToIntPolicy::Error).is_err());
+        assert_eq!(
+            deserialize(code, FloatToIntPolicy::Integral)
+                .unwrap_err()
+                .to_string(),
+            "expected an integral float, got 3.5"
+        );
+        assert_eq!(
+            deserialize("[3.0, 2]", FloatToIntPolicy::Integral).unwrap(),
+            [3, 2]
+        );
+        assert_eq!(
+            deserialize(code, FloatToIntPolicy::Truncate).unwrap(),
+            [3, 3, -3, 2]
+        );
+        assert_eq!(
+            deserialize(code, FloatToIntPolicy::Round).unwrap(),
+            [3, 4, -4, 2]
+        );
+
+        // floats which don't fit are still an error
+        for code in ["[float('nan')]", "[float('inf')]", "[1e6]"] {
+            assert!(deserialize(code, FloatToIntPolicy::Round).is_err());
+        }
+    }
+
+    #[test]
+    fn test_int_overflow() {
+        /// Accepts any integer, as types such as `serde_json::Number` do
+        #[derive(Debug, PartialEq)]
+        struct Wide(i128);
+
+        impl<'de> Deserialize<'de> for Wide {
+            fn deserialize<D: de::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                struct WideVisitor;
+
+                impl de::Visitor<'_> for WideVisitor {
+                    type Value = Wide;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("an integer")
+                    }
+
+                    fn visit_i64<E>(self, v: i64) -> std::result::Result<Wide, E> {
+                        Ok(Wide(v.into()))
+                    }
+
+                    fn visit_u64<E>(self, v: u64) -> std::result::Result<Wide, E> {
+                        Ok(Wide(v.into()))
+                    }
+
+                    fn visit_i128<E>(self, v: i128) -> std::result::Result<Wide, E> {
+                        Ok(Wide(v))
+                    }
+                }
+
+                deserializer.deserialize_i32(WideVisitor)
+            }
+        }
+
+        Python::with_gil(|py| {
+            let obj = py.eval_bound("[300, -2 ** 70]", None, None).unwrap();
+            let err = depythonize::<Vec<u8>>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "int 300 is out of range for u8");
+            let err = depythonize::<Vec<Wide>>(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "int -1180591620717411303424 is out of range for i32"
+            );
+
+            let mut de = Depythonizer::builder(&obj)
+                .int_overflow(IntOverflowPolicy::Widen)
+                .build();
+            assert_eq!(
+                Vec::<Wide>::deserialize(&mut de).unwrap(),
+                [Wide(300), Wide(-(1 << 70))]
+            );
+            let mut de = Depythonizer::builder(&obj)
+                .int_overflow(IntOverflowPolicy::Widen)
+                .build();
+            let err = Vec::<u8>::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "invalid value: integer `300`, expected u8");
+
+            let obj = py.eval_bound("[2 ** 63, -2 ** 130]", None, None).unwrap();
+            let err = depythonize::<JsonValue>(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "int -1361129467683753853853498429727072845824 is out of range for i128 and u128"
+            );
+            let mut de = Depythonizer::builder(&obj)
+                .int_overflow(IntOverflowPolicy::String)
+                .build();
+            assert_eq!(
+                JsonValue::deserialize(&mut de).unwrap(),
+                json!([1u64 << 63, "-1361129467683753853853498429727072845824"])
+            );
+        })
+    }
+
+    #[test]
+    fn test_borrowed_str() {
+        use std::borrow::Cow;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Entry<'a> {
+            key: &'a str,
+            #[serde(borrow)]
+            value: Cow<'a, str>,
+            tags: Vec<&'a str>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "[{'key': 'a', 'value': 'x', 'tags': ['t']}, {'key': 'b', 'value': 'é', 'tags': []}]",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let arena = BorrowArena::new();
+            let entries: Vec<Entry> = depythonize_borrowed(&obj, &arena).unwrap();
+            assert_eq!(
+                entries,
+                [
+                    Entry {
+                        key: "a",
+                        value: "x".into(),
+                        tags: vec!["t"],
+                    },
+                    Entry {
+                        key: "b",
+                        value: "é".into(),
+                        tags: vec![],
+                    },
+                ]
+            );
+            assert!(matches!(entries[1].value, Cow::Borrowed(_)));
+
+            // the strings outlive the objects they came from
+            drop(obj);
+            let obj = py.eval_bound("'c'", None, None).unwrap();
+            let c: &str = depythonize_borrowed(&obj, &arena).unwrap();
+            assert_eq!((entries[0].tags[0], c), ("t", "c"));
+        })
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Open {
+            a: u8,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Closed {
+            a: u8,
+            b: Option<u8>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Shape {
+            Point { a: u8 },
+        }
+
+        fn builder<'a, 'py>(
+            obj: &'a Bound<'py, PyAny>,
+            policy: UnknownFieldPolicy,
+        ) -> Depythonizer<'a, 'py> {
+            Depythonizer::builder(obj).unknown_fields(policy).build()
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'a': 1, 'c': 2, 'd': 4}", None, None)
+                .unwrap();
+            assert_eq!(depythonize::<Open>(&obj).unwrap(), Open { a: 1 });
+            assert!(depythonize::<Closed>(&obj).is_err());
+
+            let mut de = builder(&obj, UnknownFieldPolicy::Deny);
+            let err = Open::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "unknown fields `c`, `d`, expected `a`");
+            let mut de = builder(&obj, UnknownFieldPolicy::Deny);
+            let err = Closed::deserialize(&mut de).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "unknown fields `c`, `d`, expected `a` or `b`"
+            );
+
+            let mut de = builder(&obj, UnknownFieldPolicy::Ignore);
+            assert_eq!(
+                Closed::deserialize(&mut de).unwrap(),
+                Closed { a: 1, b: None }
+            );
+
+            let obj = py
+                .eval_bound("{'Point': {'a': 1, 'z': 0}}", None, None)
+                .unwrap();
+            assert!(depythonize::<Shape>(&obj).is_ok());
+            let mut de = builder(&obj, UnknownFieldPolicy::Deny);
+            let err = Shape::deserialize(&mut de).unwrap_err();
+            assert_eq!(err.to_string(), "unknown field `z`, expected `a`");
+
+            // maps are unaffected
+            let obj = py.eval_bound("{'a': 1}", None, None).unwrap();
+            let mut de = builder(&obj, UnknownFieldPolicy::Deny);
+            assert_eq!(
+                HashMap::<String, u8>::deserialize(&mut de).unwrap(),
+                hashmap! { "a".to_string() => 1 }
+            );
+        })
+    }
+
+    #[test]
+    fn test_field_matching() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            log_level: String,
+            #[serde(rename = "maxConn")]
+            max_conn: u8,
+        }
+
+        fn deserialize(code: &str, matching: FieldMatching) -> Result<Config> {
+            Python::with_gil(|py| {
+                let obj = py.eval_bound(code, None, None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .field_matching(matching)
+                    .unknown_fields(UnknownFieldPolicy::Deny)
+                    .build();
+                Config::deserialize(&mut de)
+            })
+        }
+
+        let expected = Config {
+            log_level: "info".to_string(),
+            max_conn: 4,
+        };
+        let code = "{'LOG_LEVEL': 'info', 'MaxConn': 4}";
+        assert!(deserialize(code, FieldMatching::Exact).is_err());
+        assert_eq!(
+            deserialize(code, FieldMatching::IgnoreCase).unwrap(),
+            expected
+        );
+
+        let code = "{'Log-Level': 'info', 'max_conn': 4}";
+        assert_eq!(
+            deserialize(code, FieldMatching::IgnoreCase)
+                .unwrap_err()
+                .to_string(),
+            "unknown fields `Log-Level`, `max_conn`, expected `log_level` or `maxConn`"
+        );
+        assert_eq!(
+            deserialize(code, FieldMatching::IgnoreCaseAndSeparators).unwrap(),
+            expected
+        );
+
+        // keys which match a field exactly take precedence
+        let code = "{'log_level': 'info', 'LOG_LEVEL': 'debug', 'maxConn': 4}";
+        assert_eq!(
+            deserialize(code, FieldMatching::IgnoreCase)
+                .unwrap_err()
+                .to_string(),
+            "duplicate field `log_level`"
+        );
+    }
+
+    #[test]
+    fn test_rename_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Profile {
+            user_name: String,
+            display_name: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Payload {
+            profile: Profile,
+            extra: HashMap<String, u8>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'profile': {'userName': 'alice', 'displayName': 'Alice'}, 'extra': {'userName': 1}}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj)
+                .rename_fields([("userName", "user_name")])
+                .rename_fields(HashMap::from([(
+                    "displayName".to_string(),
+                    "Display_Name".to_string(),
+                )]))
+                .field_matching(FieldMatching::IgnoreCase)
+                .build();
+            assert_eq!(
+                Payload::deserialize(&mut de).unwrap(),
+                Payload {
+                    profile: Profile {
+                        user_name: "alice".to_string(),
+                        display_name: Some("Alice".to_string()),
+                    },
+                    // maps keep their keys
+                    extra: hashmap! { "userName".to_string() => 1 },
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_field_case() {
+        use serde::Serialize;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Request {
+            request_id: u8,
+            http_headers: HashMap<String, String>,
+        }
+
+        let request = Request {
+            request_id: 1,
+            http_headers: hashmap! { "content_type".to_string() => "json".to_string() },
+        };
+        Python::with_gil(|py| {
+            for case in [ser::FieldCase::Camel, ser::FieldCase::Kebab] {
+                let pythonizer = crate::Pythonizer::builder(py).field_case(case).build();
+                let obj = request.serialize(pythonizer).unwrap();
+                let mut de = Depythonizer::builder(&obj).field_case(case).build();
+                assert_eq!(Request::deserialize(&mut de).unwrap(), request);
+            }
+
+            // the names of the fields match as well
+            let obj = py
+                .eval_bound(
+                    "{'request_id': 1, 'httpHeaders': {'content_type': 'json'}}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj)
+                .field_case(ser::FieldCase::Camel)
+                .build();
+            assert_eq!(Request::deserialize(&mut de).unwrap(), request);
+        })
+    }
+
+    #[test]
+    fn test_decimals() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Price {
+            amount: String,
+            approx: f64,
+            single: f32,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, policy: DecimalPolicy) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                locals
+                    .set_item("D", py.import_bound("decimal")?.getattr("Decimal")?)
+                    .unwrap();
+                let obj = py.eval_bound(code, None, Some(&locals)).unwrap();
+                let mut de = Depythonizer::builder(&obj).decimals(policy).build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let code = "{'amount': D('19.990'), 'approx': D('0.1'), 'single': D('0.5')}";
+        let expected = Price {
+            amount: "19.990".to_string(),
+            approx: 0.1,
+            single: 0.5,
+        };
+        assert_eq!(
+            deserialize::<Price>(code, DecimalPolicy::String).unwrap(),
+            expected
+        );
+        assert_eq!(
+            deserialize::<Price>(code, DecimalPolicy::Float).unwrap(),
+            expected
+        );
+        assert_eq!(
+            deserialize::<Price>(code, DecimalPolicy::ExactFloat)
+                .unwrap_err()
+                .to_string(),
+            "decimal 0.1 can't be converted into a float exactly"
+        );
+        let code = "{'amount': D('1'), 'approx': D('0.25'), 'single': D('Infinity')}";
+        assert!(deserialize::<Price>(code, DecimalPolicy::ExactFloat).is_ok());
+        // representable as an `f64` but not as an `f32`
+        let code = "{'amount': D('1'), 'approx': D('0.25'), 'single': D('0.1000000000000000055511151231257827021181583404541015625')}";
+        assert!(deserialize::<Price>(code, DecimalPolicy::ExactFloat).is_err());
+
+        // where any type is expected
+        let code = "[D('1.50'), D('-2')]";
+        assert_eq!(
+            deserialize::<JsonValue>(code, DecimalPolicy::String).unwrap(),
+            json!(["1.50", "-2"])
+        );
+        assert_eq!(
+            deserialize::<JsonValue>(code, DecimalPolicy::Float).unwrap(),
+            json!([1.5, -2.0])
+        );
+    }
+
+    #[test]
+    fn test_fractions() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Gear {
+            ratio: (i64, u64),
+            approx: f64,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, policy: FractionPolicy) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                locals
+                    .set_item("F", py.import_bound("fractions")?.getattr("Fraction")?)
+                    .unwrap();
+                let obj = py.eval_bound(code, None, Some(&locals)).unwrap();
+                let mut de = Depythonizer::builder(&obj).fractions(policy).build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let code = "{'ratio': F(-6, 8), 'approx': F(1, 3)}";
+        assert_eq!(
+            deserialize::<Gear>(code, FractionPolicy::Ratio)
+                .unwrap_err()
+                .to_string(),
+            "fraction 1/3 can't be converted into a float without `FractionPolicy::Float`"
+        );
+        assert_eq!(
+            deserialize::<Gear>(code, FractionPolicy::Float).unwrap(),
+            Gear {
+                ratio: (-3, 4),
+                approx: 1.0 / 3.0,
+            }
+        );
+
+        // where any type is expected
+        let code = "[F(1, 2), F(5)]";
+        assert_eq!(
+            deserialize::<JsonValue>(code, FractionPolicy::Ratio).unwrap(),
+            json!([[1, 2], [5, 1]])
+        );
+        assert_eq!(
+            deserialize::<JsonValue>(code, FractionPolicy::Float).unwrap(),
+            json!([0.5, 5.0])
+        );
+        assert_eq!(
+            deserialize::<Vec<Vec<i8>>>(code, FractionPolicy::Float).unwrap(),
+            [[1, 2], [5, 1]]
+        );
+    }
+
+    #[test]
+    fn test_complex() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Complex {
+            re: f64,
+            im: f64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Polar(f32, f32);
+
+        Python::with_gil(|py| {
+            let obj = py.eval_bound("1.5-0.25j", None, None).unwrap();
+            let pair: (f64, f64) = depythonize(&obj).unwrap();
+            assert_eq!(pair, (1.5, -0.25));
+            let c: Complex = depythonize(&obj).unwrap();
+            assert_eq!(c, Complex { re: 1.5, im: -0.25 });
+            let p: Polar = depythonize(&obj).unwrap();
+            assert_eq!(p, Polar(1.5, -0.25));
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any, json!([1.5, -0.25]));
+
+            let err = depythonize::<(f64, f64, f64)>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "expected sequence of length 3, got 2");
+        })
+    }
+
+    #[test]
+    fn test_uuid() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Record {
+            id: String,
+            #[serde(with = "serde_bytes")]
+            raw: Vec<u8>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "(lambda u: {'id': u, 'raw': u})(__import__('uuid').UUID('67e55044-10b1-426f-9247-bb680e5fe0c8'))",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let record: Record = depythonize(&obj).unwrap();
+            assert_eq!(record.id, "67e55044-10b1-426f-9247-bb680e5fe0c8");
+            assert_eq!(
+                record.raw,
+                obj.get_item("raw")
+                    .unwrap()
+                    .getattr("bytes")
+                    .unwrap()
+                    .extract::<Vec<u8>>()
+                    .unwrap()
+            );
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any["id"], "67e55044-10b1-426f-9247-bb680e5fe0c8");
+
+            // leading zeros are kept
+            let obj = py
+                .eval_bound("__import__('uuid').UUID(int=1)", None, None)
+                .unwrap();
+            let id: String = depythonize(&obj).unwrap();
+            assert_eq!(id, "00000000-0000-0000-0000-000000000001");
+        })
+    }
+
+    #[test]
+    fn test_path_like() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            root: PathBuf,
+            cache: String,
+            log: PathBuf,
+        }
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import pathlib\nclass Log:\n    def __fspath__(self):\n        return 'var/log'\nobj = {'root': pathlib.PurePosixPath('/srv'), 'cache': pathlib.PurePosixPath('cache/dir'), 'log': Log()}",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let config: Config = depythonize(&obj).unwrap();
+            assert_eq!(
+                config,
+                Config {
+                    root: PathBuf::from("/srv"),
+                    cache: "cache/dir".to_string(),
+                    log: PathBuf::from("var/log"),
+                }
+            );
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any["log"], "var/log");
+        })
+    }
+
+    #[test]
+    fn test_ip_objects() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Route {
+            gateway: IpAddr,
+            host: Ipv6Addr,
+            interface: String,
+            network: String,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "(lambda ip: {'gateway': ip.ip_address('192.0.2.1'), 'host': ip.IPv6Address('::1'), 'interface': ip.ip_interface('192.0.2.5/24'), 'network': ip.ip_network('2001:db8::/32')})(__import__('ipaddress'))",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let route: Route = depythonize(&obj).unwrap();
+            assert_eq!(
+                route,
+                Route {
+                    gateway: IpAddr::from([192, 0, 2, 1]),
+                    host: Ipv6Addr::LOCALHOST,
+                    interface: "192.0.2.5/24".to_string(),
+                    network: "2001:db8::/32".to_string(),
+                }
+            );
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any["network"], "2001:db8::/32");
+        })
+    }
+
+    #[test]
+    fn test_enum_variant_index() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        fn deserialize(code: &str, strict_bool: bool, subclasses: SubclassPolicy) -> Result<Color> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                py.run_bound("class Count(int): pass", Some(&locals), None)
+                    .unwrap();
+                let obj = py.eval_bound(code, Some(&locals), None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .strict_bool(strict_bool)
+                    .subclasses(subclasses)
+                    .build();
+                Color::deserialize(&mut de)
+            })
+        }
+
+        for strict_bool in [false, true] {
+            assert_eq!(
+                deserialize("1", strict_bool, SubclassPolicy::Accept).unwrap(),
+                Color::Green
+            );
+            assert_eq!(
+                deserialize("True", strict_bool, SubclassPolicy::Accept)
+                    .unwrap_err()
+                    .to_string(),
+                "expected either a str or dict for enum"
+            );
+        }
+        assert_eq!(
+            deserialize("Count(1)", false, SubclassPolicy::Accept).unwrap(),
+            Color::Green
+        );
+        assert_eq!(
+            deserialize("Count(1)", false, SubclassPolicy::Exact)
+                .unwrap_err()
+                .to_string(),
+            "expected an exact 'int', got an instance of its subclass 'Count'"
+        );
+    }
+
+    #[test]
+    fn test_enum_members() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Light {
+            color: Color,
+            label: String,
+            level: u8,
+            ratio: f64,
+            on: bool,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, policy: EnumMemberPolicy) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                py.run_bound(
+                    "import enum\nclass Color(enum.Enum):\n    Red = 0\n    Green = 1\nclass Level(enum.Enum):\n    LOW = 3\n    OFF = 0\nclass Ratio(enum.Enum):\n    HALF = 0.5\nclass Shade(str, enum.Enum):\n    Green = 'green'",
+                    Some(&locals),
+                    None,
+                )
+                .unwrap();
+                let obj = py.eval_bound(code, Some(&locals), None).unwrap();
+                let mut de = Depythonizer::builder(&obj).enum_members(policy).build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let code = "{'color': Color.Green, 'label': Color.Red, 'level': Level.LOW, 'ratio': Ratio.HALF, 'on': Level.OFF}";
+        assert!(deserialize::<Light>(code, EnumMemberPolicy::Name).is_err());
+        // the value of `Color.Red` isn't a string
+        assert_eq!(
+            deserialize::<Light>(code, EnumMemberPolicy::Value)
+                .unwrap_err()
+                .to_string(),
+            "unexpected type: 'int' object cannot be converted to 'PyString'"
+        );
+        let code =
+            "{'color': Color.Green, 'label': Color.Red, 'level': 3, 'ratio': 0.5, 'on': False}";
+        assert_eq!(
+            deserialize::<Light>(code, EnumMemberPolicy::Name).unwrap(),
+            Light {
+                color: Color::Green,
+                label: "Red".to_string(),
+                level: 3,
+                ratio: 0.5,
+                on: false,
+            }
+        );
+        let code = "{'color': Color.Green, 'label': 'Red', 'level': Level.LOW, 'ratio': Ratio.HALF, 'on': Level.OFF}";
+        assert_eq!(
+            deserialize::<Light>(code, EnumMemberPolicy::Value).unwrap(),
+            Light {
+                color: Color::Green,
+                label: "Red".to_string(),
+                level: 3,
+                ratio: 0.5,
+                on: false,
+            }
+        );
+
+        // members of `str` subclasses, as of `enum.StrEnum`, by name like other members
+        assert_eq!(
+            deserialize::<Color>("Shade.Green", EnumMemberPolicy::Name).unwrap(),
+            Color::Green
+        );
+        assert_eq!(
+            deserialize::<Color>("Shade.Green", EnumMemberPolicy::Value)
+                .unwrap_err()
+                .to_string(),
+            "unknown variant `green`, expected `Red` or `Green`"
+        );
+
+        // where any type is expected
+        let code = "[Color.Green, Ratio.HALF]";
+        assert_eq!(
+            deserialize::<JsonValue>(code, EnumMemberPolicy::Name).unwrap(),
+            json!(["Green", "HALF"])
+        );
+        assert_eq!(
+            deserialize::<JsonValue>(code, EnumMemberPolicy::Value).unwrap(),
+            json!([1, 0.5])
+        );
+    }
+
+    #[test]
+    fn test_conversion_methods() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Shape {
+            name: String,
+            points: Vec<Point>,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, methods: &[&str]) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                py.run_bound(
+                    "class Point:\n    def __init__(self, x, y):\n        self.x, self.y = x, y\n    def to_dict(self):\n        return {'x': self.x, 'y': self.y}\n\
+                     class Shape:\n    def __init__(self, *points):\n        self.points = points\n    def as_json(self):\n        return {'name': 'shape', 'points': self.points}\n    def to_dict(self):\n        raise ValueError('not this one')\n\
+                     class Broken:\n    def to_dict(self):\n        raise ValueError('broken')",
+                    Some(&locals),
+                    None,
+                )
+                .unwrap();
+                let obj = py.eval_bound(code, Some(&locals), None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .conversion_methods(methods.iter().copied())
+                    .build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let code = "Shape(Point(1, 2), Point(3, 4))";
+        assert!(deserialize::<Shape>(code, &[]).is_err());
+        // the first of the methods which an object has is called
+        let expected = Shape {
+            name: "shape".to_string(),
+            points: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        };
+        assert_eq!(
+            deserialize::<Shape>(code, &["as_json", "to_dict"]).unwrap(),
+            expected
+        );
+        assert_eq!(
+            deserialize::<JsonValue>(code, &["as_json", "to_dict"]).unwrap(),
+            json!({ "name": "shape", "points": [{ "x": 1, "y": 2 }, { "x": 3, "y": 4 }] })
+        );
+        assert_eq!(
+            deserialize::<Shape>(code, &["to_dict", "as_json"])
+                .unwrap_err()
+                .to_string(),
+            "ValueError: not this one"
+        );
+        assert_eq!(
+            deserialize::<Point>("Broken()", &["to_dict"])
+                .unwrap_err()
+                .to_string(),
+            "ValueError: broken"
+        );
+        // supported types aren't converted
+        assert_eq!(
+            deserialize::<Point>("{'x': 5, 'y': 6}", &["to_dict"]).unwrap(),
+            Point { x: 5, y: 6 }
+        );
+        assert!(deserialize::<Point>("Point", &["to_dict"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        fn deserialize<T: DeserializeOwned>(code: &str, parse_numbers: bool) -> Result<T> {
+            Python::with_gil(|py| {
+                let obj = py.eval_bound(code, None, None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .parse_numbers(parse_numbers)
+                    .build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        assert!(deserialize::<i32>("'42'", false).is_err());
+        assert!(deserialize::<f64>("'3.14'", false).is_err());
+        assert_eq!(deserialize::<i32>("'-42'", true).unwrap(), -42);
+        assert_eq!(deserialize::<u8>("'+7'", true).unwrap(), 7);
+        assert_eq!(
+            deserialize::<u128>("'340282366920938463463374607431768211455'", true).unwrap(),
+            u128::MAX
+        );
+        assert_eq!(deserialize::<f64>("'2.5'", true).unwrap(), 2.5);
+        assert_eq!(deserialize::<f32>("'1e3'", true).unwrap(), 1000.0);
+        assert_eq!(deserialize::<f64>("'42'", true).unwrap(), 42.0);
+        assert!(deserialize::<f64>("'nan'", true).unwrap().is_nan());
+        assert_eq!(
+            deserialize::<Vec<i64>>("['1', 2]", true).unwrap(),
+            vec![1, 2]
+        );
+
+        assert_eq!(
+            deserialize::<u8>("'300'", true).unwrap_err().to_string(),
+            "int 300 is out of range for u8"
+        );
+        assert_eq!(
+            deserialize::<i32>("'3.0'", true).unwrap_err().to_string(),
+            "invalid integer \"3.0\": invalid digit found in string"
+        );
+        assert_eq!(
+            deserialize::<i32>("' 4'", true).unwrap_err().to_string(),
+            "invalid integer \" 4\": invalid digit found in string"
+        );
+        assert_eq!(
+            deserialize::<i32>("'1_000'", true).unwrap_err().to_string(),
+            "invalid integer \"1_000\": invalid digit found in string"
+        );
+        assert_eq!(
+            deserialize::<f64>("'3,14'", true).unwrap_err().to_string(),
+            "invalid float \"3,14\": invalid float literal"
+        );
+        // strings are still strings where any type is expected
+        assert_eq!(deserialize::<JsonValue>("'42'", true).unwrap(), json!("42"));
+    }
+
+    #[test]
+    fn test_fill_defaults() {
+        #[derive(Debug, Default, Deserialize, PartialEq)]
+        struct Limits {
+            depth: u8,
+            ratio: f32,
+        }
+
+        #[derive(Debug, Default, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            verbose: bool,
+            retries: i64,
+            timeout: Option<f64>,
+            hosts: Vec<String>,
+            labels: HashMap<String, String>,
+            pair: (u16, char),
+            limits: Limits,
+            extra: JsonValue,
+        }
+
+        #[derive(Debug, Default, Deserialize, PartialEq)]
+        enum Mode {
+            #[default]
+            Fast,
+            Slow,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Job {
+            mode: Mode,
+        }
+
+        fn seven() -> u8 {
+            7
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Task {
+            #[serde(default)]
+            mode: Mode,
+            #[serde(default = "seven")]
+            priority: u8,
+            #[serde(alias = "attempts")]
+            retries: u8,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, fill_defaults: bool) -> Result<T> {
+            Python::with_gil(|py| {
+                let obj = py.eval_bound(code, None, None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .fill_defaults(fill_defaults)
+                    .build();
+                de.deserialize()
+            })
+        }
+
+        assert_eq!(
+            deserialize::<Config>("{}", false).unwrap_err().to_string(),
+            "missing field `name`"
+        );
+        assert_eq!(
+            deserialize::<Config>("{}", true).unwrap(),
+            Config::default()
+        );
+        assert_eq!(
+            deserialize::<Config>(
+                "{'name': 'app', 'retries': None, 'timeout': 1.5, 'limits': {'ratio': 0.5}}",
+                true
+            )
+            .unwrap(),
+            Config {
+                name: "app".to_string(),
+                timeout: Some(1.5),
+                limits: Limits {
+                    depth: 0,
+                    ratio: 0.5,
+                },
+                ..Config::default()
+            }
+        );
+        // `None` is only replaced in structs
+        assert!(deserialize::<Vec<i64>>("[None]", true).is_err());
+        assert_eq!(
+            deserialize::<Job>("{}", true).unwrap_err().to_string(),
+            "enum Mode has no default to fill a missing value with"
+        );
+        assert!(deserialize::<Job>("{'mode': 'Fast'}", true).is_ok());
+
+        // the defaults of serde take precedence
+        assert_eq!(
+            deserialize::<Task>("{'attempts': 2}", true).unwrap(),
+            Task {
+                mode: Mode::Fast,
+                priority: 7,
+                retries: 2,
+            }
+        );
+        assert_eq!(
+            deserialize::<Task>("{'mode': 'Slow', 'priority': None}", true).unwrap(),
+            Task {
+                mode: Mode::Slow,
+                priority: 0,
+                retries: 0,
+            }
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Batch {
+            items: Vec<u32>,
+            name: String,
+            retries: u32,
+        }
+
+        assert_eq!(
+            deserialize::<Batch>("{'items': [0, 1, 2], 'name': 'x'}", true).unwrap(),
+            Batch {
+                items: vec![0, 1, 2],
+                name: "x".to_string(),
+                retries: 0,
+            }
+        );
+        // a generator is exhausted by the first attempt, so there is no other
+        assert_eq!(
+            deserialize::<Batch>("{'items': (i for i in range(3)), 'name': 'x'}", true)
+                .unwrap_err()
+                .to_string(),
+            "missing field `retries`"
+        );
+
+        // nor is a conversion method called again
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "class Record:\n    calls = 0\n    def to_dict(self):\n        Record.calls += 1\n        return {'items': [], 'name': 'x'}",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = py.eval_bound("Record()", Some(&locals), None).unwrap();
+            let mut de = Depythonizer::builder(&obj)
+                .fill_defaults(true)
+                .conversion_methods(["to_dict"])
+                .build();
+            let err = de.deserialize::<Batch>().unwrap_err();
+            assert_eq!(err.to_string(), "missing field `retries`");
+            let calls = py.eval_bound("Record.calls", Some(&locals), None).unwrap();
+            assert_eq!(calls.extract::<u32>().unwrap(), 1);
+        });
+    }
+
+    #[test]
+    fn test_numeric_protocols() {
+        fn deserialize<T: DeserializeOwned>(
+            code: &str,
+            numeric_protocols: bool,
+            float_to_int: FloatToIntPolicy,
+        ) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                py.run_bound(
+                    "import decimal\nclass Index:\n    def __index__(self): return 7\nclass Meters:\n    def __float__(self): return 2.5",
+                    Some(&locals),
+                    None,
+                )
+                .unwrap();
+                let obj = py.eval_bound(code, Some(&locals), None).unwrap();
+                let mut de = Depythonizer::builder(&obj)
+                    .numeric_protocols(numeric_protocols)
+                    .float_to_int(float_to_int)
+                    .build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let error = FloatToIntPolicy::Error;
+        // `__index__` and `__float__` are always used where a float is expected
+        assert_eq!(deserialize::<f64>("Index()", false, error).unwrap(), 7.0);
+        assert_eq!(deserialize::<f64>("Meters()", false, error).unwrap(), 2.5);
+        assert_eq!(deserialize::<i64>("Index()", false, error).unwrap(), 7);
+        assert!(deserialize::<i64>("decimal.Decimal('3')", false, error).is_err());
+        assert!(deserialize::<JsonValue>("Index()", false, error).is_err());
+        assert!(deserialize::<JsonValue>("Meters()", false, error).is_err());
+
+        assert_eq!(
+            deserialize::<i64>("decimal.Decimal('3')", true, error).unwrap(),
+            3
+        );
+        assert_eq!(
+            deserialize::<i64>("Meters()", true, error)
+                .unwrap_err()
+                .to_string(),
+            "expected an integral number, got 2.5"
+        );
+        assert_eq!(
+            deserialize::<i64>("Meters()", true, FloatToIntPolicy::Round).unwrap(),
+            3
+        );
+        assert_eq!(
+            deserialize::<u8>("decimal.Decimal('300')", true, error)
+                .unwrap_err()
+                .to_string(),
+            "int 300 is out of range for u8"
+        );
+        assert_eq!(
+            deserialize::<JsonValue>("[Index(), Meters()]", true, error).unwrap(),
+            json!([7, 2.5])
+        );
+        // decimals are still converted by the decimal policy where any type is expected
+        assert_eq!(
+            deserialize::<JsonValue>("decimal.Decimal('3')", true, error).unwrap(),
+            json!("3")
+        );
+        assert!(deserialize::<i64>("'3'", true, error).is_err());
+    }
+
+    #[test]
+    fn test_subclasses() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reading {
+            name: String,
+            count: i64,
+            ratio: f64,
+            tags: Vec<String>,
+        }
+
+        fn deserialize<T: DeserializeOwned>(code: &str, policy: SubclassPolicy) -> Result<T> {
+            Python::with_gil(|py| {
+                let locals = PyDict::new_bound(py);
+                py.run_bound(
+                    "import enum\nclass Name(str): pass\nclass Count(int): pass\nclass Ratio(float): pass\nclass Table(dict): pass\nclass Tags(list): pass\nclass Level(enum.IntEnum):\n    LOW = 1\n    HIGH = 2",
+                    Some(&locals),
+                    None,
+                )
+                .unwrap();
+                let obj = py.eval_bound(code, Some(&locals), None).unwrap();
+                let mut de = Depythonizer::builder(&obj).subclasses(policy).build();
+                T::deserialize(&mut de)
+            })
+        }
+
+        let code = "Table(name=Name('a'), count=Count(2), ratio=Ratio(0.5), tags=Tags(['x']))";
+        let expected = Reading {
+            name: "a".to_string(),
+            count: 2,
+            ratio: 0.5,
+            tags: vec!["x".to_string()],
+        };
+        assert_eq!(
+            deserialize::<Reading>(code, SubclassPolicy::Accept).unwrap(),
+            expected
+        );
+        assert_eq!(
+            deserialize::<JsonValue>(code, SubclassPolicy::Accept).unwrap(),
+            json!({ "name": "a", "count": 2, "ratio": 0.5, "tags": ["x"] })
+        );
+        assert_eq!(
+            deserialize::<Reading>(code, SubclassPolicy::Exact)
+                .unwrap_err()
+                .to_string(),
+            "expected an exact 'dict', got an instance of its subclass 'Table'"
+        );
+        for (code, base, subclass) in [
+            ("Name('a')", "str", "Name"),
+            ("Count(2)", "int", "Count"),
+            ("Ratio(0.5)", "float", "Ratio"),
+            ("Tags(['x'])", "list", "Tags"),
+        ] {
+            let code = format!(
+                "{{'name': 'a', 'count': 2, 'ratio': 0.5, 'tags': ['x'], '{}': {}}}",
+                base, code
+            );
+            let err = deserialize::<JsonValue>(&code, SubclassPolicy::Exact).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "expected an exact '{}', got an instance of its subclass '{}'",
+                    base, subclass
+                )
+            );
+        }
+        let code = "{'name': 'a', 'count': 2, 'ratio': 0.5, 'tags': ['x']}";
+        assert_eq!(
+            deserialize::<Reading>(code, SubclassPolicy::Exact).unwrap(),
+            expected
+        );
+        // `bool` is a subclass of `int` which can't be subclassed itself
+        assert!(deserialize::<bool>("True", SubclassPolicy::Exact).unwrap());
+
+        // members of `int` subclasses are their values unless exact types are required,
+        // when they are converted like other enum members
+        assert_eq!(
+            deserialize::<JsonValue>("Level.HIGH", SubclassPolicy::Accept).unwrap(),
+            json!(2)
+        );
+        assert_eq!(
+            deserialize::<i64>("Level.HIGH", SubclassPolicy::Accept).unwrap(),
+            2
+        );
+        assert_eq!(
+            deserialize::<JsonValue>("Level.HIGH", SubclassPolicy::Exact).unwrap(),
+            json!("HIGH")
+        );
+        assert!(deserialize::<i64>("Level.HIGH", SubclassPolicy::Exact).is_err());
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "__import__('enum').IntEnum('Level', ['LOW', 'HIGH']).HIGH",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let mut de = Depythonizer::builder(&obj)
+                .subclasses(SubclassPolicy::Exact)
+                .enum_members(EnumMemberPolicy::Value)
+                .build();
+            assert_eq!(i64::deserialize(&mut de).unwrap(), 2);
+        });
+    }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_numpy_scalar_subclasses() {
+        Python::with_gil(|py| {
+            for policy in [SubclassPolicy::Accept, SubclassPolicy::Exact] {
+                let obj = py
+                    .eval_bound(
+                        "[__import__('numpy').float64(0.5), __import__('numpy').int64(3), __import__('numpy').str_('a')]",
+                        None,
+                        None,
+                    )
+                    .unwrap();
+                let mut de = Depythonizer::builder(&obj).subclasses(policy).build();
+                let actual = JsonValue::deserialize(&mut de).unwrap();
+                assert_eq!(actual, json!([0.5, 3, "a"]));
+            }
+        })
+    }
+
+    #[test]
+    fn test_object_attributes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Node {
+            name: String,
+            weight: f64,
+            child: Option<Box<Node>>,
+        }
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "class Base:\n    __slots__ = 'name'\n\
+                 class Node(Base):\n    \
+                     __slots__ = ('weight', 'child', 'unset')\n    \
+                     def __init__(self, name, weight, child=None):\n        \
+                         self.name = name\n        \
+                         self.weight = weight\n        \
+                         self.child = child\n\
+                 class Plain:\n    \
+                     def __init__(self):\n        \
+                         self.name = 'plain'\n        \
+                         self.weight = 0.5\n        \
+                         self.child = Node('leaf', 1.5)\n\
+                 obj = [Node('root', 2.0, Node('leaf', 1.0)), Plain()]",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            assert!(depythonize::<Vec<Node>>(&obj).is_err());
+
+            let mut de = Depythonizer::builder(&obj).object_attributes(true).build();
+            let nodes = Vec::<Node>::deserialize(&mut de).unwrap();
+            assert_eq!(
+                nodes,
+                [
+                    Node {
+                        name: "root".to_string(),
+                        weight: 2.0,
+                        child: Some(Box::new(Node {
+                            name: "leaf".to_string(),
+                            weight: 1.0,
+                            child: None,
+                        })),
+                    },
+                    Node {
+                        name: "plain".to_string(),
+                        weight: 0.5,
+                        child: Some(Box::new(Node {
+                            name: "leaf".to_string(),
+                            weight: 1.5,
+                            child: None,
+                        })),
+                    },
+                ]
+            );
+
+            let mut de = Depythonizer::builder(&obj).object_attributes(true).build();
+            let any = JsonValue::deserialize(&mut de).unwrap();
+            assert_eq!(
+                any[0],
+                json!({ "name": "root", "weight": 2.0, "child": { "name": "leaf", "weight": 1.0, "child": null } })
+            );
+
+            // objects without attributes are still unsupported
+            let obj = py.eval_bound("object()", None, None).unwrap();
+            let mut de = Depythonizer::builder(&obj).object_attributes(true).build();
+            assert!(JsonValue::deserialize(&mut de).is_err());
+        })
+    }
+
+    #[test]
+    fn test_collections_dicts() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inventory {
+            counts: HashMap<String, usize>,
+            groups: HashMap<String, Vec<i32>>,
+            ordered: Vec<(String, i32)>,
+        }
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import collections\n\
+                 groups = collections.defaultdict(list)\n\
+                 groups['odd'].append(1)\n\
+                 ordered = collections.OrderedDict([('b', 2), ('a', 1)])\n\
+                 obj = collections.OrderedDict([('counts', collections.Counter('abca')), ('groups', groups), ('ordered', list(ordered.items()))])",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let inventory: Inventory = depythonize(&obj).unwrap();
+            assert_eq!(
+                inventory,
+                Inventory {
+                    counts: hashmap! {
+                        "a".to_string() => 2,
+                        "b".to_string() => 1,
+                        "c".to_string() => 1,
+                    },
+                    groups: hashmap! {"odd".to_string() => vec![1]},
+                    ordered: vec![("b".to_string(), 2), ("a".to_string(), 1)],
+                }
+            );
+
+            // keys keep the order of an `OrderedDict`
+            let ordered = locals.get_item("ordered").unwrap().unwrap();
+            let any: JsonValue = depythonize(&ordered).unwrap();
+            assert_eq!(any, json!({ "a": 1, "b": 2 }));
+            let access = Depythonizer::from_object(&ordered).dict_access().unwrap();
+            let keys: Vec<String> = access.keys.extract().unwrap();
+            assert_eq!(keys, ["b", "a"]);
+
+            // a `Counter` may hold negative counts, which aren't `usize`s
+            let counts = obj.get_item("counts").unwrap();
+            counts.set_item("d", -1).unwrap();
+            let err = depythonize::<HashMap<String, usize>>(&counts).unwrap_err();
+            assert_eq!(err.to_string(), "int -1 is out of range for u64");
+            let counts: HashMap<String, i64> = depythonize(&counts).unwrap();
+            assert_eq!(counts["d"], -1);
+        })
+    }
+
+    #[test]
+    fn test_mapping_protocol() {
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import collections, types\n\
+                 class Lookup:\n    \
+                     def __init__(self, data):\n        \
+                         self.data = data\n    \
+                     def keys(self):\n        \
+                         return self.data.keys()\n    \
+                     def __getitem__(self, key):\n        \
+                         return self.data[key]\n\
+                 class Sub(dict):\n    \
+                     pass\n\
+                 obj = [Lookup({'a': 1}), Sub(a=2), collections.ChainMap({'a': 3}, {'a': 0, 'b': 4}), types.MappingProxyType({'a': 5})]",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let maps: Vec<HashMap<String, i32>> = depythonize(&obj).unwrap();
+            assert_eq!(
+                maps,
+                [
+                    hashmap! {"a".to_string() => 1},
+                    hashmap! {"a".to_string() => 2},
+                    hashmap! {"a".to_string() => 3, "b".to_string() => 4},
+                    hashmap! {"a".to_string() => 5},
+                ]
+            );
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any[0], json!({ "a": 1 }));
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Record {
+                a: i32,
+            }
+            let record: Record = depythonize(&obj.get_item(0).unwrap()).unwrap();
+            assert_eq!(record, Record { a: 1 });
+        })
+    }
+
+    #[test]
+    fn test_sequence_protocol() {
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import array, collections\n\
+                 class Squares:\n    \
+                     def __len__(self):\n        \
+                         return 3\n    \
+                     def __getitem__(self, index):\n        \
+                         if index >= 3:\n            \
+                             raise IndexError(index)\n        \
+                         return index * index\n\
+                 obj = [Squares(), collections.deque([1, 2]), array.array('i', [7]), collections.UserList([5])]",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let seqs: Vec<Vec<i32>> = depythonize(&obj).unwrap();
+            assert_eq!(seqs, [vec![0, 1, 4], vec![1, 2], vec![7], vec![5]]);
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any, json!([[0, 1, 4], [1, 2], [7], [5]]));
+            let tuple: (i32, i32, i32) = depythonize(&obj.get_item(0).unwrap()).unwrap();
+            assert_eq!(tuple, (0, 1, 4));
+        })
+    }
+
+    #[test]
+    fn test_iterators() {
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "def countdown(n):\n    \
+                     while n > 0:\n        \
+                         yield n\n        \
+                         n -= 1\n\
+                 obj = {'generator': countdown(3), 'map': map(abs, [-1, 2]), 'filter': filter(None, [0, 5]), 'view': {'a': 1, 'b': 2}.values()}",
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let seqs: HashMap<String, Vec<i32>> = depythonize(&obj).unwrap();
+            assert_eq!(
+                seqs,
+                hashmap! {
+                    "generator".to_string() => vec![3, 2, 1],
+                    "map".to_string() => vec![1, 2],
+                    "filter".to_string() => vec![5],
+                    "view".to_string() => vec![1, 2],
+                }
+            );
+            // generators are consumed
+            let seqs: HashMap<String, Vec<i32>> = depythonize(&obj).unwrap();
+            assert!(seqs["generator"].is_empty());
+
+            // iterators are sequences where any type is expected
+            let obj = py.eval_bound("iter(range(2))", None, None).unwrap();
+            let any: JsonValue = depythonize(&obj).unwrap();
+            assert_eq!(any, json!([0, 1]));
+
+            // mappings aren't sequences of their keys
+            let obj = py.eval_bound("{'a': 1}", None, None).unwrap();
+            assert!(depythonize::<Vec<String>>(&obj).is_err());
+        })
+    }
 }
@@ -11,55 +11,67 @@ pub type Result<T> = result::Result<T, PythonizeError>;
 /// Errors that can occur when serializing/deserializing Python objects
 pub struct PythonizeError {
     pub(crate) inner: Box<ErrorImpl>,
+    /// The field whose absence the error reports, if it comes from
+    /// [`de::Error::missing_field`]
+    missing_field: Option<&'static str>,
 }
 
 impl PythonizeError {
+    fn new(inner: ErrorImpl) -> Self {
+        Self {
+            inner: Box::new(inner),
+            missing_field: None,
+        }
+    }
+
+    /// The field whose absence the error reports, if it is serde's error for a struct
+    /// field that is missing and has no default
+    pub(crate) fn missing_field(&self) -> Option<&'static str> {
+        self.missing_field
+    }
+
     pub(crate) fn msg<T>(text: T) -> Self
     where
         T: ToString,
     {
-        Self {
-            inner: Box::new(ErrorImpl::Message(text.to_string())),
-        }
+        Self::new(ErrorImpl::Message(text.to_string()))
     }
 
     pub(crate) fn unsupported_type<T>(t: T) -> Self
     where
         T: ToString,
     {
-        Self {
-            inner: Box::new(ErrorImpl::UnsupportedType(t.to_string())),
-        }
+        Self::new(ErrorImpl::UnsupportedType(t.to_string()))
     }
 
     pub(crate) fn dict_key_not_string() -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::DictKeyNotString),
-        }
+        Self::new(ErrorImpl::DictKeyNotString)
     }
 
     pub(crate) fn incorrect_sequence_length(expected: usize, got: usize) -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::IncorrectSequenceLength { expected, got }),
-        }
+        Self::new(ErrorImpl::IncorrectSequenceLength { expected, got })
     }
 
     pub(crate) fn invalid_enum_type() -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::InvalidEnumType),
-        }
+        Self::new(ErrorImpl::InvalidEnumType)
     }
 
     pub(crate) fn invalid_length_enum() -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::InvalidLengthEnum),
-        }
+        Self::new(ErrorImpl::InvalidLengthEnum)
     }
 
     pub(crate) fn invalid_length_char() -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::InvalidLengthChar),
-        }
+        Self::new(ErrorImpl::InvalidLengthChar)
+    }
+
+    pub(crate) fn unknown_fields(unknown: &[String], expected: &[&str]) -> Self {
+        Self::new(ErrorImpl::UnknownFields {
+            unknown: unknown.to_vec(),
+            expected: expected
+                .iter()
+                .map(|field| format!("`{}`", field))
+                .collect(),
+        })
     }
 }
 
@@ -84,6 +96,11 @@ pub enum ErrorImpl {
     InvalidLengthEnum,
     /// Expected a `char`, but got a Python str that was not length 1
     InvalidLengthChar,
+    /// Dict keys which are not fields of the struct, quoted, and the quoted fields
+    UnknownFields {
+        unknown: Vec<String>,
+        expected: Vec<String>,
+    },
 }
 
 impl error::Error for PythonizeError {}
@@ -104,6 +121,18 @@ impl Display for PythonizeError {
                 f.write_str("expected tagged enum dict to have exactly 1 key")
             }
             ErrorImpl::InvalidLengthChar => f.write_str("expected a str of length 1 for char"),
+            ErrorImpl::UnknownFields { unknown, expected } => {
+                match unknown.as_slice() {
+                    [field] => write!(f, "unknown field {}, ", field)?,
+                    fields => write!(f, "unknown fields {}, ", fields.join(", "))?,
+                }
+                match expected.as_slice() {
+                    [] => f.write_str("there are no fields"),
+                    [field] => write!(f, "expected {}", field),
+                    [first, second] => write!(f, "expected {} or {}", first, second),
+                    fields => write!(f, "expected one of {}", fields.join(", ")),
+                }
+            }
         }
     }
 }
@@ -119,9 +148,7 @@ impl ser::Error for PythonizeError {
     where
         T: Display,
     {
-        Self {
-            inner: Box::new(ErrorImpl::Message(msg.to_string())),
-        }
+        Self::new(ErrorImpl::Message(msg.to_string()))
     }
 }
 
@@ -130,8 +157,13 @@ impl de::Error for PythonizeError {
     where
         T: Display,
     {
+        Self::new(ErrorImpl::Message(msg.to_string()))
+    }
+
+    fn missing_field(field: &'static str) -> Self {
         Self {
-            inner: Box::new(ErrorImpl::Message(msg.to_string())),
+            missing_field: Some(field),
+            ..Self::custom(format_args!("missing field `{}`", field))
         }
     }
 }
@@ -139,27 +171,21 @@ impl de::Error for PythonizeError {
 /// Convert an exception raised in Python to a `PythonizeError`
 impl From<PyErr> for PythonizeError {
     fn from(other: PyErr) -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::PyErr(other)),
-        }
+        Self::new(ErrorImpl::PyErr(other))
     }
 }
 
 /// Handle errors that occur when attempting to use `PyAny::cast_as`
 impl<'a, 'py> From<DowncastError<'a, 'py>> for PythonizeError {
     fn from(other: DowncastError<'a, 'py>) -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::UnexpectedType(other.to_string())),
-        }
+        Self::new(ErrorImpl::UnexpectedType(other.to_string()))
     }
 }
 
 /// Handle errors that occur when attempting to use `PyAny::cast_as`
 impl<'py> From<DowncastIntoError<'py>> for PythonizeError {
     fn from(other: DowncastIntoError<'py>) -> Self {
-        Self {
-            inner: Box::new(ErrorImpl::UnexpectedType(other.to_string())),
-        }
+        Self::new(ErrorImpl::UnexpectedType(other.to_string()))
     }
 }
 
@@ -175,7 +201,8 @@ impl From<PythonizeError> for PyErr {
             | ErrorImpl::InvalidEnumType => PyTypeError::new_err(other.to_string()),
             ErrorImpl::IncorrectSequenceLength { .. }
             | ErrorImpl::InvalidLengthEnum
-            | ErrorImpl::InvalidLengthChar => PyValueError::new_err(other.to_string()),
+            | ErrorImpl::InvalidLengthChar
+            | ErrorImpl::UnknownFields { .. } => PyValueError::new_err(other.to_string()),
         }
     }
 }
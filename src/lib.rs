@@ -1,14 +1,40 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "burn")]
+mod burn;
 mod de;
 mod error;
+pub mod native;
+#[cfg(feature = "pandas")]
+mod pandas;
+mod pythonized;
 mod ser;
+mod staged;
 
+#[cfg(feature = "arrow")]
+pub use crate::arrow::{depythonize_record_batch, pythonize_record_batch};
+#[cfg(feature = "burn")]
+pub use crate::burn::{depythonize_tensor, pythonize_tensor};
 #[allow(deprecated)]
 pub use crate::de::depythonize_bound;
-pub use crate::de::{depythonize, Depythonizer};
+pub use crate::de::{
+    depythonize, depythonize_borrowed, BorrowArena, DecimalPolicy, Depythonizer,
+    DepythonizerBuilder, EnumMemberPolicy, FieldMatching, FloatToIntPolicy, FractionPolicy,
+    IntOverflowPolicy, SubclassPolicy, UnknownFieldPolicy,
+};
 pub use crate::error::{PythonizeError, Result};
+#[cfg(feature = "pandas")]
+pub use crate::pandas::{depythonize_records, pythonize_dataframe};
+pub use crate::pythonized::Pythonized;
 pub use crate::ser::{
-    pythonize, pythonize_custom, PythonizeDefault, PythonizeListType, PythonizeMappingType,
-    PythonizeNamedMappingType, PythonizeTypes, PythonizeUnnamedMappingAdapter, Pythonizer,
+    pythonize, pythonize_custom, pythonize_into, FieldCase, KeyPolicy, NonFinitePolicy,
+    PythonizeBytesType, PythonizeDataclass, PythonizeDefault, PythonizeFrozenDict,
+    PythonizeImmutable, PythonizeImmutablesMap, PythonizeListType, PythonizeMappingProxy,
+    PythonizeMappingType, PythonizeNamedMappingType, PythonizeNamedTuple, PythonizeOrderedDict,
+    PythonizeSetType, PythonizeSortedDict, PythonizeStructType, PythonizeTupleType, PythonizeTypes,
+    PythonizeUnitVariantType, PythonizeUnnamedMappingAdapter, Pythonizer, PythonizerBuilder,
+    UnitStructPolicy,
 };
+pub use crate::staged::Staged;
@@ -0,0 +1,925 @@
+//! Serde adapters for Rust types which have a native Python counterpart.
+//!
+//! Serde's data model cannot tell a serializer that a sequence is really a
+//! set, so by default [`pythonize`](crate::pythonize) has to produce the same
+//! structures as `serde_json`. Annotating a field with
+//! `#[serde(with = "pythonize::native")]` tags its value so that `pythonize`
+//! produces the matching Python type, and [`depythonize`](crate::depythonize)
+//! accepts it back. Other serde formats see the intermediate data which `pythonize`
+//! converts, such as the `(days, seconds, microseconds)` of a `datetime.timedelta`,
+//! rather than what the type would serialize as without the annotation.
+//!
+//! | Rust type | Python type | Cargo feature |
+//! |-----------|-------------|---------------|
+//! | `HashSet<T>`, `BTreeSet<T>` | [`PythonizeTypes::Set`](crate::PythonizeTypes::Set) | |
+//! | `std::time::Duration`, in whole microseconds | `datetime.timedelta` | `std-duration` |
+//! | `std::time::SystemTime` | `datetime.datetime` in UTC | |
+//! | `std::path::PathBuf` | `pathlib.Path`, from any `os.PathLike` | |
+//! | `std::net::IpAddr`, `std::net::Ipv4Addr`, `std::net::Ipv6Addr` | `ipaddress.IPv4Address`, `ipaddress.IPv6Address` | |
+//! | `std::net::SocketAddr` | `(host, port)` or `(host, port, flowinfo, scope_id)` tuple | |
+//! | unit-only `enum`s, with [`enum_member`] | member of a generated `enum.Enum` class | |
+//! | `Vec<T>` of numbers, with [`packed`] | `list` created in bulk, or `bytes` for `Vec<u8>` | |
+//! | `chrono::DateTime<Utc>`, `chrono::DateTime<FixedOffset>` | `datetime.datetime` | `chrono` |
+//! | `chrono::NaiveDateTime` | naive `datetime.datetime` | `chrono` |
+//! | `chrono::NaiveDate` | `datetime.date` | `chrono` |
+//! | `chrono::NaiveTime` | `datetime.time` | `chrono` |
+//! | `chrono::Duration` | `datetime.timedelta` | `chrono` |
+//! | `chrono::DateTime<chrono_tz::Tz>` | `datetime.datetime` with a `zoneinfo.ZoneInfo` time zone, or from a `pytz` one | `chrono-tz` |
+//! | `time::OffsetDateTime` | `datetime.datetime` | `time` |
+//! | `time::PrimitiveDateTime` | naive `datetime.datetime` | `time` |
+//! | `time::Date` | `datetime.date` | `time` |
+//! | `time::Time` | `datetime.time` | `time` |
+//! | `time::Duration` | `datetime.timedelta` | `time` |
+//! | `jiff::Timestamp` | `datetime.datetime` in UTC | `jiff` |
+//! | `jiff::Zoned` | `datetime.datetime` with a `zoneinfo.ZoneInfo` time zone, or from a `pytz` one | `jiff` |
+//! | `uuid::Uuid` | `uuid.UUID` | `uuid` |
+//! | `rust_decimal::Decimal` | `decimal.Decimal` | `rust_decimal` |
+//! | `bigdecimal::BigDecimal` | `decimal.Decimal` | `bigdecimal` |
+//! | `num_bigint::BigInt`, `num_bigint::BigUint` | `int` | `num-bigint` |
+//! | `num_complex::Complex<f64>`, `num_complex::Complex<f32>` | `complex` | `num-complex` |
+//! | `num_rational::Ratio<T>` | `fractions.Fraction` | `num-rational` |
+//! | `url::Url` | `str`, or `urllib.parse.SplitResult` with [`split_url`] | `url` |
+//! | `semver::Version` | `packaging.version.Version`, or `str` if `packaging` is not installed | `semver` |
+//! | `ndarray::Array<A, D>`, `ndarray::ArrayView<A, D>` of an [`Element`] type | `numpy.ndarray` | `numpy` |
+//! | `nalgebra::OMatrix<T, R, C>` of an [`Element`] type, such as `SMatrix`, `SVector` and `DMatrix` | 2-D `numpy.ndarray`, or 1-D for vectors | `nalgebra` |
+//! | `polars::frame::DataFrame`, shared through the Arrow C stream interface | `polars.DataFrame` | `polars` |
+//! | `polars::series::Series`, shared through the Arrow C stream interface | `polars.Series` | `polars` |
+//!
+//! `Option`s of any of these types are also supported.
+//! Datetimes, dates and times can be converted into ISO 8601 strings instead with
+//! [`PythonizerBuilder::datetime_iso`](crate::PythonizerBuilder::datetime_iso).
+//!
+//! ```rust
+//! use std::collections::HashSet;
+//!
+//! use pyo3::prelude::*;
+//! use pyo3::types::PySet;
+//! use pythonize::pythonize;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Tagged {
+//!     #[serde(with = "pythonize::native")]
+//!     tags: HashSet<String>,
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let tagged = Tagged {
+//!         tags: HashSet::from(["a".to_string()]),
+//!     };
+//!     let obj = pythonize(py, &tagged).unwrap();
+//!     assert!(obj.get_item("tags").unwrap().is_instance_of::<PySet>());
+//! })
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyImportError;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, PyAny, PyObject, PyResult, Python};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+use serde::ser::{self, Serialize, Serializer};
+
+pub(crate) mod array;
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+#[cfg(feature = "chrono")]
+mod chrono;
+mod datetime;
+#[cfg(feature = "std-duration")]
+mod duration;
+pub mod enum_member;
+pub(crate) mod ipaddress;
+#[cfg(feature = "jiff")]
+mod jiff;
+#[cfg(feature = "nalgebra")]
+mod nalgebra;
+#[cfg(feature = "numpy")]
+mod ndarray;
+mod net;
+#[cfg(feature = "num-bigint")]
+mod num_bigint;
+#[cfg(feature = "num-complex")]
+mod num_complex;
+#[cfg(feature = "num-rational")]
+mod num_rational;
+mod number;
+pub(crate) mod numpy;
+mod packaging;
+pub mod packed;
+pub(crate) mod pandas;
+#[cfg(feature = "polars")]
+pub(crate) mod polars;
+pub(crate) mod pyarrow;
+pub(crate) mod records;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+#[cfg(feature = "semver")]
+mod semver;
+pub(crate) mod text;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "url")]
+mod url;
+#[cfg(feature = "numpy")]
+pub use self::ndarray::Element;
+#[cfg(feature = "url")]
+pub use self::url::split_url;
+#[cfg(feature = "uuid")]
+mod uuid;
+
+/// Newtype name which marks a sequence to be serialized as [`PythonizeTypes::Set`](crate::PythonizeTypes::Set)
+pub(crate) const SET_TOKEN: &str = "$pythonize::native::Set";
+
+/// Newtype name which marks a unit variant to be serialized as an `enum.Enum` member
+pub(crate) const ENUM_TOKEN: &str = "$pythonize::native::Enum";
+/// Newtype name which marks a vector to be converted in bulk by [`packed`]
+pub(crate) const PACKED_TOKEN: &str = "$pythonize::native::Packed";
+/// Newtype name which marks [`datetime::DateTime`] components
+const DATETIME_TOKEN: &str = "$pythonize::native::DateTime";
+/// Newtype name which marks [`datetime::ZonedDateTime`] components
+#[cfg_attr(not(any(feature = "jiff", feature = "chrono-tz")), allow(dead_code))]
+const ZONED_DATETIME_TOKEN: &str = "$pythonize::native::ZonedDateTime";
+/// Newtype name which marks [`datetime::Date`] components
+const DATE_TOKEN: &str = "$pythonize::native::Date";
+/// Newtype name which marks [`datetime::Time`] components
+const TIME_TOKEN: &str = "$pythonize::native::Time";
+/// Newtype name which marks [`datetime::TimeDelta`] components
+const TIMEDELTA_TOKEN: &str = "$pythonize::native::TimeDelta";
+/// Newtype name which marks the hyphenated string form of a `uuid.UUID`
+#[cfg_attr(not(feature = "uuid"), allow(dead_code))]
+const UUID_TOKEN: &str = "$pythonize::native::Uuid";
+/// Newtype name which marks the string form of a `decimal.Decimal`
+#[cfg_attr(
+    not(any(feature = "rust_decimal", feature = "bigdecimal")),
+    allow(dead_code)
+)]
+const DECIMAL_TOKEN: &str = "$pythonize::native::Decimal";
+/// Newtype name which marks the decimal string form of an `int`
+#[cfg_attr(not(feature = "num-bigint"), allow(dead_code))]
+const INT_TOKEN: &str = "$pythonize::native::Int";
+/// Newtype name which marks [`number::Complex`] components
+#[cfg_attr(not(feature = "num-complex"), allow(dead_code))]
+const COMPLEX_TOKEN: &str = "$pythonize::native::Complex";
+/// Newtype name which marks the `numerator/denominator` string form of a `fractions.Fraction`
+#[cfg_attr(not(feature = "num-rational"), allow(dead_code))]
+const FRACTION_TOKEN: &str = "$pythonize::native::Fraction";
+/// Newtype name which marks the string form of a `pathlib.Path`
+const PATH_TOKEN: &str = "$pythonize::native::Path";
+/// Newtype name which marks the string form of an `ipaddress` address
+const IP_ADDRESS_TOKEN: &str = "$pythonize::native::IpAddress";
+/// Newtype name which marks the components of a `socket` address tuple
+const SOCKET_ADDRESS_TOKEN: &str = "$pythonize::native::SocketAddress";
+/// Newtype name which marks a URL string
+#[cfg_attr(not(feature = "url"), allow(dead_code))]
+const URL_TOKEN: &str = "$pythonize::native::Url";
+/// Newtype name which marks a URL string to be split into a `urllib.parse.SplitResult`
+#[cfg_attr(not(feature = "url"), allow(dead_code))]
+const SPLIT_URL_TOKEN: &str = "$pythonize::native::SplitUrl";
+/// Newtype name which marks the string form of a `packaging.version.Version`
+#[cfg_attr(not(feature = "semver"), allow(dead_code))]
+const VERSION_TOKEN: &str = "$pythonize::native::Version";
+/// Newtype name which marks the dtype, shape and data of a `numpy.ndarray`
+#[cfg_attr(not(feature = "numpy"), allow(dead_code))]
+const ARRAY_TOKEN: &str = "$pythonize::native::Array";
+/// Newtype name which marks a `polars.DataFrame`
+#[cfg(feature = "polars")]
+pub(crate) const DATAFRAME_TOKEN: &str = "$pythonize::native::DataFrame";
+/// Newtype name which marks a `polars.Series`, as a single-column frame
+#[cfg(feature = "polars")]
+pub(crate) const SERIES_TOKEN: &str = "$pythonize::native::Series";
+
+/// Python types which tagged values are converted to and from
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    DateTime,
+    ZonedDateTime,
+    Date,
+    Time,
+    TimeDelta,
+    Uuid,
+    Decimal,
+    Int,
+    Complex,
+    Fraction,
+    Path,
+    IpAddress,
+    SocketAddress,
+    Url,
+    SplitUrl,
+    Version,
+    Array,
+}
+
+impl Kind {
+    pub(crate) fn from_token(token: &str) -> Option<Self> {
+        match token {
+            DATETIME_TOKEN => Some(Kind::DateTime),
+            ZONED_DATETIME_TOKEN => Some(Kind::ZonedDateTime),
+            DATE_TOKEN => Some(Kind::Date),
+            TIME_TOKEN => Some(Kind::Time),
+            TIMEDELTA_TOKEN => Some(Kind::TimeDelta),
+            UUID_TOKEN => Some(Kind::Uuid),
+            DECIMAL_TOKEN => Some(Kind::Decimal),
+            INT_TOKEN => Some(Kind::Int),
+            COMPLEX_TOKEN => Some(Kind::Complex),
+            FRACTION_TOKEN => Some(Kind::Fraction),
+            PATH_TOKEN => Some(Kind::Path),
+            IP_ADDRESS_TOKEN => Some(Kind::IpAddress),
+            SOCKET_ADDRESS_TOKEN => Some(Kind::SocketAddress),
+            URL_TOKEN => Some(Kind::Url),
+            SPLIT_URL_TOKEN => Some(Kind::SplitUrl),
+            VERSION_TOKEN => Some(Kind::Version),
+            ARRAY_TOKEN => Some(Kind::Array),
+            _ => None,
+        }
+    }
+
+    /// Whether objects of this kind are from the `datetime` module and have an
+    /// `isoformat()` method
+    pub(crate) fn is_datetime(self) -> bool {
+        matches!(
+            self,
+            Kind::DateTime | Kind::ZonedDateTime | Kind::Date | Kind::Time
+        )
+    }
+
+    /// Build the Python object from the pythonized payload of a tagged value
+    pub(crate) fn create<'py>(self, payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Kind::DateTime => datetime::datetime_to_python(payload),
+            Kind::ZonedDateTime => datetime::zoned_datetime_to_python(payload),
+            Kind::Date => datetime::date_to_python(payload),
+            Kind::Time => datetime::time_to_python(payload),
+            Kind::TimeDelta => datetime::timedelta_to_python(payload),
+            Kind::Uuid => text::uuid_to_python(payload),
+            Kind::Decimal => text::decimal_to_python(payload),
+            Kind::Int => text::int_to_python(payload),
+            Kind::Complex => number::complex_to_python(payload),
+            Kind::Fraction => text::fraction_to_python(payload),
+            Kind::Path => text::path_to_python(payload),
+            Kind::IpAddress => ipaddress::ip_address_to_python(payload),
+            Kind::SocketAddress => ipaddress::socket_address_to_python(payload),
+            Kind::Url => text::url_to_python(payload),
+            Kind::SplitUrl => text::split_url_to_python(payload),
+            Kind::Version => packaging::version_to_python(payload),
+            Kind::Array => numpy::array_to_python(payload),
+        }
+    }
+
+    /// Decompose a Python object into the payload of a tagged value, or `None` if
+    /// the object is not of this kind
+    pub(crate) fn decompose<'py>(
+        self,
+        obj: &Bound<'py, PyAny>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        match self {
+            Kind::DateTime => datetime::datetime_from_python(obj),
+            Kind::ZonedDateTime => datetime::zoned_datetime_from_python(obj),
+            Kind::Date => datetime::date_from_python(obj),
+            Kind::Time => datetime::time_from_python(obj),
+            Kind::TimeDelta => datetime::timedelta_from_python(obj),
+            Kind::Uuid => text::uuid_from_python(obj),
+            Kind::Decimal => text::decimal_from_python(obj),
+            Kind::Int => text::int_from_python(obj),
+            Kind::Complex => number::complex_from_python(obj),
+            Kind::Fraction => text::fraction_from_python(obj),
+            Kind::Path => text::path_from_python(obj),
+            Kind::IpAddress => ipaddress::ip_address_from_python(obj),
+            Kind::SocketAddress => ipaddress::socket_address_from_python(obj),
+            Kind::Url | Kind::SplitUrl => text::url_from_python(obj),
+            Kind::Version => packaging::version_from_python(obj),
+            Kind::Array => numpy::array_from_python(obj),
+        }
+    }
+}
+
+/// Import `module.name` on first use and cache it for subsequent calls
+pub(crate) fn import<'py>(
+    cell: &'static GILOnceCell<PyObject>,
+    py: Python<'py>,
+    module: &str,
+    name: &str,
+) -> PyResult<&'py Bound<'py, PyAny>> {
+    cell.get_or_try_init(py, || Ok(py.import_bound(module)?.getattr(name)?.unbind()))
+        .map(|obj| obj.bind(py))
+}
+
+/// Like [`import`], but `None` if `module` is not installed, for optional third-party
+/// packages which can only be involved if they are installed
+fn import_optional<'py>(
+    cell: &'static GILOnceCell<Option<PyObject>>,
+    py: Python<'py>,
+    module: &str,
+    name: &str,
+) -> PyResult<Option<&'py Bound<'py, PyAny>>> {
+    let obj = cell.get_or_try_init(py, || match py.import_bound(module) {
+        Ok(module) => Ok(Some(module.getattr(name)?.unbind())),
+        Err(err) if err.is_instance_of::<PyImportError>(py) => Ok(None),
+        Err(err) => Err(err),
+    })?;
+    Ok(obj.as_ref().map(|obj| obj.bind(py)))
+}
+
+/// Like [`import_optional`], but also `None` if `module` has not been imported yet, for
+/// checking whether an object is of a third-party type: no such object can exist before
+/// then, and importing the module just to find that out would be slow
+fn import_loaded<'py>(
+    cell: &'static GILOnceCell<Option<PyObject>>,
+    py: Python<'py>,
+    module: &str,
+    name: &str,
+) -> PyResult<Option<&'py Bound<'py, PyAny>>> {
+    static MODULES: GILOnceCell<PyObject> = GILOnceCell::new();
+    if let Some(Some(obj)) = cell.get(py) {
+        return Ok(Some(obj.bind(py)));
+    }
+    if !import(&MODULES, py, "sys", "modules")?.contains(module)? {
+        return Ok(None);
+    }
+    import_optional(cell, py, module, name)
+}
+
+/// Rust types with a native Python counterpart, for use with
+/// `#[serde(with = "pythonize::native")]`
+pub trait Native: Sized {
+    /// Serialize this value, tagged with its Python representation
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    /// Deserialize a value which was tagged with its Python representation
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// Serialize a [`Native`] value; for use with `#[serde(with = "pythonize::native")]`
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Native,
+    S: Serializer,
+{
+    value.serialize_native(serializer)
+}
+
+/// Deserialize a [`Native`] value; for use with `#[serde(with = "pythonize::native")]`
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Native,
+    D: Deserializer<'de>,
+{
+    T::deserialize_native(deserializer)
+}
+
+/// Serialize `value` wrapped in a newtype struct named `token`
+pub(crate) fn serialize_tagged<S, T>(
+    serializer: S,
+    token: &'static str,
+    value: &T,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ?Sized + Serialize,
+{
+    serializer.serialize_newtype_struct(token, value)
+}
+
+/// Deserialize a value wrapped in a newtype struct named `token`
+pub(crate) fn deserialize_tagged<'de, D, T>(
+    deserializer: D,
+    token: &'static str,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_newtype_struct(token, TaggedVisitor(PhantomData))
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for TaggedVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tagged native value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// The text of a number, which also accepts untagged integers and floats
+#[cfg(any(
+    feature = "rust_decimal",
+    feature = "bigdecimal",
+    feature = "num-bigint",
+    feature = "num-rational"
+))]
+struct NumericText(String);
+
+#[cfg(any(
+    feature = "rust_decimal",
+    feature = "bigdecimal",
+    feature = "num-bigint",
+    feature = "num-rational"
+))]
+impl<'de> Deserialize<'de> for NumericText {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(NumericTextVisitor)
+    }
+}
+
+#[cfg(any(
+    feature = "rust_decimal",
+    feature = "bigdecimal",
+    feature = "num-bigint",
+    feature = "num-rational"
+))]
+struct NumericTextVisitor;
+
+#[cfg(any(
+    feature = "rust_decimal",
+    feature = "bigdecimal",
+    feature = "num-bigint",
+    feature = "num-rational"
+))]
+impl<'de> de::Visitor<'de> for NumericTextVisitor {
+    type Value = NumericText;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal number")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_string()))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_string()))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<NumericText, E> {
+        Ok(NumericText(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<NumericText, E> {
+        Ok(NumericText(v))
+    }
+}
+
+/// Binary data such as an array's elements, serialized as bytes rather than a sequence
+#[cfg(any(feature = "numpy", feature = "polars"))]
+struct Bytes(Vec<u8>);
+
+#[cfg(any(feature = "numpy", feature = "polars"))]
+impl Serialize for Bytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(any(feature = "numpy", feature = "polars"))]
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[cfg(any(feature = "numpy", feature = "polars"))]
+struct BytesVisitor;
+
+#[cfg(any(feature = "numpy", feature = "polars"))]
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("binary data")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Bytes, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            data.push(byte);
+        }
+        Ok(Bytes(data))
+    }
+}
+
+struct SerializeNative<'a, T>(&'a T);
+
+impl<T: Native> Serialize for SerializeNative<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_native(serializer)
+    }
+}
+
+struct DeserializeNative<T>(T);
+
+impl<'de, T: Native> Deserialize<'de> for DeserializeNative<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_native(deserializer).map(DeserializeNative)
+    }
+}
+
+impl<T: Native> Native for Option<T> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(value) => serializer.serialize_some(&SerializeNative(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<DeserializeNative<T>>::deserialize(deserializer).map(|v| v.map(|v| v.0))
+    }
+}
+
+impl<T, H> Native for HashSet<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Hash,
+    H: BuildHasher + Default,
+{
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, SET_TOKEN, self)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_tagged(deserializer, SET_TOKEN)
+    }
+}
+
+impl<T> Native for BTreeSet<T>
+where
+    T: Serialize + DeserializeOwned + Ord,
+{
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, SET_TOKEN, self)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_tagged(deserializer, SET_TOKEN)
+    }
+}
+
+/// Truncates to microseconds, the resolution of `datetime.datetime`
+impl Native for SystemTime {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let micros = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_micros() as i128,
+            Err(before) => -(before.duration().as_micros() as i128),
+        };
+        serialize_tagged(
+            serializer,
+            DATETIME_TOKEN,
+            &datetime::from_unix_micros(micros),
+        )
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let components = deserialize_tagged(deserializer, DATETIME_TOKEN)?;
+        let micros = datetime::to_unix_micros(components)
+            .ok_or_else(|| de::Error::custom("expected a timezone-aware datetime"))?;
+        let offset = Duration::from_micros(micros.unsigned_abs() as u64);
+        let time = if micros >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        };
+        time.ok_or_else(|| de::Error::custom("datetime out of range for SystemTime"))
+    }
+}
+
+/// Paths must be valid UTF-8 to be serialized, like with `PathBuf`'s own `Serialize` impl
+impl Native for PathBuf {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let path = self
+            .to_str()
+            .ok_or_else(|| ser::Error::custom("path contains invalid UTF-8 characters"))?;
+        serialize_tagged(serializer, PATH_TOKEN, path)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_tagged(deserializer, PATH_TOKEN)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet};
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use pyo3::prelude::*;
+    use pyo3::types::{PyBytes, PyDict, PyFrozenSet, PyList, PySet, PyString, PyTuple};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        depythonize, pythonize, pythonize_custom, PythonizeTypes, PythonizeUnnamedMappingAdapter,
+        Pythonizer,
+    };
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sets {
+        #[serde(with = "crate::native")]
+        hash: HashSet<String>,
+        #[serde(with = "crate::native")]
+        btree: BTreeSet<u32>,
+        #[serde(with = "crate::native")]
+        maybe: Option<BTreeSet<u32>>,
+    }
+
+    fn sets() -> Sets {
+        Sets {
+            hash: HashSet::from(["foo".to_string(), "bar".to_string()]),
+            btree: BTreeSet::from([3, 1, 2]),
+            maybe: None,
+        }
+    }
+
+    #[test]
+    fn test_set_roundtrip() {
+        Python::with_gil(|py| {
+            let obj = pythonize(py, &sets()).unwrap();
+            assert!(obj
+                .get_item("hash")
+                .unwrap()
+                .is_exact_instance_of::<PySet>());
+            assert!(obj
+                .get_item("btree")
+                .unwrap()
+                .is_exact_instance_of::<PySet>());
+            assert!(obj.get_item("maybe").unwrap().is_none());
+
+            let actual: Sets = depythonize(&obj).unwrap();
+            assert_eq!(actual, sets());
+        })
+    }
+
+    #[test]
+    fn test_set_other_formats_unchanged() {
+        let json = serde_json::to_value(BTreeSetOnly {
+            values: BTreeSet::from([2, 1]),
+        })
+        .unwrap();
+        assert_eq!(json, serde_json::json!({ "values": [1, 2] }));
+    }
+
+    #[derive(Serialize)]
+    struct BTreeSetOnly {
+        #[serde(with = "crate::native")]
+        values: BTreeSet<u32>,
+    }
+
+    struct PythonizeFrozenSet;
+
+    impl<'py> PythonizeTypes<'py> for PythonizeFrozenSet {
+        type Map = PyDict;
+        type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+        type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+        type List = PyList;
+        type Set = PyFrozenSet;
+        type Tuple = PyTuple;
+        type UnitVariant = PyString;
+        type Bytes = PyBytes;
+    }
+
+    #[test]
+    fn test_custom_set_type() {
+        Python::with_gil(|py| {
+            let obj = pythonize_custom::<PythonizeFrozenSet, _>(py, &sets()).unwrap();
+            assert!(obj
+                .get_item("hash")
+                .unwrap()
+                .is_instance_of::<PyFrozenSet>());
+
+            let actual: Sets = depythonize(&obj).unwrap();
+            assert_eq!(actual, sets());
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Modified {
+        #[serde(with = "crate::native")]
+        at: SystemTime,
+    }
+
+    #[test]
+    fn test_system_time_roundtrip() {
+        Python::with_gil(|py| {
+            for offset in [1_700_000_000_123_456i64, -86_400_000_001] {
+                let modified = Modified {
+                    at: if offset >= 0 {
+                        UNIX_EPOCH + Duration::from_micros(offset as u64)
+                    } else {
+                        UNIX_EPOCH - Duration::from_micros(offset.unsigned_abs())
+                    },
+                };
+                let obj = pythonize(py, &modified).unwrap();
+                let at = obj.get_item("at").unwrap();
+                let timestamp: f64 = at.call_method0("timestamp").unwrap().extract().unwrap();
+                assert_eq!((timestamp * 1e6).round() as i64, offset);
+                assert!(!at.getattr("tzinfo").unwrap().is_none());
+
+                let actual: Modified = depythonize(&obj).unwrap();
+                assert_eq!(actual, modified);
+            }
+        })
+    }
+
+    #[test]
+    fn test_system_time_from_offset_datetime() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'at': __import__('datetime').datetime.fromisoformat('1970-01-01T02:00:01+02:00')}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let actual: Modified = depythonize(&obj).unwrap();
+            assert_eq!(actual.at, UNIX_EPOCH + Duration::from_secs(1));
+        })
+    }
+
+    #[test]
+    fn test_system_time_iso() {
+        Python::with_gil(|py| {
+            let modified = Modified {
+                at: UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456),
+            };
+            let pythonizer = Pythonizer::builder(py).datetime_iso(true).build();
+            let obj = modified.serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'at': '2023-11-14T22:13:20.123456+00:00'}"
+            );
+            let actual: Modified = depythonize(&obj).unwrap();
+            assert_eq!(actual, modified);
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the pandas Python package"]
+    fn test_system_time_from_pandas() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            #[serde(with = "crate::native")]
+            start: SystemTime,
+            #[serde(with = "crate::native")]
+            end: Option<SystemTime>,
+        }
+
+        Python::with_gil(|py| {
+            let pandas = py.import_bound("pandas").unwrap();
+            let obj = py
+                .eval_bound(
+                    "{'start': Timestamp('1970-01-01T00:00:01.5Z'), 'end': NaT}",
+                    Some(&pandas.dict()),
+                    None,
+                )
+                .unwrap();
+            let actual: Event = depythonize(&obj).unwrap();
+            assert_eq!(
+                actual,
+                Event {
+                    start: UNIX_EPOCH + Duration::from_millis(1500),
+                    end: None,
+                }
+            );
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Config {
+        #[serde(with = "crate::native")]
+        root: PathBuf,
+        #[serde(with = "crate::native")]
+        cache: Option<PathBuf>,
+    }
+
+    #[test]
+    fn test_path_roundtrip() {
+        Python::with_gil(|py| {
+            let config = Config {
+                root: PathBuf::from("/srv/data"),
+                cache: Some(PathBuf::from("cache/dir")),
+            };
+            let obj = pythonize(py, &config).unwrap();
+            let path = py.import_bound("pathlib").unwrap().getattr("Path").unwrap();
+            let root = obj.get_item("root").unwrap();
+            assert!(root.is_instance(&path).unwrap());
+            assert_eq!(root.str().unwrap().to_cow().unwrap(), "/srv/data");
+
+            let actual: Config = depythonize(&obj).unwrap();
+            assert_eq!(actual, config);
+        })
+    }
+
+    #[test]
+    fn test_path_from_path_like() {
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                r#"
+import os
+
+class Location(os.PathLike):
+    def __fspath__(self):
+        return "/tmp/location"
+
+obj = {"root": Location(), "cache": "plain/str"}
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let actual: Config = depythonize(&obj).unwrap();
+            assert_eq!(actual.root, PathBuf::from("/tmp/location"));
+            assert_eq!(actual.cache, Some(PathBuf::from("plain/str")));
+        })
+    }
+}
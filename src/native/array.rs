@@ -0,0 +1,163 @@
+//! The contents of `array.array`s from Python's standard library, `memoryview`s and
+//! other byte strings, which are decoded from a single copy of their buffer like those
+//! of numpy arrays
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{
+    PyAnyMethods, PyByteArray, PyByteArrayMethods, PyBytes, PyBytesMethods, PyMemoryView,
+};
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::import_loaded;
+use super::numpy::{decoder, ArrayContents, Elements};
+
+static ARRAY: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// The contents of `obj`, or `None` if it is not an `array.array`
+pub(crate) fn array_contents<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<ArrayContents<'py>>> {
+    match import_loaded(&ARRAY, obj.py(), "array", "array")? {
+        Some(array) if obj.is_instance(array)? => {}
+        _ => return Ok(None),
+    }
+    let typecode: char = obj.getattr("typecode")?.extract()?;
+    contents(obj, typecode, vec![obj.len()?])
+}
+
+/// The contents of `obj`, or `None` if it is not a `memoryview`, `bytes` or `bytearray`
+/// with at least one dimension
+///
+/// Views which aren't contiguous are copied into C order.
+pub(crate) fn buffer_contents<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<ArrayContents<'py>>> {
+    let bytes = if let Ok(bytes) = obj.downcast::<PyBytes>() {
+        bytes.as_bytes().to_vec()
+    } else if let Ok(bytes) = obj.downcast::<PyByteArray>() {
+        bytes.to_vec()
+    } else if obj.is_instance_of::<PyMemoryView>() {
+        let shape: Vec<usize> = obj.getattr("shape")?.extract()?;
+        if shape.is_empty() {
+            return Ok(None);
+        }
+        // struct module syntax, in which `@` is the default of native types and order
+        let format: String = obj.getattr("format")?.extract()?;
+        let format = format.strip_prefix('@').unwrap_or(&format);
+        let mut chars = format.chars();
+        let typecode = match (chars.next(), chars.next()) {
+            (Some(typecode), None) => typecode,
+            _ => '\0',
+        };
+        return contents(obj, typecode, shape);
+    } else {
+        return Ok(None);
+    };
+    let elements = bytes.into_iter().map(u64::from).collect();
+    Ok(Some(ArrayContents::Numeric(
+        vec![obj.len()?],
+        Elements::UInt(elements),
+    )))
+}
+
+/// The elements of an `array.array` or `memoryview` of the given shape, whose items
+/// have the C type of `typecode`, or a `list` of them if it is of another type
+fn contents<'py>(
+    obj: &Bound<'py, PyAny>,
+    typecode: char,
+    shape: Vec<usize>,
+) -> PyResult<Option<ArrayContents<'py>>> {
+    let itemsize: usize = obj.getattr("itemsize")?.extract()?;
+    // the numpy kind of the C type, whose size varies by platform
+    let kind = match typecode {
+        '?' => 'b',
+        'b' | 'h' | 'i' | 'l' | 'q' | 'n' => 'i',
+        'B' | 'H' | 'I' | 'L' | 'Q' | 'N' => 'u',
+        'e' | 'f' | 'd' => 'f',
+        // characters, and other types of the struct module
+        _ => 'U',
+    };
+    let elements = match decoder(kind, itemsize) {
+        Some(elements) => elements,
+        None => return Ok(Some(ArrayContents::List(obj.call_method0("tolist")?))),
+    };
+    let data = obj.call_method0("tobytes")?.downcast_into::<PyBytes>()?;
+    let elements = elements(data.as_bytes());
+    Ok(Some(ArrayContents::Numeric(shape, elements)))
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use serde_json::json;
+
+    use crate::depythonize;
+
+    #[test]
+    fn test_array_contents() {
+        Python::with_gil(|py| {
+            let array = py.import_bound("array").unwrap().getattr("array").unwrap();
+            let obj = array.call1(("d", vec![0.5, -1.25])).unwrap();
+            assert_eq!(depythonize::<Vec<f64>>(&obj).unwrap(), [0.5, -1.25]);
+            assert_eq!(depythonize::<(f32, f32)>(&obj).unwrap(), (0.5, -1.25));
+            assert!(depythonize::<(f32, f32, f32)>(&obj).is_err());
+
+            let obj = array.call1(("i", vec![i32::MIN, 7])).unwrap();
+            assert_eq!(depythonize::<Vec<i32>>(&obj).unwrap(), [i32::MIN, 7]);
+            let err = depythonize::<Vec<u8>>(&obj).unwrap_err();
+            assert!(err.to_string().contains("-2147483648"), "{}", err);
+
+            let obj = array.call1(("Q", vec![u64::MAX])).unwrap();
+            assert_eq!(depythonize::<Vec<u64>>(&obj).unwrap(), [u64::MAX]);
+            let any: serde_json::Value = depythonize(&obj).unwrap();
+            assert_eq!(any, json!([u64::MAX]));
+
+            let obj = array.call1(("u", "hi")).unwrap();
+            assert_eq!(depythonize::<Vec<char>>(&obj).unwrap(), ['h', 'i']);
+
+            let obj = array.call1(("b",)).unwrap();
+            assert!(depythonize::<Vec<i8>>(&obj).unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn test_buffer_contents() {
+        Python::with_gil(|py| {
+            let obj = py.eval_bound("memoryview(b'abcdef')", None, None).unwrap();
+            assert_eq!(depythonize::<Vec<u8>>(&obj).unwrap(), b"abcdef");
+
+            // not contiguous
+            let obj = py
+                .eval_bound("memoryview(b'abcdef')[::2]", None, None)
+                .unwrap();
+            assert_eq!(depythonize::<Vec<u8>>(&obj).unwrap(), b"ace");
+            let bytes: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(bytes.as_slice(), b"ace");
+
+            let obj = py
+                .eval_bound(
+                    "memoryview(bytearray(range(6))).cast('B', [2, 3])",
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                depythonize::<Vec<Vec<u8>>>(&obj).unwrap(),
+                [[0, 1, 2], [3, 4, 5]]
+            );
+            let bytes: serde_bytes::ByteBuf = depythonize(&obj).unwrap();
+            assert_eq!(bytes.as_slice(), [0, 1, 2, 3, 4, 5]);
+
+            let obj = py
+                .eval_bound(
+                    "memoryview(__import__('array').array('d', [1.5, 2.5]))",
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(depythonize::<Vec<f64>>(&obj).unwrap(), [1.5, 2.5]);
+
+            let obj = py.eval_bound("bytearray(b'xy')", None, None).unwrap();
+            assert_eq!(depythonize::<Vec<u8>>(&obj).unwrap(), b"xy");
+            assert_eq!(depythonize::<(u8, u8)>(&obj).unwrap(), (b'x', b'y'));
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, NumericText, DECIMAL_TOKEN};
+
+/// Keeps every digit and the scale, as `decimal.Decimal` construction is exact
+impl Native for BigDecimal {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DECIMAL_TOKEN, &self.to_scientific_notation())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let NumericText(text) = deserialize_tagged(deserializer, DECIMAL_TOKEN)?;
+        BigDecimal::from_str(&text)
+            .map_err(|err| D::Error::custom(format_args!("invalid decimal {:?}: {}", text, err)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bigdecimal::num_bigint::BigInt;
+    use bigdecimal::BigDecimal;
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Measurement {
+        #[serde(with = "crate::native")]
+        value: BigDecimal,
+    }
+
+    #[test]
+    fn test_bigdecimal_roundtrip() {
+        Python::with_gil(|py| {
+            for text in [
+                "1.50",
+                "-0.000000000000000000000000000000000000001",
+                "123456789012345678901234567890.123456789012345678901234567890",
+                "1E+40",
+            ] {
+                let measurement = Measurement {
+                    value: BigDecimal::from_str(text).unwrap(),
+                };
+                let obj = pythonize(py, &measurement).unwrap();
+                let value = obj.get_item("value").unwrap();
+                let expected = py
+                    .import_bound("decimal")
+                    .unwrap()
+                    .call_method1("Decimal", (text,))
+                    .unwrap();
+                assert_eq!(
+                    value.repr().unwrap().to_cow().unwrap(),
+                    expected.repr().unwrap().to_cow().unwrap()
+                );
+
+                let actual: Measurement = depythonize(&obj).unwrap();
+                assert_eq!(actual, measurement);
+                assert_eq!(
+                    actual.value.as_bigint_and_exponent(),
+                    measurement.value.as_bigint_and_exponent()
+                );
+            }
+        })
+    }
+
+    #[test]
+    fn test_bigdecimal_errors() {
+        Python::with_gil(|py| {
+            let measurement = Measurement {
+                value: BigDecimal::new(BigInt::from(1), i64::MAX),
+            };
+            let err = pythonize(py, &measurement).unwrap_err();
+            assert!(
+                err.to_string().starts_with(
+                    "ValueError: cannot convert '1e-9223372036854775807' to decimal.Decimal"
+                ),
+                "{}",
+                err
+            );
+
+            let obj = py
+                .eval_bound(
+                    "{'value': __import__('decimal').Decimal('-Infinity')}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Measurement>(&obj).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid decimal \"-Infinity\""),
+                "{}",
+                err
+            );
+        })
+    }
+}
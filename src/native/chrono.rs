@@ -0,0 +1,500 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Timelike, Utc,
+};
+use serde::de::{Deserializer, Error};
+use serde::ser::{Error as _, Serializer};
+
+#[cfg(feature = "chrono-tz")]
+use super::ZONED_DATETIME_TOKEN;
+use super::{
+    datetime, deserialize_tagged, serialize_tagged, Native, DATETIME_TOKEN, DATE_TOKEN,
+    TIMEDELTA_TOKEN, TIME_TOKEN,
+};
+
+fn date_components(date: &NaiveDate) -> datetime::Date {
+    (date.year(), date.month() as u8, date.day() as u8)
+}
+
+/// Split a time into `datetime.time` components, truncating to microseconds
+fn time_components(time: &NaiveTime) -> datetime::Time {
+    (
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+        // leap seconds cannot be represented in Python
+        (time.nanosecond() / 1000).min(999_999),
+    )
+}
+
+/// Split a datetime into `datetime.datetime` components, truncating to microseconds
+fn to_components(naive: &NaiveDateTime, offset: Option<i32>) -> datetime::DateTime {
+    let (year, month, day) = date_components(&naive.date());
+    let (hour, minute, second, microsecond) = time_components(&naive.time());
+    (year, month, day, hour, minute, second, microsecond, offset)
+}
+
+fn from_components<E: Error>(
+    components: datetime::DateTime,
+) -> Result<(NaiveDateTime, Option<i32>), E> {
+    let (year, month, day, hour, minute, second, microsecond, offset) = components;
+    NaiveDate::from_ymd_opt(year, month.into(), day.into())
+        .and_then(|date| {
+            date.and_hms_micro_opt(hour.into(), minute.into(), second.into(), microsecond)
+        })
+        .map(|naive| (naive, offset))
+        .ok_or_else(|| E::custom("invalid or out-of-range datetime"))
+}
+
+fn deserialize_aware<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let (naive, offset) = from_components(deserialize_tagged(deserializer, DATETIME_TOKEN)?)?;
+    let offset = offset.ok_or_else(|| D::Error::custom("expected a timezone-aware datetime"))?;
+    FixedOffset::east_opt(offset)
+        .and_then(|offset| offset.from_local_datetime(&naive).single())
+        .ok_or_else(|| D::Error::custom("invalid or out-of-range UTC offset"))
+}
+
+impl Native for DateTime<Utc> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(
+            serializer,
+            DATETIME_TOKEN,
+            &to_components(&self.naive_utc(), Some(0)),
+        )
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_aware(deserializer).map(|datetime| datetime.with_timezone(&Utc))
+    }
+}
+
+impl Native for DateTime<FixedOffset> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let offset = self.offset().fix().local_minus_utc();
+        serialize_tagged(
+            serializer,
+            DATETIME_TOKEN,
+            &to_components(&self.naive_local(), Some(offset)),
+        )
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_aware(deserializer)
+    }
+}
+
+/// The time zone becomes a `zoneinfo.ZoneInfo` of the same name; datetimes are accepted
+/// with `zoneinfo.ZoneInfo` and `pytz` time zones, or in UTC
+#[cfg(feature = "chrono-tz")]
+impl Native for DateTime<chrono_tz::Tz> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components = to_components(&self.naive_utc(), Some(0));
+        let zoned = (components, Some(self.timezone().name()));
+        serialize_tagged(serializer, ZONED_DATETIME_TOKEN, &zoned)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (components, key): datetime::ZonedDateTime =
+            deserialize_tagged(deserializer, ZONED_DATETIME_TOKEN)?;
+        let (naive, offset) = from_components(components)?;
+        let offset =
+            offset.ok_or_else(|| D::Error::custom("expected a timezone-aware datetime"))?;
+        let tz = match key {
+            Some(key) => key.parse().map_err(D::Error::custom)?,
+            None if offset == 0 => chrono_tz::Tz::UTC,
+            None => {
+                return Err(D::Error::custom(
+                    "expected a datetime with a zoneinfo or pytz time zone",
+                ))
+            }
+        };
+        let utc = naive - Duration::seconds(offset.into());
+        Ok(tz.from_utc_datetime(&utc))
+    }
+}
+
+impl Native for NaiveDateTime {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DATETIME_TOKEN, &to_components(self, None))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match from_components(deserialize_tagged(deserializer, DATETIME_TOKEN)?)? {
+            (naive, None) => Ok(naive),
+            (_, Some(_)) => Err(D::Error::custom("expected a naive datetime")),
+        }
+    }
+}
+
+impl Native for NaiveDate {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DATE_TOKEN, &date_components(self))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (year, month, day): datetime::Date = deserialize_tagged(deserializer, DATE_TOKEN)?;
+        NaiveDate::from_ymd_opt(year, month.into(), day.into())
+            .ok_or_else(|| D::Error::custom("invalid or out-of-range date"))
+    }
+}
+
+impl Native for NaiveTime {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, TIME_TOKEN, &time_components(self))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (hour, minute, second, microsecond): datetime::Time =
+            deserialize_tagged(deserializer, TIME_TOKEN)?;
+        NaiveTime::from_hms_micro_opt(hour.into(), minute.into(), second.into(), microsecond)
+            .ok_or_else(|| D::Error::custom("invalid time"))
+    }
+}
+
+/// Durations with a fraction of a microsecond, the resolution of `datetime.timedelta`, are
+/// rejected rather than truncated
+impl Native for Duration {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // split into seconds, as `num_microseconds` can't count durations of more than
+        // about 292,000 years
+        let seconds = self.num_seconds();
+        let subsec_nanos = (*self - Duration::seconds(seconds))
+            .num_nanoseconds()
+            .expect("less than a second");
+        if subsec_nanos % 1_000 != 0 {
+            return Err(S::Error::custom(format!(
+                "duration of {} is not a whole number of microseconds",
+                self
+            )));
+        }
+        let micros = i128::from(seconds) * 1_000_000 + i128::from(subsec_nanos / 1_000);
+        let timedelta = datetime::timedelta_from_micros(micros);
+        serialize_tagged(serializer, TIMEDELTA_TOKEN, &timedelta)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let micros =
+            datetime::timedelta_to_micros(deserialize_tagged(deserializer, TIMEDELTA_TOKEN)?);
+        i64::try_from(micros)
+            .map(Duration::microseconds)
+            .map_err(|_| D::Error::custom("timedelta out of range for chrono::Duration"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{
+        DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    };
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Event {
+        #[serde(with = "crate::native")]
+        utc: DateTime<Utc>,
+        #[serde(with = "crate::native")]
+        fixed: DateTime<FixedOffset>,
+    }
+
+    #[test]
+    fn test_datetime_roundtrip() {
+        Python::with_gil(|py| {
+            let event = Event {
+                utc: Utc.with_ymd_and_hms(2024, 8, 10, 12, 30, 15).unwrap(),
+                fixed: FixedOffset::east_opt(5 * 3600 + 1800)
+                    .unwrap()
+                    .with_ymd_and_hms(1999, 12, 31, 23, 59, 59)
+                    .unwrap(),
+            };
+            let obj = pythonize(py, &event).unwrap();
+
+            let utc = obj.get_item("utc").unwrap();
+            assert_eq!(
+                utc.call_method0("isoformat")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "2024-08-10T12:30:15+00:00"
+            );
+            let fixed = obj.get_item("fixed").unwrap();
+            assert_eq!(
+                fixed
+                    .call_method0("isoformat")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "1999-12-31T23:59:59+05:30"
+            );
+
+            let actual: Event = depythonize(&obj).unwrap();
+            assert_eq!(actual, event);
+        })
+    }
+
+    #[test]
+    fn test_datetime_truncates_to_microseconds() {
+        Python::with_gil(|py| {
+            let datetime = Utc.timestamp_opt(1_700_000_000, 123_456_789).unwrap();
+            let obj = pythonize(
+                py,
+                &Event {
+                    utc: datetime,
+                    fixed: datetime.fixed_offset(),
+                },
+            )
+            .unwrap();
+            let actual: Event = depythonize(&obj).unwrap();
+            assert_eq!(actual.utc.timestamp_subsec_nanos(), 123_456_000);
+        })
+    }
+
+    #[test]
+    fn test_naive_datetime_rejected() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'utc': __import__('datetime').datetime(2024, 1, 1), \
+                      'fixed': __import__('datetime').datetime(2024, 1, 1)}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Event>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "expected a timezone-aware datetime");
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Naive {
+        #[serde(with = "crate::native")]
+        datetime: NaiveDateTime,
+        #[serde(with = "crate::native")]
+        date: NaiveDate,
+        #[serde(with = "crate::native")]
+        time: NaiveTime,
+    }
+
+    #[test]
+    fn test_naive_roundtrip() {
+        Python::with_gil(|py| {
+            let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+            let time = NaiveTime::from_hms_micro_opt(7, 8, 9, 10).unwrap();
+            let naive = Naive {
+                datetime: date.and_time(time),
+                date,
+                time,
+            };
+            let obj = pythonize(py, &naive).unwrap();
+
+            let isoformat = |key: &str| -> String {
+                obj.get_item(key)
+                    .unwrap()
+                    .call_method0("isoformat")
+                    .unwrap()
+                    .extract()
+                    .unwrap()
+            };
+            assert_eq!(isoformat("datetime"), "2024-02-29T07:08:09.000010");
+            assert_eq!(isoformat("date"), "2024-02-29");
+            assert_eq!(isoformat("time"), "07:08:09.000010");
+
+            let actual: Naive = depythonize(&obj).unwrap();
+            assert_eq!(actual, naive);
+        })
+    }
+
+    #[test]
+    fn test_date_rejects_datetime() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'datetime': __import__('datetime').datetime(2024, 1, 1), \
+                      'date': __import__('datetime').datetime(2024, 1, 1), \
+                      'time': __import__('datetime').time()}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert!(depythonize::<Naive>(&obj).is_err());
+        })
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn test_chrono_tz_roundtrip() {
+        use chrono_tz::Tz;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Meeting {
+            #[serde(with = "crate::native")]
+            start: DateTime<Tz>,
+        }
+
+        Python::with_gil(|py| {
+            let meeting = Meeting {
+                start: chrono_tz::Europe::Paris
+                    .with_ymd_and_hms(2024, 7, 1, 9, 0, 0)
+                    .unwrap(),
+            };
+            let obj = pythonize(py, &meeting).unwrap();
+            let start = obj.get_item("start").unwrap();
+            assert_eq!(
+                start.repr().unwrap().to_string(),
+                "datetime.datetime(2024, 7, 1, 9, 0, tzinfo=zoneinfo.ZoneInfo(key='Europe/Paris'))"
+            );
+            let actual: Meeting = depythonize(&obj).unwrap();
+            assert_eq!(actual, meeting);
+            assert_eq!(actual.start.timezone(), Tz::Europe__Paris);
+
+            // `pytz` time zones have a `zone` instead of a `key`
+            let locals = pyo3::types::PyDict::new_bound(py);
+            py.run_bound(
+                r#"
+import datetime
+
+class FakePytz(datetime.tzinfo):
+    zone = "Asia/Tokyo"
+
+    def utcoffset(self, dt):
+        return datetime.timedelta(hours=9)
+
+obj = {"start": datetime.datetime(2024, 7, 1, 16, 0, tzinfo=FakePytz())}
+"#,
+                Some(&locals),
+                None,
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let actual: Meeting = depythonize(&obj).unwrap();
+            assert_eq!(actual.start.timezone(), Tz::Asia__Tokyo);
+            assert_eq!(actual.start, meeting.start);
+
+            // fixed offsets other than UTC have no zone name
+            let obj = py
+                .eval_bound(
+                    "{'start': __import__('datetime').datetime.fromisoformat('2024-07-01T09:00+02:00')}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Meeting>(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "expected a datetime with a zoneinfo or pytz time zone"
+            );
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Timeout {
+        #[serde(with = "crate::native")]
+        duration: Duration,
+    }
+
+    #[test]
+    fn test_duration_roundtrip() {
+        Python::with_gil(|py| {
+            for duration in [
+                Duration::microseconds(-1),
+                Duration::days(3) + Duration::microseconds(4),
+                Duration::days(-100_000_000),
+            ] {
+                let timeout = Timeout { duration };
+                let obj = pythonize(py, &timeout).unwrap();
+                let timedelta = obj.get_item("duration").unwrap();
+                assert_eq!(
+                    timedelta
+                        .call_method0("total_seconds")
+                        .unwrap()
+                        .extract::<f64>()
+                        .unwrap(),
+                    duration.num_microseconds().unwrap() as f64 / 1e6
+                );
+                let actual: Timeout = depythonize(&obj).unwrap();
+                assert_eq!(actual, timeout);
+            }
+
+            // nanoseconds can't be represented
+            let timeout = Timeout {
+                duration: Duration::nanoseconds(-1_500),
+            };
+            let err = pythonize(py, &timeout).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "duration of -PT0.0000015S is not a whole number of microseconds"
+            );
+        })
+    }
+
+    #[test]
+    fn test_duration_overflow() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'duration': __import__('datetime').timedelta.max}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Timeout>(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "timedelta out of range for chrono::Duration"
+            );
+
+            // durations which don't fit in a timedelta can't be serialized
+            let timeout = Timeout {
+                duration: Duration::days(1_000_000_000),
+            };
+            assert!(pythonize(py, &timeout).is_err());
+        })
+    }
+}
@@ -0,0 +1,329 @@
+//! Conversions between tagged payloads and objects from Python's `datetime` module
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyString};
+use pyo3::{Bound, IntoPy, PyAny, PyObject, PyResult, Python};
+
+use super::import;
+
+/// Components of a `datetime.datetime`: year, month, day, hour, minute, second,
+/// microsecond and the offset from UTC in seconds, which is `None` for naive datetimes
+pub(crate) type DateTime = (i32, u8, u8, u8, u8, u8, u32, Option<i32>);
+
+/// An aware [`DateTime`] and the IANA key of its `zoneinfo.ZoneInfo` or `pytz` time
+/// zone, if any
+#[cfg_attr(not(any(feature = "jiff", feature = "chrono-tz")), allow(dead_code))]
+pub(crate) type ZonedDateTime = (DateTime, Option<String>);
+
+/// Components of a `datetime.date`: year, month and day
+pub(crate) type Date = (i32, u8, u8);
+
+/// Components of a naive `datetime.time`: hour, minute, second and microsecond
+pub(crate) type Time = (u8, u8, u8, u32);
+
+/// Components of a `datetime.timedelta`: days, seconds and microseconds
+pub(crate) type TimeDelta = (i64, u32, u32);
+
+/// Convert a signed number of microseconds into normalized [`TimeDelta`] components
+#[cfg_attr(
+    not(any(feature = "std-duration", feature = "chrono", feature = "time")),
+    allow(dead_code)
+)]
+pub(crate) fn timedelta_from_micros(micros: i128) -> TimeDelta {
+    let secs = micros.div_euclid(1_000_000);
+    (
+        secs.div_euclid(86_400) as i64,
+        secs.rem_euclid(86_400) as u32,
+        micros.rem_euclid(1_000_000) as u32,
+    )
+}
+
+/// Convert [`TimeDelta`] components into a signed number of microseconds
+#[cfg_attr(
+    not(any(feature = "std-duration", feature = "chrono", feature = "time")),
+    allow(dead_code)
+)]
+pub(crate) fn timedelta_to_micros(timedelta: TimeDelta) -> i128 {
+    let (days, seconds, microseconds) = timedelta;
+    (i128::from(days) * 86_400 + i128::from(seconds)) * 1_000_000 + i128::from(microseconds)
+}
+
+/// Convert microseconds since the Unix epoch into UTC [`DateTime`] components
+pub(crate) fn from_unix_micros(micros: i128) -> DateTime {
+    let secs = micros.div_euclid(1_000_000) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let secs = secs.rem_euclid(86_400);
+    (
+        year as i32,
+        month,
+        day,
+        (secs / 3600) as u8,
+        (secs / 60 % 60) as u8,
+        (secs % 60) as u8,
+        micros.rem_euclid(1_000_000) as u32,
+        Some(0),
+    )
+}
+
+/// Convert aware [`DateTime`] components into microseconds since the Unix epoch,
+/// or `None` for naive datetimes
+pub(crate) fn to_unix_micros(datetime: DateTime) -> Option<i128> {
+    let (year, month, day, hour, minute, second, microsecond, offset) = datetime;
+    let secs = days_from_civil(year.into(), month, day) * 86_400
+        + i64::from(hour) * 3600
+        + i64::from(minute) * 60
+        + i64::from(second)
+        - i64::from(offset?);
+    Some(i128::from(secs) * 1_000_000 + i128::from(microsecond))
+}
+
+// Proleptic Gregorian calendar conversions, following Howard Hinnant's
+// http://howardhinnant.github.io/date_algorithms.html
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u8, day as u8)
+}
+
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+static DATE: GILOnceCell<PyObject> = GILOnceCell::new();
+static TIME: GILOnceCell<PyObject> = GILOnceCell::new();
+static DATETIME: GILOnceCell<PyObject> = GILOnceCell::new();
+static TIMEDELTA: GILOnceCell<PyObject> = GILOnceCell::new();
+static TIMEZONE: GILOnceCell<PyObject> = GILOnceCell::new();
+static ZONEINFO: GILOnceCell<PyObject> = GILOnceCell::new();
+
+fn tzinfo(py: Python<'_>, offset: Option<i32>) -> PyResult<Bound<'_, PyAny>> {
+    match offset {
+        None => Ok(py.None().into_bound(py)),
+        Some(0) => import(&TIMEZONE, py, "datetime", "timezone")?.getattr("utc"),
+        Some(seconds) => {
+            let delta = import(&TIMEDELTA, py, "datetime", "timedelta")?.call1((0, seconds))?;
+            import(&TIMEZONE, py, "datetime", "timezone")?.call1((delta,))
+        }
+    }
+}
+
+fn utc_offset(datetime: &Bound<'_, PyAny>) -> PyResult<Option<i32>> {
+    let offset = datetime.call_method0("utcoffset")?;
+    if offset.is_none() {
+        return Ok(None);
+    }
+    let days: i32 = offset.getattr("days")?.extract()?;
+    let seconds: i32 = offset.getattr("seconds")?.extract()?;
+    Ok(Some(days * 86_400 + seconds))
+}
+
+/// Parse `obj` with `class.fromisoformat()` if it is a `str`, as created with the
+/// `datetime_iso` option of the `Pythonizer`
+fn parse_isoformat<'py>(
+    obj: &Bound<'py, PyAny>,
+    class: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    if obj.is_instance_of::<PyString>() {
+        class.call_method1("fromisoformat", (obj,))
+    } else {
+        Ok(obj.clone())
+    }
+}
+
+pub(crate) fn datetime_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let py = payload.py();
+    let (year, month, day, hour, minute, second, microsecond, offset): DateTime =
+        payload.extract()?;
+    let args = (
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        microsecond,
+        tzinfo(py, offset)?,
+    );
+    import(&DATETIME, py, "datetime", "datetime")?.call1(args)
+}
+
+pub(crate) fn datetime_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    let class = import(&DATETIME, py, "datetime", "datetime")?;
+    let obj = &parse_isoformat(obj, class)?;
+    if !obj.is_instance(class)? {
+        return Ok(None);
+    }
+    let payload: DateTime = (
+        obj.getattr("year")?.extract()?,
+        obj.getattr("month")?.extract()?,
+        obj.getattr("day")?.extract()?,
+        obj.getattr("hour")?.extract()?,
+        obj.getattr("minute")?.extract()?,
+        obj.getattr("second")?.extract()?,
+        obj.getattr("microsecond")?.extract()?,
+        utc_offset(obj)?,
+    );
+    Ok(Some(
+        IntoPy::<PyObject>::into_py(payload, py).into_bound(py),
+    ))
+}
+
+pub(crate) fn zoned_datetime_to_python<'py>(
+    payload: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = payload.py();
+    let datetime = datetime_to_python(&payload.get_item(0)?)?;
+    match payload.get_item(1)?.extract::<Option<String>>()? {
+        Some(key) => {
+            let zone = import(&ZONEINFO, py, "zoneinfo", "ZoneInfo")?.call1((key,))?;
+            datetime.call_method1("astimezone", (zone,))
+        }
+        None => Ok(datetime),
+    }
+}
+
+pub(crate) fn zoned_datetime_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    let obj = &parse_isoformat(obj, import(&DATETIME, py, "datetime", "datetime")?)?;
+    let datetime = match datetime_from_python(obj)? {
+        Some(datetime) => datetime,
+        None => return Ok(None),
+    };
+    let tzinfo = obj.getattr("tzinfo")?;
+    // `zoneinfo.ZoneInfo` has a `key` and `pytz` time zones have a `zone`
+    let mut key = None;
+    for attr in ["key", "zone"] {
+        if tzinfo.hasattr(attr)? {
+            key = Some(tzinfo.getattr(attr)?).filter(|key| key.is_instance_of::<PyString>());
+            break;
+        }
+    }
+    Ok(Some(
+        IntoPy::<PyObject>::into_py((datetime, key), py).into_bound(py),
+    ))
+}
+
+pub(crate) fn date_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let date: Date = payload.extract()?;
+    import(&DATE, payload.py(), "datetime", "date")?.call1(date)
+}
+
+pub(crate) fn date_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    let obj = &parse_isoformat(obj, import(&DATE, py, "datetime", "date")?)?;
+    // `datetime.datetime` is a subclass of `datetime.date`
+    if !obj.is_instance(import(&DATE, py, "datetime", "date")?)?
+        || obj.is_instance(import(&DATETIME, py, "datetime", "datetime")?)?
+    {
+        return Ok(None);
+    }
+    let payload: Date = (
+        obj.getattr("year")?.extract()?,
+        obj.getattr("month")?.extract()?,
+        obj.getattr("day")?.extract()?,
+    );
+    Ok(Some(
+        IntoPy::<PyObject>::into_py(payload, py).into_bound(py),
+    ))
+}
+
+pub(crate) fn time_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let time: Time = payload.extract()?;
+    import(&TIME, payload.py(), "datetime", "time")?.call1(time)
+}
+
+pub(crate) fn time_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    let class = import(&TIME, py, "datetime", "time")?;
+    let obj = &parse_isoformat(obj, class)?;
+    if !obj.is_instance(class)? {
+        return Ok(None);
+    }
+    let payload: Time = (
+        obj.getattr("hour")?.extract()?,
+        obj.getattr("minute")?.extract()?,
+        obj.getattr("second")?.extract()?,
+        obj.getattr("microsecond")?.extract()?,
+    );
+    Ok(Some(
+        IntoPy::<PyObject>::into_py(payload, py).into_bound(py),
+    ))
+}
+
+pub(crate) fn timedelta_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let timedelta: TimeDelta = payload.extract()?;
+    import(&TIMEDELTA, payload.py(), "datetime", "timedelta")?.call1(timedelta)
+}
+
+pub(crate) fn timedelta_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    if !obj.is_instance(import(&TIMEDELTA, py, "datetime", "timedelta")?)? {
+        return Ok(None);
+    }
+    let payload: TimeDelta = (
+        obj.getattr("days")?.extract()?,
+        obj.getattr("seconds")?.extract()?,
+        obj.getattr("microseconds")?.extract()?,
+    );
+    Ok(Some(
+        IntoPy::<PyObject>::into_py(payload, py).into_bound(py),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{civil_from_days, days_from_civil, from_unix_micros, to_unix_micros};
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-719_162), (1, 1, 1));
+        assert_eq!(civil_from_days(2_932_896), (9999, 12, 31));
+
+        for days in (-800_000..3_000_000).step_by(13) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn test_unix_micros() {
+        assert_eq!(
+            from_unix_micros(-1),
+            (1969, 12, 31, 23, 59, 59, 999_999, Some(0))
+        );
+        assert_eq!(
+            to_unix_micros((1970, 1, 1, 1, 0, 0, 5, Some(3600))),
+            Some(5)
+        );
+        assert_eq!(to_unix_micros((1970, 1, 1, 0, 0, 0, 0, None)), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use serde::de::{Deserializer, Error as _};
+use serde::ser::{Error as _, Serializer};
+
+use super::{datetime, deserialize_tagged, serialize_tagged, Native, TIMEDELTA_TOKEN};
+
+/// Durations with a fraction of a microsecond, the resolution of `datetime.timedelta`, are
+/// rejected rather than truncated
+impl Native for Duration {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.subsec_nanos() % 1_000 != 0 {
+            return Err(S::Error::custom(format!(
+                "duration of {:?} is not a whole number of microseconds",
+                self
+            )));
+        }
+        let timedelta = datetime::timedelta_from_micros(self.as_micros() as i128);
+        serialize_tagged(serializer, TIMEDELTA_TOKEN, &timedelta)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let micros =
+            datetime::timedelta_to_micros(deserialize_tagged(deserializer, TIMEDELTA_TOKEN)?);
+        let micros = u64::try_from(micros)
+            .map_err(|_| D::Error::custom("expected a non-negative timedelta"))?;
+        Ok(Duration::from_micros(micros))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize, Pythonizer};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Timeout {
+        #[serde(with = "crate::native")]
+        duration: Duration,
+    }
+
+    #[test]
+    fn test_duration_roundtrip() {
+        Python::with_gil(|py| {
+            let timeout = Timeout {
+                duration: Duration::new(2 * 86_400 + 5, 123_456_000),
+            };
+            let obj = pythonize(py, &timeout).unwrap();
+            let duration = obj.get_item("duration").unwrap();
+            assert_eq!(
+                duration.repr().unwrap().to_cow().unwrap(),
+                "datetime.timedelta(days=2, seconds=5, microseconds=123456)"
+            );
+
+            let actual: Timeout = depythonize(&obj).unwrap();
+            assert_eq!(actual, timeout);
+        })
+    }
+
+    #[test]
+    fn test_sub_microsecond_duration_rejected() {
+        Python::with_gil(|py| {
+            let timeout = Timeout {
+                duration: Duration::new(5, 123_456_789),
+            };
+            let err = pythonize(py, &timeout).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "duration of 5.123456789s is not a whole number of microseconds"
+            );
+        })
+    }
+
+    #[test]
+    fn test_negative_timedelta_rejected() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'duration': __import__('datetime').timedelta(seconds=-1)}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Timeout>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "expected a non-negative timedelta");
+        })
+    }
+
+    #[test]
+    fn test_duration_iso() {
+        Python::with_gil(|py| {
+            // timedeltas have no ISO format
+            let pythonizer = Pythonizer::builder(py).datetime_iso(true).build();
+            let obj = Timeout {
+                duration: Duration::from_secs(1),
+            }
+            .serialize(pythonizer)
+            .unwrap();
+            assert_eq!(obj.get_item("duration").unwrap().to_string(), "0:00:01");
+        })
+    }
+
+    #[test]
+    fn test_duration_json_compatible() {
+        Python::with_gil(|py| {
+            let pythonizer = Pythonizer::builder(py).json_compatible(true).build();
+            let obj = Timeout {
+                duration: Duration::from_millis(1500),
+            }
+            .serialize(pythonizer)
+            .unwrap();
+
+            let locals = PyDict::new_bound(py);
+            locals.set_item("obj", obj).unwrap();
+            py.run_bound(
+                "import json; result = json.dumps(obj, separators=(',', ':'))",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let result = locals.get_item("result").unwrap().unwrap();
+            assert_eq!(result.to_string(), r#"{"duration":[0,1,500000]}"#);
+        })
+    }
+}
@@ -0,0 +1,303 @@
+//! Serde adapter converting unit-only enums to and from members of a generated
+//! `enum.Enum` class, for use with `#[serde(with = "pythonize::native::enum_member")]`
+//!
+//! The class is named after the Rust enum and has a member for each of its variants,
+//! whose value is the variant name. It is created the first time the enum is
+//! serialized, and reused from then on. Serializing an enum by itself only reveals the
+//! variant being serialized, so the adapter also needs the enum to implement
+//! `Deserialize`, which declares all of its variants.
+//!
+//! Members of any `enum.Enum` class are deserialized by their name, with or without the
+//! adapter, and other serde formats see the variant name as usual.
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//! use pythonize::{depythonize, pythonize};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! enum Color {
+//!     Red,
+//!     Green,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Pixel {
+//!     #[serde(with = "pythonize::native::enum_member")]
+//!     color: Color,
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let pixel = Pixel { color: Color::Green };
+//!     let obj = pythonize(py, &pixel).unwrap();
+//!     let color = obj.get_item("color").unwrap();
+//!     assert_eq!(color.repr().unwrap().to_string(), "<Color.Green: 'Green'>");
+//!     assert_eq!(depythonize::<Pixel>(&obj).unwrap(), pixel);
+//! })
+//! ```
+
+use std::cell::Cell;
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods, PyString, PyTuple};
+use pyo3::{Bound, Py, PyAny, PyObject, PyResult};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer, Visitor};
+use serde::ser::{Error as _, Serialize, Serializer};
+
+use super::{deserialize_tagged, import, serialize_tagged, ENUM_TOKEN};
+
+/// The name and variants of an enum
+pub(crate) type Definition = (&'static str, &'static [&'static str]);
+
+thread_local! {
+    /// The definition of the enum being serialized by [`serialize`], which the
+    /// `Pythonizer` takes to create its class
+    static DEFINITION: Cell<Option<Definition>> = const { Cell::new(None) };
+}
+
+/// Clears [`DEFINITION`] when dropped, so that a serializer which panics doesn't leave
+/// it for the next enum member to take
+struct ClearDefinition;
+
+impl Drop for ClearDefinition {
+    fn drop(&mut self) {
+        DEFINITION.with(|cell| cell.set(None));
+    }
+}
+
+static ENUM: GILOnceCell<PyObject> = GILOnceCell::new();
+static CLASSES: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+
+/// A deserializer which only records the name and variants of the enum passed to
+/// `deserialize_enum`
+struct Introspect<'a>(&'a mut Option<Definition>);
+
+impl<'de> Deserializer<'de> for Introspect<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("not an enum"))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        *self.0 = Some((name, variants));
+        Err(de::Error::custom("enum definition recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Serialize a unit variant as a member of a generated `enum.Enum` class
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + DeserializeOwned,
+    S: Serializer,
+{
+    let mut definition = None;
+    let _ = T::deserialize(Introspect(&mut definition));
+    if definition.is_none() {
+        return Err(S::Error::custom("enum_member can only be used with enums"));
+    }
+    serialize_with_definition(definition, serializer, value)
+}
+
+/// Serialize a unit variant of the enum with the given definition, as [`serialize`] does
+pub(crate) fn serialize_with_definition<T, S>(
+    definition: Option<Definition>,
+    serializer: S,
+    value: &T,
+) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    DEFINITION.with(|cell| cell.set(definition));
+    let _clear = ClearDefinition;
+    serialize_tagged(serializer, ENUM_TOKEN, value)
+}
+
+/// Take the definition of the enum being serialized by [`serialize`], for serializers
+/// which convert the variant later
+pub(crate) fn take_definition() -> Option<Definition> {
+    DEFINITION.with(|cell| cell.take())
+}
+
+/// Deserialize a unit variant from an `enum.Enum` member or variant name
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserialize_tagged(deserializer, ENUM_TOKEN)
+}
+
+/// Convert the pythonized variant name into a member of the class for the enum being
+/// serialized by [`serialize`]
+pub(crate) fn enum_member_to_python<'py>(
+    variant: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (name, variants) = match take_definition() {
+        Some(definition) => definition,
+        None => return Ok(variant.clone()),
+    };
+    let py = variant.py();
+    let classes = CLASSES
+        .get_or_init(py, || PyDict::new_bound(py).unbind())
+        .bind(py);
+    let key = (name, PyTuple::new_bound(py, variants));
+    let class = match classes.get_item(&key)? {
+        Some(class) => class,
+        None => {
+            let members: Vec<_> = variants.iter().map(|&variant| (variant, variant)).collect();
+            let class = import(&ENUM, py, "enum", "Enum")?.call1((name, members))?;
+            classes.set_item(&key, &class)?;
+            class
+        }
+    };
+    class.get_item(variant)
+}
+
+/// The name of `obj` if it is a member of an `enum.Enum` class
+pub(crate) fn enum_member_name<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyString>>> {
+    if !obj.is_instance(import(&ENUM, obj.py(), "enum", "Enum")?)? {
+        return Ok(None);
+    }
+    Ok(Some(obj.getattr("name")?.downcast_into()?))
+}
+
+/// The value of `obj` if it is a member of an `enum.Enum` class
+pub(crate) fn enum_member_value<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if !obj.is_instance(import(&ENUM, obj.py(), "enum", "Enum")?)? {
+        return Ok(None);
+    }
+    Ok(Some(obj.getattr("value")?))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Write};
+    use std::panic;
+
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use serde::{Deserialize, Serialize};
+
+    use super::take_definition;
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        Debug,
+        Info,
+        Warning,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Logger {
+        #[serde(with = "crate::native::enum_member")]
+        level: Level,
+        fallback: Level,
+    }
+
+    #[test]
+    fn test_enum_member_roundtrip() {
+        Python::with_gil(|py| {
+            let logger = Logger {
+                level: Level::Info,
+                fallback: Level::Warning,
+            };
+            let obj = pythonize(py, &logger).unwrap();
+            let level = obj.get_item("level").unwrap();
+            assert_eq!(level.repr().unwrap().to_string(), "<Level.info: 'info'>");
+            let class = level.get_type();
+            assert_eq!(class.len().unwrap(), 3);
+            assert_eq!(obj.get_item("fallback").unwrap().to_string(), "warning");
+
+            // the class is created once
+            let other = pythonize(py, &logger).unwrap();
+            assert!(other.get_item("level").unwrap().is(&level));
+
+            let actual: Logger = depythonize(&obj).unwrap();
+            assert_eq!(actual, logger);
+
+            // members of any class are accepted by name, with or without the adapter
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import enum\nclass Severity(enum.Enum):\n    debug = 10\n    warning = 30\nobj = {'level': Severity.debug, 'fallback': Severity.warning}",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let obj = locals.get_item("obj").unwrap().unwrap();
+            let actual: Logger = depythonize(&obj).unwrap();
+            assert_eq!(
+                actual,
+                Logger {
+                    level: Level::Debug,
+                    fallback: Level::Warning,
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_enum_member_payload() {
+        let logger = Logger {
+            level: Level::Debug,
+            fallback: Level::Info,
+        };
+        let json = serde_json::to_value(&logger).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "level": "debug", "fallback": "info" })
+        );
+        let actual: Logger = serde_json::from_value(json).unwrap();
+        assert_eq!(actual, logger);
+    }
+
+    #[test]
+    fn test_panic_clears_definition() {
+        /// Panics on writing the start of `self.0`
+        struct PanickingWriter(&'static str);
+
+        impl Write for PanickingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                assert!(!buf.starts_with(self.0.as_bytes()), "write failed");
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let logger = Logger {
+            level: Level::Debug,
+            fallback: Level::Info,
+        };
+        let result =
+            panic::catch_unwind(|| serde_json::to_writer(PanickingWriter("debug"), &logger));
+        assert!(result.is_err());
+        assert!(take_definition().is_none());
+    }
+}
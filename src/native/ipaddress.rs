@@ -0,0 +1,75 @@
+//! Conversions between tagged payloads and objects from Python's `ipaddress` module
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyString, PyTuple, PyTupleMethods};
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::{import, import_loaded};
+
+static IP_ADDRESS: GILOnceCell<PyObject> = GILOnceCell::new();
+static IPV4_ADDRESS: GILOnceCell<PyObject> = GILOnceCell::new();
+static IPV6_ADDRESS: GILOnceCell<PyObject> = GILOnceCell::new();
+static LOADED_CLASSES: [GILOnceCell<Option<PyObject>>; 4] = [
+    GILOnceCell::new(),
+    GILOnceCell::new(),
+    GILOnceCell::new(),
+    GILOnceCell::new(),
+];
+
+pub(crate) fn ip_address_to_python<'py>(
+    payload: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    import(&IP_ADDRESS, payload.py(), "ipaddress", "ip_address")?.call1((payload,))
+}
+
+pub(crate) fn ip_address_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    if obj.is_instance(import(&IPV4_ADDRESS, py, "ipaddress", "IPv4Address")?)?
+        || obj.is_instance(import(&IPV6_ADDRESS, py, "ipaddress", "IPv6Address")?)?
+    {
+        Ok(Some(obj.str()?.into_any()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// `str(obj)` if `obj` is an address, interface or network from the `ipaddress` module,
+/// such as `192.0.2.1`, `192.0.2.1/24` or `192.0.2.0/24`
+pub(crate) fn ip_object_text<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyString>>> {
+    // interfaces are subclasses of addresses
+    let names = ["IPv4Address", "IPv6Address", "IPv4Network", "IPv6Network"];
+    for (cell, name) in LOADED_CLASSES.iter().zip(names) {
+        match import_loaded(cell, obj.py(), "ipaddress", name)? {
+            Some(class) if obj.is_instance(class)? => return Ok(Some(obj.str()?)),
+            Some(_) => {}
+            None => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Convert the pythonized `(host, port)` or `(host, port, flowinfo, scope_id)` payload
+/// into the tuple used by the `socket` module
+pub(crate) fn socket_address_to_python<'py>(
+    payload: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    Ok(PyTuple::new_bound(payload.py(), payload.iter()?.collect::<PyResult<Vec<_>>>()?).into_any())
+}
+
+/// Tuples are passed on with the host converted to a string, so that it may also be
+/// an `ipaddress` object
+pub(crate) fn socket_address_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let tuple = match obj.downcast::<PyTuple>() {
+        Ok(tuple) if !tuple.is_empty() => tuple,
+        _ => return Ok(None),
+    };
+    let mut items: Vec<_> = tuple.iter().collect();
+    items[0] = items[0].str()?.into_any();
+    Ok(Some(PyTuple::new_bound(obj.py(), items).into_any()))
+}
@@ -0,0 +1,148 @@
+use jiff::tz::{Offset, TimeZone};
+use jiff::{Timestamp, Zoned};
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{
+    datetime, deserialize_tagged, serialize_tagged, Native, DATETIME_TOKEN, ZONED_DATETIME_TOKEN,
+};
+
+fn timestamp_from_components<E: Error>(components: datetime::DateTime) -> Result<Timestamp, E> {
+    let micros = datetime::to_unix_micros(components)
+        .ok_or_else(|| E::custom("expected a timezone-aware datetime"))?;
+    let micros = i64::try_from(micros).map_err(E::custom)?;
+    Timestamp::from_microsecond(micros).map_err(E::custom)
+}
+
+/// Truncates to microseconds, the resolution of `datetime.datetime`
+impl Native for Timestamp {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components = datetime::from_unix_micros(self.as_microsecond().into());
+        serialize_tagged(serializer, DATETIME_TOKEN, &components)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        timestamp_from_components(deserialize_tagged(deserializer, DATETIME_TOKEN)?)
+    }
+}
+
+/// Time zones with an IANA name become `zoneinfo.ZoneInfo`, all others become a fixed
+/// `datetime.timezone`; truncates to microseconds, the resolution of `datetime.datetime`
+impl Native for Zoned {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (year, month, day, hour, minute, second, microsecond, _) =
+            datetime::from_unix_micros(self.timestamp().as_microsecond().into());
+        let components = (year, month, day, hour, minute, second, microsecond, Some(0));
+        let key = self.time_zone().iana_name();
+        let zoned: (datetime::DateTime, Option<&str>) = match key {
+            Some(_) => (components, key),
+            // no zone to convert to, so keep the local time and its offset
+            None => {
+                let local = self.datetime();
+                (
+                    (
+                        local.year().into(),
+                        local.month() as u8,
+                        local.day() as u8,
+                        local.hour() as u8,
+                        local.minute() as u8,
+                        local.second() as u8,
+                        (local.subsec_nanosecond() / 1000) as u32,
+                        Some(self.offset().seconds()),
+                    ),
+                    None,
+                )
+            }
+        };
+        serialize_tagged(serializer, ZONED_DATETIME_TOKEN, &zoned)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (components, key): datetime::ZonedDateTime =
+            deserialize_tagged(deserializer, ZONED_DATETIME_TOKEN)?;
+        let timestamp = timestamp_from_components(components)?;
+        let time_zone = match key {
+            Some(key) => TimeZone::get(&key).map_err(D::Error::custom)?,
+            None => {
+                // checked to be aware by `timestamp_from_components`
+                let offset = components.7.unwrap_or_default();
+                TimeZone::fixed(Offset::from_seconds(offset).map_err(D::Error::custom)?)
+            }
+        };
+        Ok(Zoned::new(timestamp, time_zone))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jiff::tz::{Offset, TimeZone};
+    use jiff::{Timestamp, Zoned};
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Meeting {
+        #[serde(with = "crate::native")]
+        created: Timestamp,
+        #[serde(with = "crate::native")]
+        starts: Zoned,
+    }
+
+    fn str_of(obj: &Bound<'_, PyAny>, key: &str) -> String {
+        obj.get_item(key).unwrap().str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_jiff_roundtrip() {
+        Python::with_gil(|py| {
+            let created = Timestamp::from_microsecond(1_700_000_000_000_001).unwrap();
+            let meeting = Meeting {
+                created,
+                starts: created.in_tz("Europe/Paris").unwrap(),
+            };
+            let obj = pythonize(py, &meeting).unwrap();
+            assert_eq!(str_of(&obj, "created"), "2023-11-14 22:13:20.000001+00:00");
+            assert_eq!(str_of(&obj, "starts"), "2023-11-14 23:13:20.000001+01:00");
+            let starts = obj.get_item("starts").unwrap();
+            assert_eq!(
+                starts.getattr("tzinfo").unwrap().str().unwrap().to_string(),
+                "Europe/Paris"
+            );
+
+            let actual: Meeting = depythonize(&obj).unwrap();
+            assert_eq!(actual, meeting);
+            assert_eq!(actual.starts.time_zone().iana_name(), Some("Europe/Paris"));
+        })
+    }
+
+    #[test]
+    fn test_jiff_fixed_offset() {
+        Python::with_gil(|py| {
+            let created = Timestamp::from_second(0).unwrap();
+            let fixed = TimeZone::fixed(Offset::from_seconds(-7200).unwrap());
+            let meeting = Meeting {
+                created,
+                starts: Zoned::new(created, fixed),
+            };
+            let obj = pythonize(py, &meeting).unwrap();
+            assert_eq!(str_of(&obj, "starts"), "1969-12-31 22:00:00-02:00");
+
+            let actual: Meeting = depythonize(&obj).unwrap();
+            assert_eq!(actual, meeting);
+        })
+    }
+}
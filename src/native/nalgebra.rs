@@ -0,0 +1,156 @@
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, Dim, OMatrix, Scalar};
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::ndarray::ArrayPayload;
+use super::{deserialize_tagged, serialize_tagged, Element, Native, ARRAY_TOKEN};
+
+/// Whether `C` is statically a single column, in which case the matrix is a vector
+fn is_vector<C: Dim>() -> bool {
+    C::try_to_usize() == Some(1)
+}
+
+/// The shape of `OMatrix<_, R, C>` in numpy notation, with `_` for dynamic dimensions
+fn expected_shape<R: Dim, C: Dim>() -> String {
+    let dim = |n: Option<usize>| n.map_or_else(|| "_".to_string(), |n| n.to_string());
+    if is_vector::<C>() {
+        format!("({},)", dim(R::try_to_usize()))
+    } else {
+        format!("({}, {})", dim(R::try_to_usize()), dim(C::try_to_usize()))
+    }
+}
+
+/// Vectors become 1-D arrays and other matrices 2-D arrays, in row-major order;
+/// when deserializing, the shape must match any fixed dimensions
+impl<T, R, C> Native for OMatrix<T, R, C>
+where
+    T: Element + Scalar,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<R, C>,
+{
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let shape = if is_vector::<C>() {
+            vec![self.nrows()]
+        } else {
+            vec![self.nrows(), self.ncols()]
+        };
+        let (rows, cols) = self.shape();
+        let elements = (0..rows).flat_map(|i| (0..cols).map(move |j| &self[(i, j)]));
+        let payload = ArrayPayload::new(shape, elements);
+        serialize_tagged(serializer, ARRAY_TOKEN, &payload)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let payload: ArrayPayload = deserialize_tagged(deserializer, ARRAY_TOKEN)?;
+        let (actual, elements) = payload.into_elements::<T, D::Error>()?;
+        let fits = |expected: Option<usize>, n: usize| expected.map_or(true, |e| e == n);
+        let dims = match (actual.as_slice(), is_vector::<C>()) {
+            (&[rows], true) => Some((rows, 1)),
+            (&[rows, cols], false) => Some((rows, cols)),
+            _ => None,
+        }
+        .filter(|&(rows, cols)| fits(R::try_to_usize(), rows) && fits(C::try_to_usize(), cols));
+        let (rows, cols) = dims.ok_or_else(|| {
+            let actual: Vec<_> = actual.iter().map(ToString::to_string).collect();
+            D::Error::custom(format_args!(
+                "expected an array with shape {}, got ({})",
+                expected_shape::<R, C>(),
+                actual.join(", ")
+            ))
+        })?;
+        Ok(OMatrix::from_row_iterator_generic(
+            R::from_usize(rows),
+            C::from_usize(cols),
+            elements,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{DMatrix, Matrix2x3, Vector3};
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Transform {
+        #[serde(with = "crate::native")]
+        matrix: Matrix2x3<f32>,
+        #[serde(with = "crate::native")]
+        offset: Vector3<i64>,
+        #[serde(with = "crate::native")]
+        weights: DMatrix<f64>,
+    }
+
+    fn transform() -> Transform {
+        Transform {
+            matrix: Matrix2x3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0),
+            offset: Vector3::new(7, 8, 9),
+            weights: DMatrix::from_row_slice(1, 2, &[0.5, 0.25]),
+        }
+    }
+
+    #[test]
+    fn test_matrix_payload() {
+        let json = serde_json::to_value(transform()).unwrap();
+        assert_eq!(json["matrix"][1], serde_json::json!([2, 3]));
+        let first_row: Vec<u8> = [1.0f32, 2.0, 3.0]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        assert_eq!(
+            json["matrix"][2].as_array().unwrap()[..12],
+            serde_json::json!(first_row).as_array().unwrap()[..]
+        );
+        assert_eq!(json["offset"][1], serde_json::json!([3]));
+        assert_eq!(json["weights"][1], serde_json::json!([1, 2]));
+
+        let actual: Transform = serde_json::from_value(json).unwrap();
+        assert_eq!(actual, transform());
+    }
+
+    #[test]
+    fn test_matrix_shape_check() {
+        let mut json = serde_json::to_value(transform()).unwrap();
+        json["matrix"][1] = serde_json::json!([3, 2]);
+        let err = serde_json::from_value::<Transform>(json.clone()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected an array with shape (2, 3), got (3, 2)"
+        );
+
+        json["matrix"][1] = serde_json::json!([2, 3]);
+        json["offset"][1] = serde_json::json!([3, 1]);
+        let err = serde_json::from_value::<Transform>(json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected an array with shape (3,), got (3, 1)"
+        );
+    }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_matrix_numpy_roundtrip() {
+        Python::with_gil(|py| {
+            let obj = pythonize(py, &transform()).unwrap();
+            let matrix = obj.get_item("matrix").unwrap();
+            assert_eq!(matrix.getattr("shape").unwrap().to_string(), "(2, 3)");
+            assert_eq!(matrix.get_item((0, 2)).unwrap().to_string(), "3.0");
+            let offset = obj.get_item("offset").unwrap();
+            assert_eq!(offset.getattr("shape").unwrap().to_string(), "(3,)");
+
+            let actual: Transform = depythonize(&obj).unwrap();
+            assert_eq!(actual, transform());
+        })
+    }
+}
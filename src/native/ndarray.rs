@@ -0,0 +1,303 @@
+use std::mem::size_of;
+
+use ndarray::{Array, ArrayBase, ArrayD, ArrayView, Data, Dimension, IxDyn};
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+use super::{deserialize_tagged, serialize_tagged, Bytes, Native, ARRAY_TOKEN};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Array element types with a numpy dtype
+pub trait Element: Copy + sealed::Sealed {
+    /// The dtype's kind and size, such as `f8`
+    #[doc(hidden)]
+    const TYPE_CODE: &'static str;
+
+    #[doc(hidden)]
+    fn write_ne_bytes(self, bytes: &mut Vec<u8>);
+
+    #[doc(hidden)]
+    fn read_ne_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $code:literal),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl Element for $ty {
+            const TYPE_CODE: &'static str = $code;
+
+            fn write_ne_bytes(self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_ne_bytes());
+            }
+
+            fn read_ne_bytes(bytes: &[u8]) -> Self {
+                let mut buf = [0; size_of::<$ty>()];
+                buf.copy_from_slice(bytes);
+                <$ty>::from_ne_bytes(buf)
+            }
+        }
+    )*};
+}
+
+impl_element!(
+    f32 => "f4", f64 => "f8",
+    i8 => "i1", i16 => "i2", i32 => "i4", i64 => "i8",
+    u8 => "u1", u16 => "u2", u32 => "u4", u64 => "u8"
+);
+
+impl sealed::Sealed for bool {}
+
+impl Element for bool {
+    const TYPE_CODE: &'static str = "b1";
+
+    fn write_ne_bytes(self, bytes: &mut Vec<u8>) {
+        bytes.push(self.into());
+    }
+
+    fn read_ne_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+/// The numpy `dtype.str` of `A` in native byte order, such as `<f8`
+fn dtype<A: Element>() -> String {
+    let order = if size_of::<A>() == 1 {
+        '|'
+    } else if cfg!(target_endian = "little") {
+        '<'
+    } else {
+        '>'
+    };
+    format!("{}{}", order, A::TYPE_CODE)
+}
+
+/// An array's dtype, shape and elements in C order and native byte order
+pub(crate) struct ArrayPayload(String, Vec<usize>, Bytes);
+
+impl ArrayPayload {
+    /// `elements` must be in C order and match `shape`
+    pub(crate) fn new<'a, A: Element + 'a>(
+        shape: Vec<usize>,
+        elements: impl IntoIterator<Item = &'a A>,
+    ) -> Self {
+        let len: usize = shape.iter().product();
+        let mut data = Vec::with_capacity(len * size_of::<A>());
+        for element in elements {
+            element.write_ne_bytes(&mut data);
+        }
+        ArrayPayload(dtype::<A>(), shape, Bytes(data))
+    }
+
+    /// The shape and elements, checking that the dtype is that of `A`
+    pub(crate) fn into_elements<A: Element, E: Error>(self) -> Result<(Vec<usize>, Vec<A>), E> {
+        let ArrayPayload(actual, shape, Bytes(data)) = self;
+        let expected = dtype::<A>();
+        if actual != expected {
+            return Err(E::custom(format_args!(
+                "expected an array with dtype {}, got {}",
+                expected, actual
+            )));
+        }
+        let len: usize = shape.iter().product();
+        if data.len() != len * size_of::<A>() {
+            return Err(E::invalid_length(
+                data.len(),
+                &"data matching the array shape",
+            ));
+        }
+        let elements = data
+            .chunks_exact(size_of::<A>())
+            .map(A::read_ne_bytes)
+            .collect();
+        Ok((shape, elements))
+    }
+}
+
+impl Serialize for ArrayPayload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (&self.0, &self.1, &self.2).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArrayPayload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (dtype, shape, data) = Deserialize::deserialize(deserializer)?;
+        Ok(ArrayPayload(dtype, shape, data))
+    }
+}
+
+fn serialize_array<A, S, D, Ser>(
+    array: &ArrayBase<S, D>,
+    serializer: Ser,
+) -> Result<Ser::Ok, Ser::Error>
+where
+    A: Element,
+    S: Data<Elem = A>,
+    D: Dimension,
+    Ser: Serializer,
+{
+    let payload = ArrayPayload::new(array.shape().to_vec(), array.iter());
+    serialize_tagged(serializer, ARRAY_TOKEN, &payload)
+}
+
+/// Copied into a `numpy.ndarray` of the matching dtype, which must match exactly when
+/// deserializing
+impl<A: Element, D: Dimension> Native for Array<A, D> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self, serializer)
+    }
+
+    fn deserialize_native<'de, De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        let payload: ArrayPayload = deserialize_tagged(deserializer, ARRAY_TOKEN)?;
+        let (shape, elements) = payload.into_elements()?;
+        let ndim = shape.len();
+        ArrayD::from_shape_vec(IxDyn(&shape), elements)
+            .map_err(De::Error::custom)?
+            .into_dimensionality()
+            .map_err(|_| {
+                De::Error::custom(format_args!(
+                    "expected an array with {} dimensions, got {}",
+                    D::NDIM.map_or_else(|| "any number of".to_string(), |n| n.to_string()),
+                    ndim
+                ))
+            })
+    }
+}
+
+/// Views can only be serialized, as there is no array for them to borrow from when
+/// deserializing
+impl<A: Element, D: Dimension> Native for ArrayView<'_, A, D> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self, serializer)
+    }
+
+    fn deserialize_native<'de, De>(_deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        Err(De::Error::custom(
+            "cannot deserialize an array view, use an owned array instead",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::{array, Array1, Array2, ArrayView2};
+    use pyo3::prelude::*;
+    use pyo3::types::IntoPyDict;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Arrays {
+        #[serde(with = "crate::native")]
+        matrix: Array2<f64>,
+        #[serde(with = "crate::native")]
+        mask: Array1<bool>,
+    }
+
+    fn arrays() -> Arrays {
+        Arrays {
+            matrix: array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+            mask: array![true, false],
+        }
+    }
+
+    #[test]
+    fn test_array_payload() {
+        let json = serde_json::to_value(arrays()).unwrap();
+        let little_endian = cfg!(target_endian = "little");
+        assert_eq!(json["matrix"][0], if little_endian { "<f8" } else { ">f8" });
+        assert_eq!(json["matrix"][1], serde_json::json!([2, 3]));
+        assert_eq!(json["matrix"][2].as_array().unwrap().len(), 48);
+        assert_eq!(json["mask"], serde_json::json!(["|b1", [2], [1, 0]]));
+
+        let actual: Arrays = serde_json::from_value(json).unwrap();
+        assert_eq!(actual, arrays());
+    }
+
+    #[test]
+    #[ignore = "needs the numpy Python package"]
+    fn test_numpy_roundtrip() {
+        Python::with_gil(|py| {
+            let numpy = py.import_bound("numpy").unwrap();
+            let obj = pythonize(py, &arrays()).unwrap();
+            let matrix = obj.get_item("matrix").unwrap();
+            assert!(matrix
+                .is_instance(&numpy.getattr("ndarray").unwrap())
+                .unwrap());
+            assert_eq!(matrix.getattr("shape").unwrap().to_string(), "(2, 3)");
+            assert_eq!(matrix.getattr("dtype").unwrap().to_string(), "float64");
+            assert!(matrix
+                .getattr("flags")
+                .unwrap()
+                .getattr("writeable")
+                .unwrap()
+                .is_truthy()
+                .unwrap());
+
+            let actual: Arrays = depythonize(&obj).unwrap();
+            assert_eq!(actual, arrays());
+
+            // transposed and big-endian arrays are converted to C order and native byte order
+            let obj = py
+                .eval_bound(
+                    "{'matrix': np.arange(6, dtype='>f8').reshape(3, 2).T, 'mask': np.array([True, False])}",
+                    None,
+                    Some(&[("np", numpy.clone())].into_py_dict_bound(py)),
+                )
+                .unwrap();
+            let actual: Arrays = depythonize(&obj).unwrap();
+            assert_eq!(actual.matrix, array![[0.0, 2.0, 4.0], [1.0, 3.0, 5.0]]);
+
+            obj.set_item("mask", numpy.call_method1("zeros", (2,)).unwrap())
+                .unwrap();
+            let err = depythonize::<Arrays>(&obj).unwrap_err();
+            assert_eq!(err.to_string(), "expected an array with dtype |b1, got <f8");
+        })
+    }
+
+    #[test]
+    fn test_array_view() {
+        #[derive(Serialize)]
+        struct View<'a> {
+            #[serde(with = "crate::native")]
+            matrix: ArrayView2<'a, f64>,
+        }
+
+        let matrix = arrays().matrix;
+        let json = serde_json::to_value(View { matrix: matrix.t() }).unwrap();
+        assert_eq!(json["matrix"][1], serde_json::json!([3, 2]));
+        let transposed: Vec<f64> = json["matrix"][2]
+            .as_array()
+            .unwrap()
+            .chunks(8)
+            .map(|chunk| {
+                let bytes: Vec<u8> = chunk.iter().map(|b| b.as_u64().unwrap() as u8).collect();
+                f64::from_ne_bytes(bytes.try_into().unwrap())
+            })
+            .collect();
+        assert_eq!(transposed, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    }
+}
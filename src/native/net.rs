@@ -0,0 +1,205 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer, Error, SeqAccess};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, IP_ADDRESS_TOKEN, SOCKET_ADDRESS_TOKEN};
+
+fn parse<T, E>(text: &str) -> Result<T, E>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    E: Error,
+{
+    text.parse()
+        .map_err(|err| E::custom(format_args!("invalid address {:?}: {}", text, err)))
+}
+
+macro_rules! impl_ip_address {
+    ($($ty:ty),*) => {$(
+        impl Native for $ty {
+            fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serialize_tagged(serializer, IP_ADDRESS_TOKEN, &self.to_string())
+            }
+
+            fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let text: String = deserialize_tagged(deserializer, IP_ADDRESS_TOKEN)?;
+                parse(&text)
+            }
+        }
+    )*};
+}
+
+impl_ip_address!(IpAddr, Ipv4Addr, Ipv6Addr);
+
+/// Serialized as a `(host, port)` tuple for IPv4 and a `(host, port, flowinfo, scope_id)`
+/// tuple for IPv6, as used by Python's `socket` module
+impl Native for SocketAddr {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            SocketAddr::V4(addr) => serialize_tagged(
+                serializer,
+                SOCKET_ADDRESS_TOKEN,
+                &(addr.ip().to_string(), addr.port()),
+            ),
+            SocketAddr::V6(addr) => serialize_tagged(
+                serializer,
+                SOCKET_ADDRESS_TOKEN,
+                &(
+                    addr.ip().to_string(),
+                    addr.port(),
+                    addr.flowinfo(),
+                    addr.scope_id(),
+                ),
+            ),
+        }
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let SocketAddress(addr) = deserialize_tagged(deserializer, SOCKET_ADDRESS_TOKEN)?;
+        Ok(addr)
+    }
+}
+
+/// A socket address from either a tuple or a `host:port` string
+struct SocketAddress(SocketAddr);
+
+impl<'de> Deserialize<'de> for SocketAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(SocketAddressVisitor)
+    }
+}
+
+struct SocketAddressVisitor;
+
+impl<'de> de::Visitor<'de> for SocketAddressVisitor {
+    type Value = SocketAddress;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a socket address tuple or string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<SocketAddress, E> {
+        parse(v).map(SocketAddress)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<SocketAddress, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let host: String = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let port: u16 = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let addr = match parse(&host)? {
+            IpAddr::V4(ip) => SocketAddr::V4(SocketAddrV4::new(ip, port)),
+            IpAddr::V6(ip) => {
+                let flowinfo = seq.next_element()?.unwrap_or_default();
+                let scope_id = seq.next_element()?.unwrap_or_default();
+                SocketAddr::V6(SocketAddrV6::new(ip, port, flowinfo, scope_id))
+            }
+        };
+        match seq.next_element::<de::IgnoredAny>()? {
+            Some(_) => Err(A::Error::custom("too many socket address components")),
+            None => Ok(SocketAddress(addr)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Peer {
+        #[serde(with = "crate::native")]
+        ip: IpAddr,
+        #[serde(with = "crate::native")]
+        v4: Ipv4Addr,
+        #[serde(with = "crate::native")]
+        v6: Ipv6Addr,
+        #[serde(with = "crate::native")]
+        listen: SocketAddr,
+        #[serde(with = "crate::native")]
+        connect: SocketAddr,
+    }
+
+    fn repr(obj: &Bound<'_, PyAny>, key: &str) -> String {
+        obj.get_item(key).unwrap().repr().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_net_roundtrip() {
+        Python::with_gil(|py| {
+            let peer = Peer {
+                ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+                v4: Ipv4Addr::new(192, 168, 0, 1),
+                v6: "fe80::1".parse().unwrap(),
+                listen: "0.0.0.0:8080".parse().unwrap(),
+                connect: "[::1]:443".parse().unwrap(),
+            };
+            let obj = pythonize(py, &peer).unwrap();
+            assert_eq!(repr(&obj, "ip"), "IPv6Address('::1')");
+            assert_eq!(repr(&obj, "v4"), "IPv4Address('192.168.0.1')");
+            assert_eq!(repr(&obj, "v6"), "IPv6Address('fe80::1')");
+            assert_eq!(repr(&obj, "listen"), "('0.0.0.0', 8080)");
+            assert_eq!(repr(&obj, "connect"), "('::1', 443, 0, 0)");
+
+            let actual: Peer = depythonize(&obj).unwrap();
+            assert_eq!(actual, peer);
+        })
+    }
+
+    #[test]
+    fn test_net_from_python() {
+        Python::with_gil(|py| {
+            let locals = PyModule::import_bound(py, "ipaddress").unwrap().dict();
+            let obj = py
+                .eval_bound(
+                    "{'ip': '10.0.0.1', 'v4': IPv4Address('127.0.0.1'), 'v6': '::', \
+                     'listen': (IPv4Address('127.0.0.1'), 80), 'connect': '[::1]:22'}",
+                    None,
+                    Some(&locals),
+                )
+                .unwrap();
+            let actual: Peer = depythonize(&obj).unwrap();
+            assert_eq!(actual.ip, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+            assert_eq!(actual.v4, Ipv4Addr::LOCALHOST);
+            assert_eq!(actual.v6, Ipv6Addr::UNSPECIFIED);
+            assert_eq!(actual.listen, "127.0.0.1:80".parse().unwrap());
+            assert_eq!(actual.connect, "[::1]:22".parse().unwrap());
+
+            obj.set_item("v4", "::1").unwrap();
+            let err = depythonize::<Peer>(&obj).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid address \"::1\""),
+                "{}",
+                err
+            );
+        })
+    }
+}
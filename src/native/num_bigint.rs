@@ -0,0 +1,108 @@
+use std::str::FromStr;
+
+use num_bigint::{BigInt, BigUint};
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, NumericText, INT_TOKEN};
+
+fn parse<T: FromStr, E: Error>(text: &str) -> Result<T, E>
+where
+    T::Err: std::fmt::Display,
+{
+    text.parse()
+        .map_err(|err| E::custom(format_args!("invalid integer {:?}: {}", text, err)))
+}
+
+impl Native for BigInt {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, INT_TOKEN, &self.to_string())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let NumericText(text) = deserialize_tagged(deserializer, INT_TOKEN)?;
+        parse(&text)
+    }
+}
+
+impl Native for BigUint {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, INT_TOKEN, &self.to_string())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let NumericText(text) = deserialize_tagged(deserializer, INT_TOKEN)?;
+        parse(&text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::{BigInt, BigUint};
+    use pyo3::prelude::*;
+    use pyo3::types::PyLong;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Factorials {
+        #[serde(with = "crate::native")]
+        signed: BigInt,
+        #[serde(with = "crate::native")]
+        unsigned: BigUint,
+    }
+
+    #[test]
+    fn test_bigint_roundtrip() {
+        Python::with_gil(|py| {
+            let unsigned = (1u32..=30).map(BigUint::from).product::<BigUint>();
+            let factorials = Factorials {
+                signed: -BigInt::from(unsigned.clone()),
+                unsigned,
+            };
+            let obj = pythonize(py, &factorials).unwrap();
+            let signed = obj.get_item("signed").unwrap();
+            assert!(signed.is_exact_instance_of::<PyLong>());
+            assert_eq!(
+                signed.repr().unwrap().to_cow().unwrap(),
+                "-265252859812191058636308480000000"
+            );
+
+            let actual: Factorials = depythonize(&obj).unwrap();
+            assert_eq!(actual, factorials);
+        })
+    }
+
+    #[test]
+    fn test_bigint_from_python() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'signed': -2 ** 100, 'unsigned': 2 ** 100}", None, None)
+                .unwrap();
+            let actual: Factorials = depythonize(&obj).unwrap();
+            assert_eq!(actual.unsigned, BigUint::from(1u8) << 100u32);
+            assert_eq!(actual.signed, -(BigInt::from(1u8) << 100u32));
+
+            obj.set_item("unsigned", -1i32).unwrap();
+            let err = depythonize::<Factorials>(&obj).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid integer \"-1\""),
+                "{}",
+                err
+            );
+        })
+    }
+}
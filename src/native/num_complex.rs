@@ -0,0 +1,106 @@
+use num_complex::Complex;
+use serde::de::Deserializer;
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, number, serialize_tagged, Native, COMPLEX_TOKEN};
+
+impl Native for Complex<f64> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components: number::Complex = (self.re, self.im);
+        serialize_tagged(serializer, COMPLEX_TOKEN, &components)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (re, im): number::Complex = deserialize_tagged(deserializer, COMPLEX_TOKEN)?;
+        Ok(Complex::new(re, im))
+    }
+}
+
+/// Python's `complex` has double precision, so parts are rounded when deserializing
+impl Native for Complex<f32> {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components: number::Complex = (self.re.into(), self.im.into());
+        serialize_tagged(serializer, COMPLEX_TOKEN, &components)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (re, im): number::Complex = deserialize_tagged(deserializer, COMPLEX_TOKEN)?;
+        Ok(Complex::new(re as f32, im as f32))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex;
+    use pyo3::prelude::*;
+    use pyo3::types::PyComplex;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Signal {
+        #[serde(with = "crate::native")]
+        sample: Complex<f64>,
+        #[serde(with = "crate::native")]
+        coarse: Complex<f32>,
+    }
+
+    #[test]
+    fn test_complex_roundtrip() {
+        Python::with_gil(|py| {
+            let signal = Signal {
+                sample: Complex::new(1.5, -0.25),
+                coarse: Complex::new(0.1, 2.0),
+            };
+            let obj = pythonize(py, &signal).unwrap();
+            let sample = obj.get_item("sample").unwrap();
+            assert!(sample.is_exact_instance_of::<PyComplex>());
+            assert_eq!(sample.repr().unwrap().to_cow().unwrap(), "(1.5-0.25j)");
+
+            let actual: Signal = depythonize(&obj).unwrap();
+            assert_eq!(actual, signal);
+        })
+    }
+
+    #[test]
+    fn test_complex_from_real() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'sample': 3, 'coarse': 0.5}", None, None)
+                .unwrap();
+            let actual: Signal = depythonize(&obj).unwrap();
+            assert_eq!(actual.sample, Complex::new(3.0, 0.0));
+            assert_eq!(actual.coarse, Complex::new(0.5, 0.0));
+
+            obj.set_item("sample", true).unwrap();
+            assert!(depythonize::<Signal>(&obj).is_err());
+        })
+    }
+
+    #[test]
+    fn test_complex_without_adapter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Plain {
+            sample: Complex<f64>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py.eval_bound("{'sample': 2-1j}", None, None).unwrap();
+            let actual: Plain = depythonize(&obj).unwrap();
+            assert_eq!(actual.sample, Complex::new(2.0, -1.0));
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use num_rational::Ratio;
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, NumericText, FRACTION_TOKEN};
+
+/// Exact for any integer type `T`, including `num_bigint::BigInt`
+impl<T> Native for Ratio<T>
+where
+    Ratio<T>: Display + FromStr,
+    <Ratio<T> as FromStr>::Err: Display,
+{
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, FRACTION_TOKEN, &self.to_string())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let NumericText(text) = deserialize_tagged(deserializer, FRACTION_TOKEN)?;
+        text.parse()
+            .map_err(|err| D::Error::custom(format_args!("invalid fraction {:?}: {}", text, err)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_rational::{Ratio, Rational64};
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Scale {
+        #[serde(with = "crate::native")]
+        factor: Rational64,
+        #[serde(with = "crate::native")]
+        small: Ratio<u8>,
+    }
+
+    #[test]
+    fn test_fraction_roundtrip() {
+        Python::with_gil(|py| {
+            let scale = Scale {
+                factor: Rational64::new(-6, 8),
+                small: Ratio::from_integer(3),
+            };
+            let obj = pythonize(py, &scale).unwrap();
+            assert_eq!(
+                obj.get_item("factor")
+                    .unwrap()
+                    .repr()
+                    .unwrap()
+                    .to_cow()
+                    .unwrap(),
+                "Fraction(-3, 4)"
+            );
+            assert_eq!(
+                obj.get_item("small")
+                    .unwrap()
+                    .repr()
+                    .unwrap()
+                    .to_cow()
+                    .unwrap(),
+                "Fraction(3, 1)"
+            );
+
+            let actual: Scale = depythonize(&obj).unwrap();
+            assert_eq!(actual, scale);
+        })
+    }
+
+    #[test]
+    fn test_fraction_from_python() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'factor': __import__('fractions').Fraction(1, 3), 'small': 7}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let actual: Scale = depythonize(&obj).unwrap();
+            assert_eq!(actual.factor, Rational64::new(1, 3));
+            assert_eq!(actual.small, Ratio::from_integer(7));
+
+            obj.set_item("small", 256).unwrap();
+            let err = depythonize::<Scale>(&obj).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid fraction \"256\""),
+                "{}",
+                err
+            );
+        })
+    }
+
+    #[test]
+    fn test_fraction_without_adapter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Plain {
+            factor: Rational64,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'factor': __import__('fractions').Fraction(-6, 8)}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let actual: Plain = depythonize(&obj).unwrap();
+            assert_eq!(actual.factor, Rational64::new(-3, 4));
+        })
+    }
+}
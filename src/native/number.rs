@@ -0,0 +1,31 @@
+//! Conversions between tagged payloads and Python's numeric types
+
+use pyo3::types::{PyAnyMethods, PyBool, PyComplex, PyComplexMethods, PyFloat, PyLong};
+use pyo3::{Bound, IntoPy, PyAny, PyObject, PyResult};
+
+/// Components of a `complex`: the real and imaginary parts
+pub(crate) type Complex = (f64, f64);
+
+pub(crate) fn complex_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let (real, imag): Complex = payload.extract()?;
+    Ok(PyComplex::from_doubles_bound(payload.py(), real, imag).into_any())
+}
+
+/// Real numbers are accepted as complex numbers with no imaginary part
+pub(crate) fn complex_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    let payload: Complex = if let Ok(complex) = obj.downcast::<PyComplex>() {
+        (complex.real(), complex.imag())
+    } else if obj.is_instance_of::<PyFloat>()
+        || (obj.is_instance_of::<PyLong>() && !obj.is_instance_of::<PyBool>())
+    {
+        (obj.extract()?, 0.0)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(
+        IntoPy::<PyObject>::into_py(payload, py).into_bound(py),
+    ))
+}
@@ -0,0 +1,159 @@
+//! Conversions between tagged payloads and `numpy.ndarray`, which is a third-party
+//! package and so is only imported once arrays are involved
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyByteArray, PyBytes, PyBytesMethods, PyTuple};
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::{import, import_loaded};
+
+static FROMBUFFER: GILOnceCell<PyObject> = GILOnceCell::new();
+static NDARRAY: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+static GENERIC: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// Build the array from a `(dtype, shape, data)` payload, where `data` holds the
+/// elements in C order and native byte order; the data is copied into a
+/// `bytearray` so that the array is writable
+pub(crate) fn array_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let py = payload.py();
+    let (dtype, shape, data): (Bound<'py, PyAny>, Bound<'py, PyAny>, Bound<'py, PyAny>) =
+        payload.extract()?;
+    let buffer = PyByteArray::from_bound(&data)?;
+    import(&FROMBUFFER, py, "numpy", "frombuffer")?
+        .call1((buffer, dtype))?
+        .call_method1("reshape", (shape,))
+}
+
+fn is_array(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+    match import_loaded(&NDARRAY, obj.py(), "numpy", "ndarray")? {
+        Some(ndarray) => obj.is_instance(ndarray),
+        None => Ok(false),
+    }
+}
+
+/// The dtype of the array in native byte order, and the array converted to it
+fn native_order<'py>(
+    array: &Bound<'py, PyAny>,
+) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+    let dtype = array.getattr("dtype")?;
+    if dtype.getattr("isnative")?.is_truthy()? {
+        return Ok((dtype, array.clone()));
+    }
+    let dtype = dtype.call_method1("newbyteorder", ("=",))?;
+    let array = array.call_method1("astype", (&dtype,))?;
+    Ok((dtype, array))
+}
+
+pub(crate) fn array_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if !is_array(obj)? {
+        return Ok(None);
+    }
+    let (dtype, array) = native_order(obj)?;
+    let payload = [
+        dtype.getattr("str")?,
+        array.getattr("shape")?,
+        array.call_method0("tobytes")?,
+    ];
+    Ok(Some(PyTuple::new_bound(obj.py(), payload).into_any()))
+}
+
+/// The elements of a numeric `numpy.ndarray` in C order, widened to 64 bits
+pub(crate) enum Elements {
+    Bool(Vec<bool>),
+    Int(Vec<i64>),
+    UInt(Vec<u64>),
+    Float(Vec<f64>),
+}
+
+/// The contents of a `numpy.ndarray` of at least one dimension, to deserialize as a sequence
+pub(crate) enum ArrayContents<'py> {
+    /// The shape and elements of an array of booleans, integers or floats, which are
+    /// decoded from a single copy of the array's data
+    Numeric(Vec<usize>, Elements),
+    /// Any other array, such as one of strings or objects, converted with `tolist()`
+    List(Bound<'py, PyAny>),
+    /// The length and named fields of a one-dimensional structured array, each with the
+    /// contents of its column, so that elements are deserialized as maps
+    Records(usize, Vec<(String, ArrayContents<'py>)>),
+}
+
+fn decode<const N: usize, T, U>(data: &[u8], from_ne_bytes: fn([u8; N]) -> T) -> Vec<U>
+where
+    U: From<T>,
+{
+    data.chunks_exact(N)
+        .map(|chunk| U::from(from_ne_bytes(chunk.try_into().unwrap())))
+        .collect()
+}
+
+/// The contents of `obj`, or `None` if it is not a `numpy.ndarray` or has no dimensions
+pub(crate) fn array_contents<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<ArrayContents<'py>>> {
+    if !is_array(obj)? || obj.getattr("ndim")?.extract::<usize>()? == 0 {
+        return Ok(None);
+    }
+    let dtype = obj.getattr("dtype")?;
+    let names = dtype.getattr("names")?;
+    if !names.is_none() && obj.getattr("ndim")?.extract::<usize>()? == 1 {
+        // each field is converted as a whole, rather than element by element
+        let mut columns = Vec::new();
+        for name in names.iter()? {
+            let name = name?;
+            let column = obj.get_item(&name)?;
+            let contents = match array_contents(&column)? {
+                Some(contents) => contents,
+                None => ArrayContents::List(column.call_method0("tolist")?),
+            };
+            columns.push((name.extract()?, contents));
+        }
+        return Ok(Some(ArrayContents::Records(obj.len()?, columns)));
+    }
+    let kind: char = dtype.getattr("kind")?.extract()?;
+    let itemsize: usize = dtype.getattr("itemsize")?.extract()?;
+    let elements = match decoder(kind, itemsize) {
+        Some(elements) => elements,
+        None => return Ok(Some(ArrayContents::List(obj.call_method0("tolist")?))),
+    };
+    let (_, array) = native_order(obj)?;
+    let data = array.call_method0("tobytes")?.downcast_into::<PyBytes>()?;
+    let elements = elements(data.as_bytes());
+    let shape = obj.getattr("shape")?.extract()?;
+    Ok(Some(ArrayContents::Numeric(shape, elements)))
+}
+
+/// How to decode elements of a numpy dtype `kind` and `itemsize` in native byte order,
+/// or `None` for arrays which aren't of booleans, integers or floats
+pub(crate) fn decoder(kind: char, itemsize: usize) -> Option<fn(&[u8]) -> Elements> {
+    Some(match (kind, itemsize) {
+        ('b', 1) => |data| Elements::Bool(data.iter().map(|&byte| byte != 0).collect()),
+        ('i', 1) => |data| Elements::Int(decode(data, i8::from_ne_bytes)),
+        ('i', 2) => |data| Elements::Int(decode(data, i16::from_ne_bytes)),
+        ('i', 4) => |data| Elements::Int(decode(data, i32::from_ne_bytes)),
+        ('i', 8) => |data| Elements::Int(decode(data, i64::from_ne_bytes)),
+        ('u', 1) => |data| Elements::UInt(decode(data, u8::from_ne_bytes)),
+        ('u', 2) => |data| Elements::UInt(decode(data, u16::from_ne_bytes)),
+        ('u', 4) => |data| Elements::UInt(decode(data, u32::from_ne_bytes)),
+        ('u', 8) => |data| Elements::UInt(decode(data, u64::from_ne_bytes)),
+        ('f', 4) => |data| Elements::Float(decode(data, f32::from_ne_bytes)),
+        ('f', 8) => |data| Elements::Float(decode(data, f64::from_ne_bytes)),
+        _ => return None,
+    })
+}
+
+/// The builtin Python equivalent of a numpy scalar such as `numpy.int64`, or `None` if
+/// `obj` is not a numpy scalar or has no builtin equivalent
+pub(crate) fn scalar_to_builtin<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let generic = match import_loaded(&GENERIC, obj.py(), "numpy", "generic")? {
+        Some(generic) if obj.is_instance(generic)? => generic,
+        _ => return Ok(None),
+    };
+    let item = obj.call_method0("item")?;
+    // e.g. `numpy.longdouble` has no builtin equivalent, so is returned unchanged
+    if item.is_instance(generic)? {
+        return Ok(None);
+    }
+    Ok(Some(item))
+}
@@ -0,0 +1,77 @@
+//! Conversions between version strings and `packaging.version.Version`, which is a
+//! third-party package and so falls back to plain strings when it is not installed
+
+use pyo3::exceptions::{PyImportError, PyValueError};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, IntoPy, PyAny, PyObject, PyResult, Python};
+
+static VERSION: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// `packaging.version.Version`, or `None` if `packaging` is not installed
+fn version_class(py: Python<'_>) -> PyResult<Option<&Bound<'_, PyAny>>> {
+    let class = VERSION.get_or_try_init(py, || match py.import_bound("packaging.version") {
+        Ok(module) => Ok(Some(module.getattr("Version")?.unbind())),
+        Err(err) if err.is_instance_of::<PyImportError>(py) => Ok(None),
+        Err(err) => Err(err),
+    })?;
+    Ok(class.as_ref().map(|class| class.bind(py)))
+}
+
+pub(crate) fn version_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    match version_class(payload.py())? {
+        Some(class) => class.call1((payload,)),
+        None => Ok(payload.clone()),
+    }
+}
+
+/// Rebuild a semantic version string from the parts of a `packaging.version.Version`,
+/// as its string form is normalized according to PEP 440; pre-, post- and
+/// development releases become pre-release identifiers and the local version
+/// becomes build metadata
+pub(crate) fn version_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    match version_class(py)? {
+        Some(class) if obj.is_instance(class)? => {}
+        _ => return Ok(None),
+    }
+
+    let epoch: u64 = obj.getattr("epoch")?.extract()?;
+    if epoch != 0 {
+        return Err(PyValueError::new_err(format!(
+            "version epoch {} cannot be represented as a semantic version",
+            epoch
+        )));
+    }
+    let release: Vec<u64> = obj.getattr("release")?.extract()?;
+    if release.len() > 3 {
+        return Err(PyValueError::new_err(format!(
+            "version {} has more than three release components",
+            obj.str()?
+        )));
+    }
+    let component = |i: usize| release.get(i).copied().unwrap_or(0);
+    let mut version = format!("{}.{}.{}", component(0), component(1), component(2));
+
+    let mut pre = Vec::new();
+    if let Some((letter, number)) = obj.getattr("pre")?.extract::<Option<(String, u64)>>()? {
+        pre.push(format!("{}.{}", letter, number));
+    }
+    if let Some(number) = obj.getattr("post")?.extract::<Option<u64>>()? {
+        pre.push(format!("post.{}", number));
+    }
+    if let Some(number) = obj.getattr("dev")?.extract::<Option<u64>>()? {
+        pre.push(format!("dev.{}", number));
+    }
+    if !pre.is_empty() {
+        version.push('-');
+        version.push_str(&pre.join("."));
+    }
+    if let Some(local) = obj.getattr("local")?.extract::<Option<String>>()? {
+        version.push('+');
+        version.push_str(&local);
+    }
+    Ok(Some(version.into_py(py).into_bound(py)))
+}
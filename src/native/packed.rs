@@ -0,0 +1,319 @@
+//! Serde adapter converting vectors of numbers in bulk, for use with
+//! `#[serde(with = "pythonize::native::packed")]`
+//!
+//! Serde hands the elements of a vector to a serializer one at a time. With the adapter,
+//! [`pythonize`](crate::pythonize) instead creates the whole `list` from the slice of
+//! elements in one loop, and a `Vec<u8>` is copied into
+//! [`PythonizeTypes::Bytes`](crate::PythonizeTypes::Bytes) in one go rather than
+//! becoming a `list` of `int`s. Other serde formats see the sequence as usual.
+//!
+//! Floats are still converted one at a time if some of them are NaN or infinite and the
+//! [`NonFinitePolicy`](crate::NonFinitePolicy) is not `Float`. Vectors are deserialized
+//! from any sequence.
+//!
+//! ```rust
+//! use pyo3::prelude::*;
+//! use pyo3::types::PyBytes;
+//! use pythonize::{depythonize, pythonize};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Signal {
+//!     #[serde(with = "pythonize::native::packed")]
+//!     samples: Vec<f64>,
+//!     #[serde(with = "pythonize::native::packed")]
+//!     raw: Vec<u8>,
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let signal = Signal {
+//!         samples: vec![0.5, 1.5],
+//!         raw: vec![1, 2],
+//!     };
+//!     let obj = pythonize(py, &signal).unwrap();
+//!     assert_eq!(obj.get_item("samples").unwrap().to_string(), "[0.5, 1.5]");
+//!     assert!(obj.get_item("raw").unwrap().is_instance_of::<PyBytes>());
+//!     assert_eq!(depythonize::<Signal>(&obj).unwrap(), signal);
+//! })
+//! ```
+
+use std::cell::Cell;
+
+use pyo3::types::PyList;
+use pyo3::{Bound, Python, ToPyObject};
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use super::{deserialize_tagged, serialize_tagged, PACKED_TOKEN};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Element types of vectors which can be converted in bulk
+pub trait PackedElement:
+    Copy + Serialize + DeserializeOwned + ToPyObject + sealed::Sealed + 'static
+{
+    #[doc(hidden)]
+    fn as_bytes(_elements: &[Self]) -> Option<&[u8]> {
+        None
+    }
+
+    #[doc(hidden)]
+    fn is_finite(self) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_packed_element {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl PackedElement for $ty {}
+    )*};
+}
+
+impl_packed_element!(i8, i16, i32, i64, u16, u32, u64);
+
+impl sealed::Sealed for u8 {}
+
+impl PackedElement for u8 {
+    fn as_bytes(elements: &[Self]) -> Option<&[u8]> {
+        Some(elements)
+    }
+}
+
+impl sealed::Sealed for f32 {}
+
+impl PackedElement for f32 {
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl sealed::Sealed for f64 {}
+
+impl PackedElement for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+/// The elements of a vector, whatever their type
+pub(crate) trait Elements {
+    /// The elements, if they are bytes
+    fn as_bytes(&self) -> Option<&[u8]>;
+
+    /// Whether none of the elements are NaN or infinite
+    fn all_finite(&self) -> bool;
+
+    /// A `list` of the elements, created in one go
+    fn to_list<'py>(&self, py: Python<'py>) -> Bound<'py, PyList>;
+}
+
+/// A slice borrowed by [`serialize`] for the duration of its call
+struct Slice<E> {
+    data: *const E,
+    len: usize,
+}
+
+impl<E: PackedElement> Slice<E> {
+    fn get(&self) -> &[E] {
+        // SAFETY: the slice is only taken by the `Pythonizer` within the call to
+        // `serialize`, which borrows it, and is cleared before that call returns or
+        // unwinds
+        unsafe { std::slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl<E: PackedElement> Elements for Slice<E> {
+    fn as_bytes(&self) -> Option<&[u8]> {
+        E::as_bytes(self.get())
+    }
+
+    fn all_finite(&self) -> bool {
+        self.get().iter().all(|element| element.is_finite())
+    }
+
+    fn to_list<'py>(&self, py: Python<'py>) -> Bound<'py, PyList> {
+        PyList::new_bound(py, self.get())
+    }
+}
+
+thread_local! {
+    /// The elements being serialized by [`serialize`], which the `Pythonizer` takes to
+    /// convert them in bulk
+    static ELEMENTS: Cell<Option<Box<dyn Elements>>> = const { Cell::new(None) };
+}
+
+/// Clears [`ELEMENTS`] when dropped, so that they don't outlive the call to
+/// [`serialize`] even if the serializer panics
+struct ClearElements;
+
+impl Drop for ClearElements {
+    fn drop(&mut self) {
+        ELEMENTS.with(|cell| cell.set(None));
+    }
+}
+
+/// Serialize a vector, which the `Pythonizer` converts in bulk
+pub fn serialize<E, S>(value: &[E], serializer: S) -> Result<S::Ok, S::Error>
+where
+    E: PackedElement,
+    S: Serializer,
+{
+    let slice = Slice {
+        data: value.as_ptr(),
+        len: value.len(),
+    };
+    ELEMENTS.with(|cell| cell.set(Some(Box::new(slice))));
+    let _clear = ClearElements;
+    serialize_tagged(serializer, PACKED_TOKEN, value)
+}
+
+/// Deserialize a vector from any sequence
+pub fn deserialize<'de, E, D>(deserializer: D) -> Result<Vec<E>, D::Error>
+where
+    E: PackedElement,
+    D: Deserializer<'de>,
+{
+    deserialize_tagged(deserializer, PACKED_TOKEN)
+}
+
+/// Take the elements being serialized by [`serialize`], which must be used before the
+/// `Pythonizer` returns
+pub(crate) fn take_elements() -> Option<Box<dyn Elements>> {
+    ELEMENTS.with(Cell::take)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Write};
+    use std::panic;
+
+    use pyo3::prelude::*;
+    use pyo3::types::{PyByteArray, PyBytes};
+    use serde::{Deserialize, Serialize};
+
+    use super::take_elements;
+    use crate::{depythonize, pythonize, NonFinitePolicy, PythonizeImmutable, Pythonizer};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Signal {
+        #[serde(with = "crate::native::packed")]
+        samples: Vec<f64>,
+        #[serde(with = "crate::native::packed")]
+        counts: Vec<i64>,
+        #[serde(with = "crate::native::packed")]
+        raw: Vec<u8>,
+    }
+
+    fn signal() -> Signal {
+        Signal {
+            samples: vec![0.25, -1.5, f64::NAN],
+            counts: vec![i64::MIN, 0, i64::MAX],
+            raw: vec![0, 255],
+        }
+    }
+
+    #[test]
+    fn test_packed_roundtrip() {
+        Python::with_gil(|py| {
+            let obj = pythonize(py, &signal()).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'samples': [0.25, -1.5, nan], \
+                 'counts': [-9223372036854775808, 0, 9223372036854775807], \
+                 'raw': b'\\x00\\xff'}"
+            );
+            let actual: Signal = depythonize(&obj).unwrap();
+            assert_eq!(actual.counts, signal().counts);
+            assert_eq!(actual.raw, signal().raw);
+            assert_eq!(actual.samples[..2], signal().samples[..2]);
+            assert!(actual.samples[2].is_nan());
+
+            // the options still apply
+            let pythonizer = Pythonizer::builder(py)
+                .non_finite(NonFinitePolicy::None)
+                .bytes_base64(true)
+                .build();
+            let obj = signal().serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.get_item("samples").unwrap().to_string(),
+                "[0.25, -1.5, None]"
+            );
+            assert_eq!(obj.get_item("raw").unwrap().to_string(), "AP8=");
+
+            let pythonizer = Pythonizer::builder(py).json_compatible(true).build();
+            let obj = Signal {
+                samples: vec![],
+                ..signal()
+            }
+            .serialize(pythonizer)
+            .unwrap();
+            assert_eq!(obj.get_item("raw").unwrap().to_string(), "[0, 255]");
+        })
+    }
+
+    #[test]
+    fn test_packed_types() {
+        Python::with_gil(|py| {
+            let pythonizer = Pythonizer::builder(py)
+                .types::<PythonizeImmutable>()
+                .build();
+            let obj = signal().serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.get_item("counts").unwrap().to_string(),
+                "(-9223372036854775808, 0, 9223372036854775807)"
+            );
+            assert!(obj.get_item("raw").unwrap().is_instance_of::<PyBytes>());
+
+            // a `bytearray` is another sequence of bytes
+            let obj = obj.call_method0("copy").unwrap();
+            obj.set_item("raw", PyByteArray::new_bound(py, &[7]))
+                .unwrap();
+            let actual: Signal = depythonize(&obj).unwrap();
+            assert_eq!(actual.raw, [7]);
+        })
+    }
+
+    #[test]
+    fn test_packed_payload() {
+        let json = serde_json::to_value(Signal {
+            samples: vec![0.5],
+            ..signal()
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "samples": [0.5],
+                "counts": [i64::MIN, 0, i64::MAX],
+                "raw": [0, 255],
+            })
+        );
+        let actual: Signal = serde_json::from_value(json).unwrap();
+        assert_eq!(actual.raw, [0, 255]);
+    }
+
+    #[test]
+    fn test_panic_clears_elements() {
+        /// Panics on writing the start of `self.0`
+        struct PanickingWriter(&'static str);
+
+        impl Write for PanickingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                assert!(!buf.starts_with(self.0.as_bytes()), "write failed");
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let result = panic::catch_unwind(|| serde_json::to_writer(PanickingWriter("["), &signal()));
+        assert!(result.is_err());
+        assert!(take_elements().is_none());
+    }
+}
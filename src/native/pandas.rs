@@ -0,0 +1,28 @@
+//! Missing values in data from pandas, which is a third-party package and so is only
+//! looked up once it has been imported
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::import_loaded;
+
+static NAT: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+static NA: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// Whether `obj` is how pandas marks a missing value: `pandas.NaT` for datetimes, or
+/// `pandas.NA` for nullable dtypes
+///
+/// pandas also marks missing floats as NaN, but a NaN is an ordinary float elsewhere, so
+/// that is left to the `nan_as_none` option of the deserializer.
+pub(crate) fn is_missing(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+    if let Some(nat) = import_loaded(&NAT, obj.py(), "pandas", "NaT")? {
+        if obj.is(nat) {
+            return Ok(true);
+        }
+    }
+    match import_loaded(&NA, obj.py(), "pandas", "NA")? {
+        Some(na) => Ok(obj.is(na)),
+        None => Ok(false),
+    }
+}
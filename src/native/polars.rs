@@ -0,0 +1,401 @@
+//! Conversions between polars frames and `polars.DataFrame`s through the Arrow C stream
+//! interface
+//!
+//! The columns are handed over as a `PyCapsule` of an `ArrowArrayStream`, following the
+//! Arrow PyCapsule interface which recent versions of polars support, so polars on either
+//! side shares their buffers rather than copying them. The frame skips its serde payload to do so: [`Native`] impls put it in
+//! [`FRAME`] for the `Pythonizer` to take, and the `Depythonizer` puts the frame it reads
+//! there for them. Other serde formats see an Arrow IPC file instead.
+
+use std::cell::Cell;
+use std::io::Cursor;
+use std::ptr;
+
+use polars::export::arrow::array::StructArray;
+use polars::export::arrow::datatypes::{ArrowDataType, Field};
+use polars::export::arrow::ffi::{export_iterator, ArrowArrayStream, ArrowArrayStreamReader};
+use polars::prelude::{
+    CompatLevel, DataFrame, IpcReader, IpcWriter, PolarsResult, SerReader, SerWriter, Series,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{IntoPyDict, PyAnyMethods, PyCFunction, PyCapsule};
+use pyo3::{ffi, Bound, IntoPy, PyAny, PyErr, PyObject, PyResult, Python};
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::{Error as _, Serialize, Serializer};
+
+use super::{
+    deserialize_tagged, import, import_loaded, serialize_tagged, Bytes, Native, DATAFRAME_TOKEN,
+    SERIES_TOKEN,
+};
+
+static NAMESPACE: GILOnceCell<PyObject> = GILOnceCell::new();
+static POLARS_DATAFRAME: GILOnceCell<PyObject> = GILOnceCell::new();
+static LOADED_DATAFRAME: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+static LOADED_SERIES: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// The capsule name of an `ArrowArrayStream` in the Arrow PyCapsule interface
+const ARROW_ARRAY_STREAM: &[u8] = b"arrow_array_stream\0";
+
+thread_local! {
+    /// The frame being serialized or deserialized by the [`Native`] impls, which is
+    /// handed between them and the `Pythonizer` or `Depythonizer` rather than converted
+    /// through its serde payload
+    static FRAME: Cell<Option<DataFrame>> = const { Cell::new(None) };
+}
+
+/// Clears [`FRAME`] when dropped, so that a frame which was not taken doesn't outlive
+/// the call which put it there, even if that call panics
+struct ClearFrame;
+
+impl Drop for ClearFrame {
+    fn drop(&mut self) {
+        FRAME.with(|cell| cell.set(None));
+    }
+}
+
+/// The payload of a tagged frame, which is an Arrow IPC file in memory unless the frame
+/// is handed over through [`FRAME`]
+struct Frame(DataFrame);
+
+impl Serialize for Frame {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut data = Vec::new();
+        IpcWriter::new(&mut data)
+            .finish(&mut self.0.clone())
+            .map_err(S::Error::custom)?;
+        Bytes(data).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Frame {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if let Some(frame) = FRAME.with(Cell::take) {
+            return Ok(Frame(frame));
+        }
+        let Bytes(data) = Bytes::deserialize(deserializer)?;
+        IpcReader::new(Cursor::new(data))
+            .finish()
+            .map(Frame)
+            .map_err(D::Error::custom)
+    }
+}
+
+fn serialize_frame<S>(
+    frame: DataFrame,
+    serializer: S,
+    token: &'static str,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    FRAME.with(|cell| cell.set(Some(frame.clone())));
+    let _clear = ClearFrame;
+    serialize_tagged(serializer, token, &Frame(frame))
+}
+
+fn deserialize_frame<'de, D>(deserializer: D, token: &'static str) -> Result<DataFrame, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Frame(frame) = deserialize_tagged(deserializer, token)?;
+    Ok(frame)
+}
+
+/// Shared with polars through the Arrow C stream interface, which preserves the schema
+impl Native for DataFrame {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_frame(self.clone(), serializer, DATAFRAME_TOKEN)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_frame(deserializer, DATAFRAME_TOKEN)
+    }
+}
+
+/// Converted as a single-column [`DataFrame`]
+impl Native for Series {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_frame(self.clone().into_frame(), serializer, SERIES_TOKEN)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let frame = deserialize_frame(deserializer, SERIES_TOKEN)?;
+        match <[Series; 1]>::try_from(frame.take_columns()) {
+            Ok([series]) => Ok(series),
+            Err(columns) => Err(D::Error::invalid_length(
+                columns.len(),
+                &"a frame with a single column",
+            )),
+        }
+    }
+}
+
+fn to_py_err(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Export the chunks of `frame` as a stream of struct arrays, one field per column
+fn export_frame(frame: &DataFrame) -> PolarsResult<ArrowArrayStream> {
+    let mut frame = frame.clone();
+    frame.align_chunks();
+    let compat_level = CompatLevel::newest();
+    let data_type = ArrowDataType::Struct(frame.schema().to_arrow(compat_level).fields);
+    let chunks = frame
+        .iter_chunks(compat_level, false)
+        .map(|batch| {
+            StructArray::try_new(data_type.clone(), batch.into_arrays(), None)
+                .map(|array| array.boxed())
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    Ok(export_iterator(
+        Box::new(chunks.into_iter().map(Ok)),
+        Field::new("", data_type, false),
+    ))
+}
+
+/// Import a stream of struct arrays, such as [`export_frame`] exports, as a frame
+fn import_frame(stream: ArrowArrayStream) -> PolarsResult<DataFrame> {
+    // SAFETY: the stream comes from a capsule of the Arrow PyCapsule interface, whose
+    // producer promises that it follows the Arrow C stream interface
+    let mut reader = unsafe { ArrowArrayStreamReader::try_new(Box::new(stream))? };
+    let data_type = reader.field().data_type().clone();
+    let mut frame: Option<DataFrame> = None;
+    while let Some(array) = unsafe { reader.next() } {
+        let chunk = match array?.as_any().downcast_ref::<StructArray>() {
+            Some(array) => DataFrame::try_from(array.clone())?,
+            None => polars::prelude::polars_bail!(
+                ComputeError: "expected a stream of struct arrays, got {:?}", data_type
+            ),
+        };
+        match &mut frame {
+            Some(frame) => {
+                frame.vstack_mut(&chunk)?;
+            }
+            None => frame = Some(chunk),
+        }
+    }
+    match frame {
+        Some(frame) => Ok(frame),
+        None => DataFrame::try_from(StructArray::new_empty(data_type)),
+    }
+}
+
+unsafe extern "C" fn destroy_capsule(capsule: *mut ffi::PyObject) {
+    // a consumer moves the stream out, leaving a released one behind
+    let stream = ffi::PyCapsule_GetPointer(capsule, ARROW_ARRAY_STREAM.as_ptr().cast());
+    drop(Box::from_raw(stream.cast::<ArrowArrayStream>()));
+}
+
+/// Wrap a stream of the chunks of `frame` in a capsule which owns it until it is consumed
+fn stream_capsule<'py>(py: Python<'py>, frame: &DataFrame) -> PyResult<Bound<'py, PyAny>> {
+    let stream = Box::into_raw(Box::new(export_frame(frame).map_err(to_py_err)?));
+    unsafe {
+        let capsule = ffi::PyCapsule_New(
+            stream.cast(),
+            ARROW_ARRAY_STREAM.as_ptr().cast(),
+            Some(destroy_capsule),
+        );
+        if capsule.is_null() {
+            drop(Box::from_raw(stream));
+        }
+        Bound::from_owned_ptr_or_err(py, capsule)
+    }
+}
+
+/// Take the stream out of a capsule of the Arrow PyCapsule interface, and import it
+fn frame_from_capsule(capsule: &Bound<'_, PyAny>) -> PyResult<DataFrame> {
+    let capsule = capsule.downcast::<PyCapsule>()?;
+    unsafe {
+        let stream =
+            ffi::PyCapsule_GetPointer(capsule.as_ptr(), ARROW_ARRAY_STREAM.as_ptr().cast())
+                .cast::<ArrowArrayStream>();
+        if stream.is_null() {
+            return Err(PyErr::fetch(capsule.py()));
+        }
+        // the capsule is left with a released stream, which it doesn't release again
+        import_frame(ptr::replace(stream, ArrowArrayStream::empty())).map_err(to_py_err)
+    }
+}
+
+/// An object exporting `frame` through `__arrow_c_stream__`, which polars accepts
+fn stream_object<'py>(py: Python<'py>, frame: DataFrame) -> PyResult<Bound<'py, PyAny>> {
+    let export = PyCFunction::new_closure_bound(py, None, None, move |args, _kwargs| {
+        stream_capsule(args.py(), &frame).map(|capsule| capsule.into_py(args.py()))
+    })?;
+    let kwargs = [("__arrow_c_stream__", export)].into_py_dict_bound(py);
+    import(&NAMESPACE, py, "types", "SimpleNamespace")?.call((), Some(&kwargs))
+}
+
+/// Take the frame which a [`Native`] impl is serializing under `token`, and convert it
+/// into a `polars.DataFrame` or `polars.Series`
+pub(crate) fn take_frame<'py>(
+    py: Python<'py>,
+    token: &'static str,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let frame = match FRAME.with(Cell::take) {
+        Some(frame) => frame,
+        None => return Ok(None),
+    };
+    let stream = stream_object(py, frame)?;
+    let frame = import(&POLARS_DATAFRAME, py, "polars", "DataFrame")?.call1((stream,))?;
+    match token {
+        SERIES_TOKEN => frame.call_method0("to_series").map(Some),
+        _ => Ok(Some(frame)),
+    }
+}
+
+/// Read `obj` as a frame if it is a `polars.DataFrame`, or a `polars.Series` for
+/// `SERIES_TOKEN`
+pub(crate) fn frame_from_python(
+    obj: &Bound<'_, PyAny>,
+    token: &'static str,
+) -> PyResult<Option<DataFrame>> {
+    let py = obj.py();
+    let frame = match token {
+        SERIES_TOKEN => match import_loaded(&LOADED_SERIES, py, "polars", "Series")? {
+            Some(series) if obj.is_instance(series)? => obj.call_method0("to_frame")?,
+            _ => return Ok(None),
+        },
+        _ => match import_loaded(&LOADED_DATAFRAME, py, "polars", "DataFrame")? {
+            Some(dataframe) if obj.is_instance(dataframe)? => obj.clone(),
+            _ => return Ok(None),
+        },
+    };
+    frame_from_capsule(&frame.call_method0("__arrow_c_stream__")?).map(Some)
+}
+
+/// Hand `frame` to the [`Native`] impl which deserializes it in `f`
+pub(crate) fn with_frame<T>(frame: DataFrame, f: impl FnOnce() -> T) -> T {
+    FRAME.with(|cell| cell.set(Some(frame)));
+    let _clear = ClearFrame;
+    f()
+}
+
+#[cfg(test)]
+mod test {
+    use polars::prelude::{df, DataFrame, NamedFrom, Series};
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::{export_frame, frame_from_capsule, import_frame, stream_object};
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Report {
+        #[serde(with = "crate::native")]
+        frame: DataFrame,
+        #[serde(with = "crate::native")]
+        totals: Series,
+    }
+
+    fn report() -> Report {
+        Report {
+            frame: df! {
+                "city" => ["Oslo", "Lima"],
+                "population" => [709_000i64, 10_000_000],
+                "coastal" => [Some(true), None],
+            }
+            .unwrap(),
+            totals: Series::new("totals", [1.5f64, 2.5]),
+        }
+    }
+
+    fn assert_report_eq(actual: &Report, expected: &Report) {
+        assert!(
+            actual.frame.equals_missing(&expected.frame),
+            "{}",
+            actual.frame
+        );
+        assert!(actual.totals.equals(&expected.totals), "{}", actual.totals);
+        assert_eq!(actual.totals.name(), expected.totals.name());
+    }
+
+    #[test]
+    fn test_polars_payload() {
+        // other formats see an Arrow IPC file
+        let json = serde_json::to_value(report()).unwrap();
+        assert!(json["frame"].is_array());
+        let actual: Report = serde_json::from_value(json).unwrap();
+        assert_report_eq(&actual, &report());
+
+        // a series must be a frame with a single column
+        let mut json = serde_json::to_value(report()).unwrap();
+        json["totals"] = json["frame"].clone();
+        let err = serde_json::from_value::<Report>(json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid length 3, expected a frame with a single column"
+        );
+    }
+
+    #[test]
+    fn test_arrow_stream() {
+        let mut frame = report().frame;
+        frame.vstack_mut(&report().frame).unwrap();
+        assert_eq!(frame.n_chunks(), 2);
+        let actual = import_frame(export_frame(&frame).unwrap()).unwrap();
+        assert!(actual.equals_missing(&frame), "{}", actual);
+        assert_eq!(actual.n_chunks(), 2);
+
+        let empty = frame.clear();
+        let actual = import_frame(export_frame(&empty).unwrap()).unwrap();
+        assert_eq!(actual.schema(), empty.schema());
+        assert_eq!(actual.height(), 0);
+    }
+
+    #[test]
+    fn test_arrow_stream_capsule() {
+        Python::with_gil(|py| {
+            let frame = report().frame;
+            let stream = stream_object(py, frame.clone()).unwrap();
+            // each call exports a new stream, which is consumed by reading it
+            for _ in 0..2 {
+                let capsule = stream.call_method0("__arrow_c_stream__").unwrap();
+                let actual = frame_from_capsule(&capsule).unwrap();
+                assert!(actual.equals_missing(&frame), "{}", actual);
+                assert!(frame_from_capsule(&capsule).is_err());
+            }
+            // an unconsumed stream is released with its capsule
+            drop(stream.call_method0("__arrow_c_stream__").unwrap());
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the polars Python package"]
+    fn test_polars_roundtrip() {
+        Python::with_gil(|py| {
+            let obj = pythonize(py, &report()).unwrap();
+            let frame = obj.get_item("frame").unwrap();
+            assert_eq!(frame.getattr("shape").unwrap().to_string(), "(2, 3)");
+            assert_eq!(
+                frame.getattr("columns").unwrap().to_string(),
+                "['city', 'population', 'coastal']"
+            );
+            let totals = obj.get_item("totals").unwrap();
+            assert_eq!(totals.getattr("name").unwrap().to_string(), "totals");
+            assert_eq!(totals.call_method0("sum").unwrap().to_string(), "4.0");
+
+            let actual: Report = depythonize(&obj).unwrap();
+            assert_report_eq(&actual, &report());
+        })
+    }
+}
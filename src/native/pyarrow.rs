@@ -0,0 +1,27 @@
+//! Rows of `pyarrow` tables, which is a third-party package and so is only looked up
+//! once it has been imported
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::import_loaded;
+
+static TABLE: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+static RECORD_BATCH: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// The rows of a `pyarrow.Table` or `pyarrow.RecordBatch` as a list of dicts, or `None`
+/// if `obj` is neither
+pub(crate) fn table_rows<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    for (cell, name) in [(&TABLE, "Table"), (&RECORD_BATCH, "RecordBatch")] {
+        match import_loaded(cell, py, "pyarrow", name)? {
+            Some(class) if obj.is_instance(class)? => {
+                return obj.call_method0("to_pylist").map(Some)
+            }
+            Some(_) => {}
+            None => return Ok(None),
+        }
+    }
+    Ok(None)
+}
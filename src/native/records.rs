@@ -0,0 +1,480 @@
+//! The fields of Python objects which are records of named attributes rather than
+//! mappings, such as dataclass, `attrs`, pydantic and `msgspec.Struct` instances, read
+//! into dicts to be deserialized as structs
+//!
+//! Named tuples are also records where a struct or a map is expected, but sequences
+//! elsewhere. Other objects are only read as records of their attributes when the
+//! `object_attributes` option of the `Depythonizer` is set.
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{
+    PyAnyMethods, PyDict, PyDictMethods, PyString, PyStringMethods, PyTuple, PyType,
+};
+use pyo3::{intern, Bound, PyAny, PyObject, PyResult};
+
+use super::{import, import_loaded};
+
+static FIELDS: GILOnceCell<PyObject> = GILOnceCell::new();
+static UNSET: GILOnceCell<Option<PyObject>> = GILOnceCell::new();
+
+/// The fields of `obj` by name, in the order they are declared, if it is a record
+pub(crate) fn record_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    // classes themselves have the attributes which mark their instances as records
+    if obj.is_instance_of::<PyType>() {
+        return Ok(None);
+    }
+    if let Some(fields) = dataclass_fields(obj)? {
+        return Ok(Some(fields));
+    }
+    if let Some(fields) = attrs_fields(obj)? {
+        return Ok(Some(fields));
+    }
+    if let Some(fields) = pydantic_fields(obj)? {
+        return Ok(Some(fields));
+    }
+    if let Some(fields) = msgspec_fields(obj)? {
+        return Ok(Some(fields));
+    }
+    named_tuple_fields(obj)
+}
+
+/// The fields given by `dataclasses.fields`, which leaves out class variables and
+/// `InitVar`s
+fn dataclass_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    if !obj
+        .get_type()
+        .hasattr(intern!(py, "__dataclass_fields__"))?
+    {
+        return Ok(None);
+    }
+    let dict = PyDict::new_bound(py);
+    for field in import(&FIELDS, py, "dataclasses", "fields")?
+        .call1((obj,))?
+        .iter()?
+    {
+        let name = field?.getattr(intern!(py, "name"))?;
+        let value = obj.getattr(name.downcast::<PyString>()?)?;
+        dict.set_item(name, value)?;
+    }
+    Ok(Some(dict))
+}
+
+/// The attributes of an `attrs` class, given by its `__attrs_attrs__`, so `attrs` itself
+/// needn't be imported
+fn attrs_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    let attributes = match obj.get_type().getattr(intern!(py, "__attrs_attrs__")) {
+        Ok(attributes) => attributes,
+        Err(_) => return Ok(None),
+    };
+    let dict = PyDict::new_bound(py);
+    for attribute in attributes.iter()? {
+        let name = attribute?.getattr(intern!(py, "name"))?;
+        let value = obj.getattr(name.downcast::<PyString>()?)?;
+        dict.set_item(name, value)?;
+    }
+    Ok(Some(dict))
+}
+
+/// The fields of a pydantic model, read directly rather than through `model_dump`,
+/// which would convert nested models and values on the Python side
+fn pydantic_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    let class = obj.get_type();
+    // `__fields__` is deprecated in pydantic 2, but is all that pydantic 1 has
+    let fields = match class.getattr(intern!(py, "model_fields")) {
+        Ok(fields) => fields,
+        Err(_) => match class.getattr(intern!(py, "__fields__")) {
+            Ok(fields) => fields,
+            Err(_) => return Ok(None),
+        },
+    };
+    let fields = match fields.downcast_into::<PyDict>() {
+        Ok(fields) => fields,
+        Err(_) => return Ok(None),
+    };
+    let dict = PyDict::new_bound(py);
+    for name in fields.keys() {
+        let value = obj.getattr(name.downcast::<PyString>()?)?;
+        dict.set_item(name, value)?;
+    }
+    Ok(Some(dict))
+}
+
+/// The fields of a `msgspec.Struct` by their attribute names in `__struct_fields__`,
+/// leaving out those which are `msgspec.UNSET` as if they were missing
+fn msgspec_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    let names = match obj.get_type().getattr(intern!(py, "__struct_fields__")) {
+        Ok(names) => names,
+        Err(_) => return Ok(None),
+    };
+    let unset = import_loaded(&UNSET, py, "msgspec", "UNSET")?;
+    let dict = PyDict::new_bound(py);
+    for name in names.iter()? {
+        let name = name?;
+        let value = obj.getattr(name.downcast::<PyString>()?)?;
+        if matches!(unset, Some(unset) if value.is(unset)) {
+            continue;
+        }
+        dict.set_item(name, value)?;
+    }
+    Ok(Some(dict))
+}
+
+/// The items of a `typing.NamedTuple` or `collections.namedtuple` by the names in its
+/// `_fields`, for when a struct rather than a sequence is expected
+fn named_tuple_fields<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    if !obj.is_instance_of::<PyTuple>() {
+        return Ok(None);
+    }
+    let names = match obj.get_type().getattr(intern!(py, "_fields")) {
+        Ok(names) => names,
+        Err(_) => return Ok(None),
+    };
+    let dict = PyDict::new_bound(py);
+    for (name, value) in names.iter()?.zip(obj.iter()?) {
+        dict.set_item(name?, value?)?;
+    }
+    Ok(Some(dict))
+}
+
+/// The attributes of any other object, from its `__dict__` and the `__slots__` of its
+/// classes, or `None` if it has neither
+pub(crate) fn attribute_fields<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let py = obj.py();
+    if obj.is_instance_of::<PyType>() {
+        return Ok(None);
+    }
+    let mut found = false;
+    let dict = PyDict::new_bound(py);
+    // base classes first, so that their slots come first as their fields would
+    let mro = obj.get_type().getattr(intern!(py, "__mro__"))?;
+    for class in mro.iter()?.collect::<PyResult<Vec<_>>>()?.iter().rev() {
+        let slots = match class
+            .getattr(intern!(py, "__dict__"))?
+            .get_item(intern!(py, "__slots__"))
+        {
+            Ok(slots) => slots,
+            Err(_) => continue,
+        };
+        found = true;
+        // a single slot may be given as a plain string
+        let names = match slots.downcast::<PyString>() {
+            Ok(name) => vec![name.clone()],
+            Err(_) => slots
+                .iter()?
+                .map(|name| Ok(name?.downcast_into::<PyString>()?))
+                .collect::<PyResult<Vec<_>>>()?,
+        };
+        for name in names {
+            let name = name.to_cow()?;
+            if name == "__dict__" || name == "__weakref__" {
+                continue;
+            }
+            // slots which haven't been assigned are left out
+            if let Ok(value) = obj.getattr(&*name) {
+                dict.set_item(&*name, value)?;
+            }
+        }
+    }
+    if let Ok(attributes) = obj.getattr(intern!(py, "__dict__")) {
+        if let Ok(attributes) = attributes.downcast::<PyDict>() {
+            found = true;
+            dict.update(attributes.as_mapping())?;
+        }
+    }
+    Ok(found.then_some(dict))
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use crate::depythonize;
+
+    /// The keys of a map, in the order they are visited
+    struct Keys(Vec<String>);
+
+    impl<'de> Deserialize<'de> for Keys {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct KeysVisitor;
+
+            impl<'de> Visitor<'de> for KeysVisitor {
+                type Value = Keys;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a map")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Keys, A::Error> {
+                    let mut keys = Vec::new();
+                    while let Some((key, IgnoredAny)) = map.next_entry()? {
+                        keys.push(key);
+                    }
+                    Ok(Keys(keys))
+                }
+            }
+
+            deserializer.deserialize_map(KeysVisitor)
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+        label: Option<String>,
+    }
+
+    fn eval<'py>(py: Python<'py>, code: &str) -> Bound<'py, PyAny> {
+        let locals = PyDict::new_bound(py);
+        py.run_bound(code, Some(&locals), None).unwrap();
+        locals.get_item("obj").unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_dataclass() {
+        Python::with_gil(|py| {
+            let obj = eval(
+                py,
+                "import dataclasses, typing\n\
+                 @dataclasses.dataclass\n\
+                 class Point:\n    \
+                     x: int\n    \
+                     y: int\n    \
+                     label: typing.Optional[str] = None\n    \
+                     scale: typing.ClassVar[int] = 10\n\
+                 obj = [Point(1, 2), Point(3, 4, 'b')]",
+            );
+            let points: Vec<Point> = depythonize(&obj).unwrap();
+            assert_eq!(
+                points,
+                [
+                    Point {
+                        x: 1,
+                        y: 2,
+                        label: None
+                    },
+                    Point {
+                        x: 3,
+                        y: 4,
+                        label: Some("b".to_string())
+                    }
+                ]
+            );
+
+            // fields are in the order they are declared, without class variables
+            let any: serde_json::Value = depythonize(&obj).unwrap();
+            assert_eq!(any[1], json!({ "x": 3, "y": 4, "label": "b" }));
+            let Keys(keys) = depythonize(&obj.get_item(0).unwrap()).unwrap();
+            assert_eq!(keys, ["x", "y", "label"]);
+
+            // the class is not a record
+            let class = obj.get_item(0).unwrap().get_type();
+            assert!(depythonize::<Point>(&class).is_err());
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the attrs Python package"]
+    fn test_attrs() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Segment {
+            start: Point,
+            end: Point,
+        }
+
+        Python::with_gil(|py| {
+            let obj = eval(
+                py,
+                "import attrs\n\
+                 @attrs.define\n\
+                 class Point:\n    \
+                     x: int\n    \
+                     y: int = attrs.field(default=0)\n    \
+                     label: str | None = None\n\
+                 @attrs.frozen\n\
+                 class Segment:\n    \
+                     start: Point\n    \
+                     end: Point\n\
+                 obj = Segment(Point(1), Point(3, 4, 'b'))",
+            );
+            let Keys(keys) = depythonize(&obj.getattr("end").unwrap()).unwrap();
+            assert_eq!(keys, ["x", "y", "label"]);
+            let segment: Segment = depythonize(&obj).unwrap();
+            assert_eq!(
+                segment,
+                Segment {
+                    start: Point {
+                        x: 1,
+                        y: 0,
+                        label: None
+                    },
+                    end: Point {
+                        x: 3,
+                        y: 4,
+                        label: Some("b".to_string())
+                    }
+                }
+            );
+            let any: serde_json::Value = depythonize(&obj).unwrap();
+            assert_eq!(
+                any,
+                json!({
+                    "start": { "x": 1, "y": 0, "label": null },
+                    "end": { "x": 3, "y": 4, "label": "b" },
+                })
+            );
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the pydantic Python package"]
+    fn test_pydantic() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Order {
+            id: u32,
+            origin: Point,
+            tags: Vec<String>,
+        }
+
+        Python::with_gil(|py| {
+            // pydantic 2 still has the API of pydantic 1 in `pydantic.v1`
+            for module in ["pydantic", "pydantic.v1"] {
+                let obj = eval(
+                    py,
+                    &format!(
+                        "import typing\n\
+                         from {} import BaseModel, Field\n\
+                         class Point(BaseModel):\n    \
+                             x: int\n    \
+                             y: int = 0\n    \
+                             label: typing.Optional[str] = None\n\
+                         class Order(BaseModel):\n    \
+                             id: int = Field(alias='orderId')\n    \
+                             origin: Point\n    \
+                             tags: typing.List[str] = []\n\
+                         obj = Order(orderId=7, origin=Point(x=1, label='a'), tags=['new'])",
+                        module
+                    ),
+                );
+                let order: Order = depythonize(&obj).unwrap();
+                assert_eq!(
+                    order,
+                    Order {
+                        id: 7,
+                        origin: Point {
+                            x: 1,
+                            y: 0,
+                            label: Some("a".to_string())
+                        },
+                        tags: vec!["new".to_string()],
+                    },
+                    "{}",
+                    module
+                );
+                let Keys(keys) = depythonize(&obj).unwrap();
+                assert_eq!(keys, ["id", "origin", "tags"], "{}", module);
+            }
+        })
+    }
+
+    #[test]
+    #[ignore = "needs the msgspec Python package"]
+    fn test_msgspec() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            kind: String,
+            at: Point,
+            #[serde(default)]
+            note: Option<String>,
+        }
+
+        Python::with_gil(|py| {
+            let obj = eval(
+                py,
+                "import typing, msgspec\n\
+                 class Point(msgspec.Struct, rename='camel'):\n    \
+                     x: int\n    \
+                     y: int = 0\n    \
+                     label: typing.Optional[str] = None\n\
+                 class Event(msgspec.Struct, array_like=True):\n    \
+                     kind: str\n    \
+                     at: Point\n    \
+                     note: typing.Union[str, msgspec.UnsetType] = msgspec.UNSET\n\
+                 obj = Event('click', Point(1, label='a'))",
+            );
+            let event: Event = depythonize(&obj).unwrap();
+            assert_eq!(
+                event,
+                Event {
+                    kind: "click".to_string(),
+                    at: Point {
+                        x: 1,
+                        y: 0,
+                        label: Some("a".to_string())
+                    },
+                    note: None,
+                }
+            );
+            // unset fields are left out
+            let Keys(keys) = depythonize(&obj).unwrap();
+            assert_eq!(keys, ["kind", "at"]);
+            let Keys(keys) = depythonize(&obj.getattr("at").unwrap()).unwrap();
+            assert_eq!(keys, ["x", "y", "label"]);
+        })
+    }
+
+    #[test]
+    fn test_named_tuple() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pair(i32, i32, Option<String>);
+
+        Python::with_gil(|py| {
+            let obj = eval(
+                py,
+                "import collections, typing\n\
+                 class Point(typing.NamedTuple):\n    \
+                     x: int\n    \
+                     y: int\n    \
+                     label: typing.Optional[str] = None\n\
+                 Legacy = collections.namedtuple('Legacy', ['x', 'y', 'label'])\n\
+                 obj = [Point(1, 2), Legacy(3, 4, 'b')]",
+            );
+            // by name where a struct is expected
+            let points: Vec<Point> = depythonize(&obj).unwrap();
+            assert_eq!(
+                points,
+                [
+                    Point {
+                        x: 1,
+                        y: 2,
+                        label: None
+                    },
+                    Point {
+                        x: 3,
+                        y: 4,
+                        label: Some("b".to_string())
+                    }
+                ]
+            );
+            let Keys(keys) = depythonize(&obj.get_item(1).unwrap()).unwrap();
+            assert_eq!(keys, ["x", "y", "label"]);
+
+            // by position where a sequence is expected, or any type
+            let pairs: Vec<Pair> = depythonize(&obj).unwrap();
+            assert_eq!(pairs[1], Pair(3, 4, Some("b".to_string())));
+            let any: serde_json::Value = depythonize(&obj).unwrap();
+            assert_eq!(any, json!([[1, 2, null], [3, 4, "b"]]));
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use rust_decimal::Decimal;
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, NumericText, DECIMAL_TOKEN};
+
+/// Keeps the scale, so `Decimal::new(150, 2)` becomes `Decimal('1.50')`
+impl Native for Decimal {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DECIMAL_TOKEN, &self.to_string())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let NumericText(text) = deserialize_tagged(deserializer, DECIMAL_TOKEN)?;
+        // Python switches to scientific notation for large exponents, e.g. `1E+3`
+        let decimal = if text.contains(['e', 'E']) {
+            Decimal::from_scientific(&text)
+        } else {
+            Decimal::from_str_exact(&text)
+        };
+        decimal.map_err(|err| D::Error::custom(format_args!("invalid decimal {:?}: {}", text, err)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Price {
+        #[serde(with = "crate::native")]
+        amount: Decimal,
+    }
+
+    #[test]
+    fn test_decimal_roundtrip() {
+        Python::with_gil(|py| {
+            for (amount, repr) in [
+                (Decimal::new(150, 2), "Decimal('1.50')"),
+                (Decimal::new(-1, 28), "Decimal('-1E-28')"),
+                (Decimal::MAX, "Decimal('79228162514264337593543950335')"),
+            ] {
+                let price = Price { amount };
+                let obj = pythonize(py, &price).unwrap();
+                let amount = obj.get_item("amount").unwrap();
+                assert_eq!(amount.repr().unwrap().to_cow().unwrap(), repr);
+
+                let actual: Price = depythonize(&obj).unwrap();
+                assert_eq!(actual, price);
+                assert_eq!(actual.amount.scale(), price.amount.scale());
+            }
+        })
+    }
+
+    #[test]
+    fn test_decimal_from_python() {
+        Python::with_gil(|py| {
+            let depythonize_amount = |expr: &str| {
+                let decimal = py.import_bound("decimal").unwrap();
+                let obj = py
+                    .eval_bound(
+                        &format!("{{'amount': {}}}", expr),
+                        Some(&decimal.dict()),
+                        None,
+                    )
+                    .unwrap();
+                depythonize::<Price>(&obj).map(|price| price.amount)
+            };
+
+            assert_eq!(
+                depythonize_amount("Decimal('1E+3')").unwrap(),
+                Decimal::new(1000, 0)
+            );
+            assert_eq!(
+                depythonize_amount("Decimal('1E-7')").unwrap(),
+                Decimal::new(1, 7)
+            );
+            assert_eq!(depythonize_amount("'2.50'").unwrap(), Decimal::new(250, 2));
+            assert_eq!(depythonize_amount("42").unwrap(), Decimal::new(42, 0));
+            assert_eq!(depythonize_amount("0.5").unwrap(), Decimal::new(5, 1));
+
+            let err = depythonize_amount("Decimal('NaN')").unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid decimal \"NaN\""),
+                "{}",
+                err
+            );
+        })
+    }
+}
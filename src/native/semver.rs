@@ -0,0 +1,107 @@
+use semver::Version;
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+
+use super::{deserialize_tagged, serialize_tagged, Native, VERSION_TOKEN};
+
+/// Becomes a plain string when `packaging` is not installed; versions which are not
+/// valid under PEP 440 cannot be converted to `packaging.version.Version`
+impl Native for Version {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, VERSION_TOKEN, &self.to_string())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text: String = deserialize_tagged(deserializer, VERSION_TOKEN)?;
+        Version::parse(&text)
+            .map_err(|err| D::Error::custom(format_args!("invalid version {:?}: {}", text, err)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use semver::Version;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Release {
+        #[serde(with = "crate::native")]
+        version: Version,
+    }
+
+    /// Use pip's vendored copy of `packaging` when it is not installed
+    fn install_packaging(py: Python<'_>) {
+        py.run_bound(
+            r#"
+import sys
+try:
+    import packaging.version
+except ImportError:
+    from pip._vendor import packaging
+    import pip._vendor.packaging.version
+    sys.modules["packaging"] = packaging
+    sys.modules["packaging.version"] = packaging.version
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_version_roundtrip() {
+        Python::with_gil(|py| {
+            install_packaging(py);
+            let class = py
+                .import_bound("packaging.version")
+                .unwrap()
+                .getattr("Version")
+                .unwrap();
+
+            for (text, normalized) in [
+                ("1.2.3", "1.2.3"),
+                ("1.0.0-rc.1+ubuntu.2", "1.0.0rc1+ubuntu.2"),
+            ] {
+                let release = Release {
+                    version: Version::parse(text).unwrap(),
+                };
+                let obj = pythonize(py, &release).unwrap();
+                let version = obj.get_item("version").unwrap();
+                assert!(version.is_instance(&class).unwrap());
+                assert_eq!(version.str().unwrap().to_cow().unwrap(), normalized);
+
+                let actual: Release = depythonize(&obj).unwrap();
+                assert_eq!(actual, release);
+            }
+
+            let obj = py
+                .eval_bound(
+                    "{'version': Version('2.1.dev4')}",
+                    Some(&py.import_bound("packaging.version").unwrap().dict()),
+                    None,
+                )
+                .unwrap();
+            let actual: Release = depythonize(&obj).unwrap();
+            assert_eq!(actual.version, Version::parse("2.1.0-dev.4").unwrap());
+
+            obj.set_item("version", "3.0.0-beta.2").unwrap();
+            let actual: Release = depythonize(&obj).unwrap();
+            assert_eq!(actual.version, Version::parse("3.0.0-beta.2").unwrap());
+
+            let release = Release {
+                version: Version::parse("1.0.0-x.7.z.92").unwrap(),
+            };
+            let err = pythonize(py, &release).unwrap_err();
+            assert!(err.to_string().contains("Invalid version"), "{}", err);
+        })
+    }
+}
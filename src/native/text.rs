@@ -0,0 +1,161 @@
+//! Conversions between string payloads and Python objects which round-trip through `str`
+
+use pyo3::exceptions::PyValueError;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyAnyMethods, PyString};
+use pyo3::{Bound, PyAny, PyObject, PyResult};
+
+use super::import;
+
+static UUID: GILOnceCell<PyObject> = GILOnceCell::new();
+static DECIMAL: GILOnceCell<PyObject> = GILOnceCell::new();
+static INT: GILOnceCell<PyObject> = GILOnceCell::new();
+static FRACTION: GILOnceCell<PyObject> = GILOnceCell::new();
+static PATH: GILOnceCell<PyObject> = GILOnceCell::new();
+static PATH_LIKE: GILOnceCell<PyObject> = GILOnceCell::new();
+static FSPATH: GILOnceCell<PyObject> = GILOnceCell::new();
+static URLSPLIT: GILOnceCell<PyObject> = GILOnceCell::new();
+static SPLIT_RESULT: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Call `class` with the string payload, naming the payload and class if it is rejected
+fn to_python<'py>(
+    class: &Bound<'py, PyAny>,
+    payload: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    class.call1((payload,)).map_err(|cause| {
+        let py = payload.py();
+        let err = PyValueError::new_err(format!(
+            "cannot convert {} to {}.{}",
+            payload
+                .repr()
+                .map_or_else(|_| "payload".into(), |repr| repr.to_string()),
+            class
+                .getattr("__module__")
+                .map_or_else(|_| "?".into(), |m| m.to_string()),
+            class
+                .getattr("__qualname__")
+                .map_or_else(|_| "?".into(), |n| n.to_string()),
+        ));
+        err.set_cause(py, Some(cause));
+        err
+    })
+}
+
+/// `str(obj)` if `obj` is an instance of `class`, so that other objects such as
+/// plain strings are deserialized as they would be without the tag
+fn from_python<'py>(
+    class: &Bound<'py, PyAny>,
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if obj.is_instance(class)? {
+        Ok(Some(obj.str()?.into_any()))
+    } else {
+        Ok(None)
+    }
+}
+
+pub(crate) fn uuid_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_python(import(&UUID, payload.py(), "uuid", "UUID")?, payload)
+}
+
+pub(crate) fn uuid_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    Ok(uuid_text(obj)?.map(|text| PyString::new_bound(obj.py(), &text).into_any()))
+}
+
+/// The 128-bit value of `obj` if it is a `uuid.UUID`, read from its `int` attribute
+pub(crate) fn uuid_value(obj: &Bound<'_, PyAny>) -> PyResult<Option<u128>> {
+    if !obj.is_instance(import(&UUID, obj.py(), "uuid", "UUID")?)? {
+        return Ok(None);
+    }
+    Ok(Some(obj.getattr("int")?.extract()?))
+}
+
+/// The hyphenated form of `obj` if it is a `uuid.UUID`, as `str(obj)` returns, but
+/// formatted from its 128-bit value rather than by Python
+pub(crate) fn uuid_text(obj: &Bound<'_, PyAny>) -> PyResult<Option<String>> {
+    Ok(uuid_value(obj)?.map(|value| {
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            value >> 96,
+            (value >> 80) & 0xffff,
+            (value >> 64) & 0xffff,
+            (value >> 48) & 0xffff,
+            value & 0xffff_ffff_ffff,
+        )
+    }))
+}
+
+pub(crate) fn decimal_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_python(
+        import(&DECIMAL, payload.py(), "decimal", "Decimal")?,
+        payload,
+    )
+}
+
+pub(crate) fn decimal_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    from_python(import(&DECIMAL, obj.py(), "decimal", "Decimal")?, obj)
+}
+
+pub(crate) fn int_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_python(import(&INT, payload.py(), "builtins", "int")?, payload)
+}
+
+pub(crate) fn int_from_python<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    from_python(import(&INT, obj.py(), "builtins", "int")?, obj)
+}
+
+pub(crate) fn fraction_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_python(
+        import(&FRACTION, payload.py(), "fractions", "Fraction")?,
+        payload,
+    )
+}
+
+pub(crate) fn fraction_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    from_python(import(&FRACTION, obj.py(), "fractions", "Fraction")?, obj)
+}
+
+pub(crate) fn path_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_python(import(&PATH, payload.py(), "pathlib", "Path")?, payload)
+}
+
+/// Accepts any `os.PathLike`, not just `pathlib.Path`
+pub(crate) fn path_from_python<'py>(
+    obj: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let py = obj.py();
+    if obj.is_instance(import(&PATH_LIKE, py, "os", "PathLike")?)? {
+        Ok(Some(import(&FSPATH, py, "os", "fspath")?.call1((obj,))?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// URLs are plain strings unless split
+pub(crate) fn url_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    Ok(payload.clone())
+}
+
+pub(crate) fn split_url_to_python<'py>(payload: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    import(&URLSPLIT, payload.py(), "urllib.parse", "urlsplit")?.call1((payload,))
+}
+
+/// Accepts a `urllib.parse.SplitResult` as well as a string
+pub(crate) fn url_from_python<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if obj.is_instance(import(
+        &SPLIT_RESULT,
+        obj.py(),
+        "urllib.parse",
+        "SplitResult",
+    )?)? {
+        Ok(Some(obj.call_method0("geturl")?))
+    } else {
+        Ok(None)
+    }
+}
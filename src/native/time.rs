@@ -0,0 +1,228 @@
+use serde::de::{Deserializer, Error};
+use serde::ser::{Error as _, Serializer};
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+use super::{
+    datetime, deserialize_tagged, serialize_tagged, Native, DATETIME_TOKEN, DATE_TOKEN,
+    TIMEDELTA_TOKEN, TIME_TOKEN,
+};
+
+fn date_components(date: Date) -> datetime::Date {
+    (date.year(), date.month().into(), date.day())
+}
+
+fn time_components(time: Time) -> datetime::Time {
+    (
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.microsecond(),
+    )
+}
+
+fn to_components(datetime: PrimitiveDateTime, offset: Option<i32>) -> datetime::DateTime {
+    let (year, month, day) = date_components(datetime.date());
+    let (hour, minute, second, microsecond) = time_components(datetime.time());
+    (year, month, day, hour, minute, second, microsecond, offset)
+}
+
+fn date_from_components<E: Error>((year, month, day): datetime::Date) -> Result<Date, E> {
+    let month = Month::try_from(month).map_err(E::custom)?;
+    Date::from_calendar_date(year, month, day).map_err(E::custom)
+}
+
+fn time_from_components<E: Error>(
+    (hour, minute, second, microsecond): datetime::Time,
+) -> Result<Time, E> {
+    Time::from_hms_micro(hour, minute, second, microsecond).map_err(E::custom)
+}
+
+fn from_components<E: Error>(
+    components: datetime::DateTime,
+) -> Result<(PrimitiveDateTime, Option<i32>), E> {
+    let (year, month, day, hour, minute, second, microsecond, offset) = components;
+    let date = date_from_components((year, month, day))?;
+    let time = time_from_components((hour, minute, second, microsecond))?;
+    Ok((PrimitiveDateTime::new(date, time), offset))
+}
+
+impl Native for OffsetDateTime {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let local = PrimitiveDateTime::new(self.date(), self.time());
+        let components = to_components(local, Some(self.offset().whole_seconds()));
+        serialize_tagged(serializer, DATETIME_TOKEN, &components)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match from_components(deserialize_tagged(deserializer, DATETIME_TOKEN)?)? {
+            (local, Some(offset)) => {
+                let offset = UtcOffset::from_whole_seconds(offset).map_err(D::Error::custom)?;
+                Ok(local.assume_offset(offset))
+            }
+            (_, None) => Err(D::Error::custom("expected a timezone-aware datetime")),
+        }
+    }
+}
+
+impl Native for PrimitiveDateTime {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DATETIME_TOKEN, &to_components(*self, None))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match from_components(deserialize_tagged(deserializer, DATETIME_TOKEN)?)? {
+            (local, None) => Ok(local),
+            (_, Some(_)) => Err(D::Error::custom("expected a naive datetime")),
+        }
+    }
+}
+
+impl Native for Date {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, DATE_TOKEN, &date_components(*self))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        date_from_components(deserialize_tagged(deserializer, DATE_TOKEN)?)
+    }
+}
+
+impl Native for Time {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, TIME_TOKEN, &time_components(*self))
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        time_from_components(deserialize_tagged(deserializer, TIME_TOKEN)?)
+    }
+}
+
+/// Durations with a fraction of a microsecond, the resolution of `datetime.timedelta`, are
+/// rejected rather than truncated
+impl Native for Duration {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.subsec_nanoseconds() % 1_000 != 0 {
+            return Err(S::Error::custom(format!(
+                "duration of {} is not a whole number of microseconds",
+                self
+            )));
+        }
+        let timedelta = datetime::timedelta_from_micros(self.whole_microseconds());
+        serialize_tagged(serializer, TIMEDELTA_TOKEN, &timedelta)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let micros =
+            datetime::timedelta_to_micros(deserialize_tagged(deserializer, TIMEDELTA_TOKEN)?);
+        let micros = i64::try_from(micros).map_err(D::Error::custom)?;
+        Ok(Duration::microseconds(micros))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Times {
+        #[serde(with = "crate::native")]
+        offset: OffsetDateTime,
+        #[serde(with = "crate::native")]
+        primitive: PrimitiveDateTime,
+        #[serde(with = "crate::native")]
+        date: Date,
+        #[serde(with = "crate::native")]
+        time: Time,
+        #[serde(with = "crate::native")]
+        duration: Duration,
+    }
+
+    #[test]
+    fn test_time_roundtrip() {
+        Python::with_gil(|py| {
+            let date = Date::from_calendar_date(2021, Month::March, 14).unwrap();
+            let time = Time::from_hms_micro(15, 9, 26, 535_897).unwrap();
+            let primitive = PrimitiveDateTime::new(date, time);
+            let times = Times {
+                offset: primitive.assume_offset(UtcOffset::from_hms(-3, -30, 0).unwrap()),
+                primitive,
+                date,
+                time,
+                duration: Duration::new(-90_061, -500_000_000),
+            };
+            let obj = pythonize(py, &times).unwrap();
+
+            let repr = |key: &str| -> String {
+                obj.get_item(key)
+                    .unwrap()
+                    .str()
+                    .unwrap()
+                    .to_cow()
+                    .unwrap()
+                    .into_owned()
+            };
+            assert_eq!(repr("offset"), "2021-03-14 15:09:26.535897-03:30");
+            assert_eq!(repr("primitive"), "2021-03-14 15:09:26.535897");
+            assert_eq!(repr("date"), "2021-03-14");
+            assert_eq!(repr("time"), "15:09:26.535897");
+            assert_eq!(repr("duration"), "-2 days, 22:58:58.500000");
+
+            let actual: Times = depythonize(&obj).unwrap();
+            assert_eq!(actual, times);
+        })
+    }
+
+    #[test]
+    fn test_sub_microsecond_duration_rejected() {
+        #[derive(Serialize)]
+        struct Timeout {
+            #[serde(with = "crate::native")]
+            duration: Duration,
+        }
+
+        Python::with_gil(|py| {
+            let timeout = Timeout {
+                duration: Duration::new(5, 123_456_789),
+            };
+            let err = pythonize(py, &timeout).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "duration of 5s123ms456µs789ns is not a whole number of microseconds"
+            );
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use url::Url;
+
+use super::{deserialize_tagged, serialize_tagged, Native, URL_TOKEN};
+
+fn parse<E: Error>(text: &str) -> Result<Url, E> {
+    Url::parse(text).map_err(|err| E::custom(format_args!("invalid URL {:?}: {}", text, err)))
+}
+
+/// Accepts a `urllib.parse.SplitResult` as well as a string
+impl Native for Url {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, URL_TOKEN, self.as_str())
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text: String = deserialize_tagged(deserializer, URL_TOKEN)?;
+        parse(&text)
+    }
+}
+
+/// Serde adapter converting [`Url`] to and from `urllib.parse.SplitResult`, for use
+/// with `#[serde(with = "pythonize::native::split_url")]`
+///
+/// Strings are also accepted when deserializing, and other serde formats see the
+/// URL as a string.
+pub mod split_url {
+    use serde::de::Deserializer;
+    use serde::ser::Serializer;
+    use url::Url;
+
+    use super::super::{deserialize_tagged, serialize_tagged, SPLIT_URL_TOKEN};
+
+    /// Serialize a [`Url`] as a `urllib.parse.SplitResult`
+    pub fn serialize<S>(url: &Url, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_tagged(serializer, SPLIT_URL_TOKEN, url.as_str())
+    }
+
+    /// Deserialize a [`Url`] from a `urllib.parse.SplitResult` or string
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Url, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text: String = deserialize_tagged(deserializer, SPLIT_URL_TOKEN)?;
+        super::parse(&text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use pyo3::types::PyString;
+    use serde::{Deserialize, Serialize};
+    use url::Url;
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Link {
+        #[serde(with = "crate::native")]
+        href: Url,
+        #[serde(with = "crate::native::split_url")]
+        parts: Url,
+    }
+
+    #[test]
+    fn test_url_roundtrip() {
+        Python::with_gil(|py| {
+            let url = Url::parse("https://user@example.com:8443/a/b?q=1#frag").unwrap();
+            let link = Link {
+                href: url.clone(),
+                parts: url,
+            };
+            let obj = pythonize(py, &link).unwrap();
+            assert!(obj
+                .get_item("href")
+                .unwrap()
+                .is_exact_instance_of::<PyString>());
+            let parts = obj.get_item("parts").unwrap();
+            assert_eq!(
+                parts.getattr("netloc").unwrap().to_string(),
+                "user@example.com:8443"
+            );
+            assert_eq!(parts.getattr("fragment").unwrap().to_string(), "frag");
+
+            let actual: Link = depythonize(&obj).unwrap();
+            assert_eq!(actual, link);
+        })
+    }
+
+    #[test]
+    fn test_url_from_python() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'href': __import__('urllib.parse').parse.urlsplit('http://a.test/x'), \
+                     'parts': 'file:///tmp'}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let actual: Link = depythonize(&obj).unwrap();
+            assert_eq!(actual.href.as_str(), "http://a.test/x");
+            assert_eq!(actual.parts.as_str(), "file:///tmp");
+
+            obj.set_item("href", "not a url").unwrap();
+            let err = depythonize::<Link>(&obj).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid URL \"not a url\""),
+                "{}",
+                err
+            );
+        })
+    }
+
+    #[test]
+    fn test_split_url_other_formats() {
+        let link = Link {
+            href: Url::parse("https://example.com/").unwrap(),
+            parts: Url::parse("https://example.com/").unwrap(),
+        };
+        assert_eq!(
+            serde_json::to_value(&link).unwrap(),
+            serde_json::json!({ "href": "https://example.com/", "parts": "https://example.com/" })
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use uuid::Uuid;
+
+use super::{deserialize_tagged, serialize_tagged, Native, UUID_TOKEN};
+
+impl Native for Uuid {
+    fn serialize_native<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let text = self.hyphenated().to_string();
+        serialize_tagged(serializer, UUID_TOKEN, &text)
+    }
+
+    fn deserialize_native<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text: String = deserialize_tagged(deserializer, UUID_TOKEN)?;
+        Uuid::parse_str(&text).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use crate::{depythonize, pythonize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Record {
+        #[serde(with = "crate::native")]
+        id: Uuid,
+        #[serde(with = "crate::native")]
+        parent: Option<Uuid>,
+    }
+
+    #[test]
+    fn test_uuid_roundtrip() {
+        Python::with_gil(|py| {
+            let record = Record {
+                id: Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8),
+                parent: None,
+            };
+            let obj = pythonize(py, &record).unwrap();
+            assert_eq!(
+                obj.get_item("id")
+                    .unwrap()
+                    .repr()
+                    .unwrap()
+                    .to_cow()
+                    .unwrap(),
+                "UUID('67e55044-10b1-426f-9247-bb680e5fe0c8')"
+            );
+
+            let actual: Record = depythonize(&obj).unwrap();
+            assert_eq!(actual, record);
+        })
+    }
+
+    #[test]
+    fn test_uuid_from_str() {
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound(
+                    "{'id': '67E55044-10B1-426F-9247-BB680E5FE0C8', 'parent': 'not a uuid'}",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let err = depythonize::<Record>(&obj).unwrap_err();
+            assert!(err.to_string().starts_with("invalid character"), "{}", err);
+
+            obj.set_item("parent", obj.get_item("id").unwrap()).unwrap();
+            let actual: Record = depythonize(&obj).unwrap();
+            assert_eq!(actual.parent, Some(actual.id));
+            assert_eq!(
+                actual.id.as_u128(),
+                0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8
+            );
+        })
+    }
+
+    #[test]
+    fn test_uuid_without_adapter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Plain {
+            id: Uuid,
+        }
+
+        Python::with_gil(|py| {
+            let obj = py
+                .eval_bound("{'id': __import__('uuid').uuid4()}", None, None)
+                .unwrap();
+            let actual: Plain = depythonize(&obj).unwrap();
+            let expected: String = obj
+                .get_item("id")
+                .unwrap()
+                .str()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(actual.id.hyphenated().to_string(), expected);
+        })
+    }
+}
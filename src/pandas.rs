@@ -0,0 +1,738 @@
+//! Column-wise conversion of Rust rows into a `pandas.DataFrame`
+
+use std::fmt::{self, Display};
+use std::result;
+
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{
+    PyAnyMethods, PyBytes, PyDict, PyList, PyString, PyStringMethods, PyTypeMethods,
+};
+use pyo3::{Bound, IntoPy, PyAny, PyObject, Python};
+use serde::de::DeserializeOwned;
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::de::depythonize_columns;
+use crate::error::{PythonizeError, Result};
+use crate::native::import;
+use crate::native::numpy::{self, ArrayContents, Elements};
+use crate::ser::{pythonize, Pythonizer};
+
+static DATAFRAME: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Convert a slice of structs into a `pandas.DataFrame`, with one column per field
+///
+/// The rows are gathered into columns on the Rust side, and columns of booleans,
+/// integers and floats become numpy arrays without creating a Python object per value,
+/// which is much faster than building the `DataFrame` from a list of dicts. Other values
+/// are converted as by [`pythonize`]. Rows may also be maps with string keys, and a
+/// column which is missing from some rows is `None` in those rows.
+pub fn pythonize_dataframe<'py, T>(py: Python<'py>, rows: &[T]) -> Result<Bound<'py, PyAny>>
+where
+    T: Serialize,
+{
+    let mut columns = Columns {
+        py,
+        names: Vec::new(),
+        columns: Vec::new(),
+        rows: 0,
+    };
+    for row in rows {
+        row.serialize(RowSerializer {
+            columns: &mut columns,
+        })?;
+        columns.end_row();
+    }
+    let data = PyDict::new_bound(py);
+    for (name, column) in columns.names.into_iter().zip(columns.columns) {
+        data.set_item(name, column.into_python(py)?)?;
+    }
+    Ok(import(&DATAFRAME, py, "pandas", "DataFrame")?.call1((data,))?)
+}
+
+/// Convert the rows of a `pandas.DataFrame` into a vector of structs, whose fields are
+/// matched to the columns by name
+///
+/// Each column is converted as a whole, so columns of booleans, integers and floats are
+/// decoded from a single copy of their numpy array rather than a Python object per
+/// value. Other columns are converted from their values as by
+/// [`depythonize`](crate::depythonize), with `pandas.NA`, `pandas.NaT` and NaN floats
+/// as `None`. Column names which are not strings are matched by their `str()`, and the
+/// index is ignored.
+pub fn depythonize_records<T>(obj: &Bound<'_, PyAny>) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    if !obj.is_instance(import(&DATAFRAME, obj.py(), "pandas", "DataFrame")?)? {
+        return Err(PythonizeError::msg(format!(
+            "expected a pandas.DataFrame, got '{}'",
+            obj.get_type().qualname()?
+        )));
+    }
+    let mut columns = Vec::new();
+    for column in obj.call_method0("items")?.iter()? {
+        let (name, series): (Bound<'_, PyAny>, Bound<'_, PyAny>) = column?.extract()?;
+        let values = series.call_method0("to_numpy")?;
+        let kind: char = values.getattr("dtype")?.getattr("kind")?.extract()?;
+        let contents = match kind {
+            'b' | 'i' | 'u' | 'f' => numpy::array_contents(&values)?,
+            _ => None,
+        };
+        let contents = match contents {
+            // missing floats are left for `Option` fields to see as `None`
+            Some(ArrayContents::Numeric(_, Elements::Float(values)))
+                if values.iter().any(|value| value.is_nan()) =>
+            {
+                ArrayContents::List(series.call_method0("tolist")?)
+            }
+            Some(contents @ ArrayContents::Numeric(..)) => contents,
+            // the values of other columns, such as `pandas.Timestamp`s rather than the
+            // integers of a `datetime64` array
+            _ => ArrayContents::List(series.call_method0("tolist")?),
+        };
+        columns.push((name.str()?.to_cow()?.into_owned(), contents));
+    }
+    depythonize_columns(obj.len()?, &columns)
+}
+
+/// A single value of a row
+enum Cell<'py> {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Object(Bound<'py, PyAny>),
+}
+
+impl<'py> Cell<'py> {
+    fn new<T>(py: Python<'py>, value: &T) -> Result<Self>
+    where
+        T: ?Sized + Serialize,
+    {
+        match value.serialize(CellSerializer { py }) {
+            Ok(cell) => Ok(cell),
+            Err(NotPrimitive) => Ok(Cell::Object(pythonize(py, value)?)),
+        }
+    }
+
+    fn into_python(self, py: Python<'py>) -> Bound<'py, PyAny> {
+        match self {
+            Cell::Bool(value) => value.into_py(py).into_bound(py),
+            Cell::Int(value) => value.into_py(py).into_bound(py),
+            Cell::Float(value) => value.into_py(py).into_bound(py),
+            Cell::Object(obj) => obj,
+        }
+    }
+}
+
+/// The values of a column, which are only converted to Python objects once they are
+/// not all booleans, integers or floats
+enum Column<'py> {
+    Bool(Vec<bool>),
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Objects(Vec<Bound<'py, PyAny>>),
+}
+
+impl<'py> Column<'py> {
+    fn len(&self) -> usize {
+        match self {
+            Column::Bool(values) => values.len(),
+            Column::Int(values) => values.len(),
+            Column::Float(values) => values.len(),
+            Column::Objects(values) => values.len(),
+        }
+    }
+
+    fn push(&mut self, py: Python<'py>, cell: Cell<'py>) {
+        match (&mut *self, cell) {
+            (Column::Bool(values), Cell::Bool(value)) => values.push(value),
+            (Column::Int(values), Cell::Int(value)) => values.push(value),
+            (Column::Float(values), Cell::Float(value)) => values.push(value),
+            (Column::Objects(values), Cell::Bool(value)) if values.is_empty() => {
+                *self = Column::Bool(vec![value])
+            }
+            (Column::Objects(values), Cell::Int(value)) if values.is_empty() => {
+                *self = Column::Int(vec![value])
+            }
+            (Column::Objects(values), Cell::Float(value)) if values.is_empty() => {
+                *self = Column::Float(vec![value])
+            }
+            (_, cell) => self.objects(py).push(cell.into_python(py)),
+        }
+    }
+
+    /// Convert the values to Python objects, once the column holds mixed types
+    fn objects(&mut self, py: Python<'py>) -> &mut Vec<Bound<'py, PyAny>> {
+        let values = match std::mem::replace(self, Column::Objects(Vec::new())) {
+            Column::Bool(values) => cells(py, values, Cell::Bool),
+            Column::Int(values) => cells(py, values, Cell::Int),
+            Column::Float(values) => cells(py, values, Cell::Float),
+            Column::Objects(values) => values,
+        };
+        *self = Column::Objects(values);
+        match self {
+            Column::Objects(values) => values,
+            _ => unreachable!(),
+        }
+    }
+
+    fn into_python(self, py: Python<'py>) -> Result<Bound<'py, PyAny>> {
+        let len = self.len();
+        let (dtype, data): (_, Vec<u8>) = match self {
+            Column::Bool(values) => ("?", values.into_iter().map(u8::from).collect()),
+            Column::Int(values) => ("=i8", values.iter().flat_map(|v| v.to_ne_bytes()).collect()),
+            Column::Float(values) => ("=f8", values.iter().flat_map(|v| v.to_ne_bytes()).collect()),
+            Column::Objects(values) => return Ok(PyList::new_bound(py, values).into_any()),
+        };
+        let payload: PyObject = (dtype, (len,), PyBytes::new_bound(py, &data)).into_py(py);
+        Ok(numpy::array_to_python(payload.bind(py))?)
+    }
+}
+
+fn cells<'py, T>(
+    py: Python<'py>,
+    values: Vec<T>,
+    cell: fn(T) -> Cell<'py>,
+) -> Vec<Bound<'py, PyAny>> {
+    values
+        .into_iter()
+        .map(|value| cell(value).into_python(py))
+        .collect()
+}
+
+/// The columns gathered so far, in the order in which they were first seen
+struct Columns<'py> {
+    py: Python<'py>,
+    names: Vec<String>,
+    columns: Vec<Column<'py>>,
+    rows: usize,
+}
+
+impl<'py> Columns<'py> {
+    fn push<T>(&mut self, name: &str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let cell = Cell::new(self.py, value)?;
+        let index = match self.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                // earlier rows did not have this column
+                let none = self.py.None().into_bound(self.py);
+                self.names.push(name.to_owned());
+                self.columns.push(Column::Objects(vec![none; self.rows]));
+                self.columns.len() - 1
+            }
+        };
+        let column = &mut self.columns[index];
+        if column.len() > self.rows {
+            return Err(PythonizeError::msg(format_args!(
+                "duplicate column {:?}",
+                name
+            )));
+        }
+        column.push(self.py, cell);
+        Ok(())
+    }
+
+    fn end_row(&mut self) {
+        self.rows += 1;
+        for column in &mut self.columns {
+            if column.len() < self.rows {
+                column.push(self.py, Cell::Object(self.py.None().into_bound(self.py)));
+            }
+        }
+    }
+}
+
+fn not_a_row() -> PythonizeError {
+    PythonizeError::msg("expected each row to be a struct or map")
+}
+
+macro_rules! reject {
+    ($error:expr; $($method:ident($($arg:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> result::Result<Self::Ok, Self::Error> {
+                Err($error)
+            }
+        )*
+    };
+}
+
+/// Serializes a row into [`Columns`]
+struct RowSerializer<'a, 'py> {
+    columns: &'a mut Columns<'py>,
+}
+
+impl<'a, 'py> ser::Serializer for RowSerializer<'a, 'py> {
+    type Ok = ();
+    type Error = PythonizeError;
+    type SerializeSeq = Impossible<(), PythonizeError>;
+    type SerializeTuple = Impossible<(), PythonizeError>;
+    type SerializeTupleStruct = Impossible<(), PythonizeError>;
+    type SerializeTupleVariant = Impossible<(), PythonizeError>;
+    type SerializeMap = RowMapSerializer<'a, 'py>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), PythonizeError>;
+
+    reject! {
+        not_a_row();
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_row())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_row())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_a_row())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_a_row())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_a_row())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_a_row())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(RowMapSerializer {
+            columns: self.columns,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_a_row())
+    }
+}
+
+impl ser::SerializeStruct for RowSerializer<'_, '_> {
+    type Ok = ();
+    type Error = PythonizeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.columns.push(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a row which is a map, such as a struct with a flattened field
+struct RowMapSerializer<'a, 'py> {
+    columns: &'a mut Columns<'py>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for RowMapSerializer<'_, '_> {
+    type Ok = ();
+    type Error = PythonizeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = key
+            .serialize(Pythonizer::new(self.columns.py))?
+            .downcast_into::<PyString>()
+            .map_err(|_| PythonizeError::dict_key_not_string())?;
+        self.key = Some(key.to_cow()?.into_owned());
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value should always be called after serialize_key");
+        self.columns.push(&key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A value which is not a boolean, integer, float or `None`, and so is converted by
+/// [`pythonize`] instead of [`CellSerializer`]
+#[derive(Debug)]
+struct NotPrimitive;
+
+impl Display for NotPrimitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not a primitive value")
+    }
+}
+
+impl std::error::Error for NotPrimitive {}
+
+impl ser::Error for NotPrimitive {
+    fn custom<T: Display>(_msg: T) -> Self {
+        NotPrimitive
+    }
+}
+
+/// Serializes primitive values into a [`Cell`] without creating Python objects
+struct CellSerializer<'py> {
+    py: Python<'py>,
+}
+
+impl<'py> ser::Serializer for CellSerializer<'py> {
+    type Ok = Cell<'py>;
+    type Error = NotPrimitive;
+    type SerializeSeq = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeTuple = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeTupleStruct = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeTupleVariant = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeMap = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeStruct = Impossible<Cell<'py>, NotPrimitive>;
+    type SerializeStructVariant = Impossible<Cell<'py>, NotPrimitive>;
+
+    fn serialize_bool(self, v: bool) -> result::Result<Cell<'py>, NotPrimitive> {
+        Ok(Cell::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> result::Result<Cell<'py>, NotPrimitive> {
+        Ok(Cell::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> result::Result<Cell<'py>, NotPrimitive> {
+        // larger values do not fit in an int64 column
+        i64::try_from(v).map(Cell::Int).map_err(|_| NotPrimitive)
+    }
+
+    fn serialize_f32(self, v: f32) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> result::Result<Cell<'py>, NotPrimitive> {
+        Ok(Cell::Float(v))
+    }
+
+    fn serialize_none(self) -> result::Result<Cell<'py>, NotPrimitive> {
+        Ok(Cell::Object(self.py.None().into_bound(self.py)))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> result::Result<Cell<'py>, NotPrimitive>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> result::Result<Cell<'py>, NotPrimitive> {
+        self.serialize_none()
+    }
+
+    reject! {
+        NotPrimitive;
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> result::Result<Cell<'py>, NotPrimitive>
+    where
+        T: ?Sized + Serialize,
+    {
+        // newtypes may be tagged values from `pythonize::native`
+        Err(NotPrimitive)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> result::Result<Cell<'py>, NotPrimitive>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> result::Result<Self::SerializeSeq, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> result::Result<Self::SerializeTuple, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> result::Result<Self::SerializeTupleStruct, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> result::Result<Self::SerializeTupleVariant, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> result::Result<Self::SerializeMap, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> result::Result<Self::SerializeStruct, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> result::Result<Self::SerializeStructVariant, NotPrimitive> {
+        Err(NotPrimitive)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use serde::{Deserialize, Serialize};
+
+    use super::{depythonize_records, pythonize_dataframe};
+
+    #[derive(Serialize)]
+    struct Trade {
+        id: u32,
+        price: f64,
+        filled: bool,
+        symbol: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<&'static str>,
+    }
+
+    #[test]
+    #[ignore = "needs the pandas Python package"]
+    fn test_dataframe() {
+        Python::with_gil(|py| {
+            let trades = [
+                Trade {
+                    id: 1,
+                    price: 9.5,
+                    filled: true,
+                    symbol: "ABC",
+                    note: None,
+                },
+                Trade {
+                    id: 2,
+                    price: 10.25,
+                    filled: false,
+                    symbol: "XYZ",
+                    note: Some("late"),
+                },
+            ];
+            let df = pythonize_dataframe(py, &trades).unwrap();
+            let columns: Vec<String> = df.getattr("columns").unwrap().extract().unwrap();
+            assert_eq!(columns, ["id", "price", "filled", "symbol", "note"]);
+            let dtypes = df.getattr("dtypes").unwrap();
+            for (column, dtype) in [("id", "int64"), ("price", "float64"), ("filled", "bool")] {
+                assert_eq!(dtypes.get_item(column).unwrap().to_string(), dtype);
+            }
+            let records = df
+                .call_method1("to_dict", ("records",))
+                .unwrap()
+                .repr()
+                .unwrap();
+            assert_eq!(
+                records.to_cow().unwrap(),
+                "[{'id': 1, 'price': 9.5, 'filled': True, 'symbol': 'ABC', 'note': None}, \
+                 {'id': 2, 'price': 10.25, 'filled': False, 'symbol': 'XYZ', 'note': 'late'}]"
+            );
+        })
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Reading {
+        sensor: String,
+        value: Option<f64>,
+        count: Option<i64>,
+        ok: bool,
+    }
+
+    #[test]
+    #[ignore = "needs the pandas Python package"]
+    fn test_depythonize_records() {
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import pandas as pd\n\
+                 df = pd.DataFrame({\n\
+                 'ok': [True, False, True],\n\
+                 'value': [0.5, float('nan'), 2.0],\n\
+                 'sensor': ['a', 'b', 'c'],\n\
+                 'count': pd.array([1, None, 3], dtype='Int64'),\n\
+                 'extra': [1, 2, 3],\n\
+                 }, index=[10, 20, 30])",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let df = locals.get_item("df").unwrap().unwrap();
+            let readings: Vec<Reading> = depythonize_records(&df).unwrap();
+            assert_eq!(
+                readings,
+                [
+                    Reading {
+                        sensor: "a".to_string(),
+                        value: Some(0.5),
+                        count: Some(1),
+                        ok: true,
+                    },
+                    Reading {
+                        sensor: "b".to_string(),
+                        value: None,
+                        count: None,
+                        ok: false,
+                    },
+                    Reading {
+                        sensor: "c".to_string(),
+                        value: Some(2.0),
+                        count: Some(3),
+                        ok: true,
+                    },
+                ]
+            );
+
+            let empty = df.call_method1("head", (0,)).unwrap();
+            let readings: Vec<Reading> = depythonize_records(&empty).unwrap();
+            assert!(readings.is_empty());
+
+            let err = depythonize_records::<Reading>(&PyDict::new_bound(py)).unwrap_err();
+            assert_eq!(err.to_string(), "expected a pandas.DataFrame, got 'dict'");
+        })
+    }
+
+    #[test]
+    fn test_dataframe_rows_must_be_structs() {
+        Python::with_gil(|py| {
+            let err = pythonize_dataframe(py, &[1, 2, 3]).unwrap_err();
+            assert_eq!(err.to_string(), "expected each row to be a struct or map");
+        })
+    }
+}
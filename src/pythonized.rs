@@ -0,0 +1,175 @@
+use std::ops::{Deref, DerefMut};
+
+use pyo3::{Bound, FromPyObject, IntoPy, PyAny, PyObject, PyResult, Python, ToPyObject};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::de::depythonize;
+use crate::ser::pythonize;
+
+/// A value which PyO3 converts to and from Python objects with serde
+///
+/// The conversions into Python objects call [`pythonize`], so a `Pythonized` value can
+/// be returned from a `#[pyfunction]` or read from a `#[pyo3(get)]` field of a
+/// `#[pyclass]` without converting it by hand. Extracting one calls [`depythonize`], for
+/// arguments and `#[pyo3(set)]` fields.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pythonize::Pythonized;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: &'static str,
+///     retries: u32,
+/// }
+///
+/// #[pyfunction]
+/// fn config() -> Pythonized<Config> {
+///     Pythonized(Config {
+///         name: "app",
+///         retries: 3,
+///     })
+/// }
+///
+/// Python::with_gil(|py| {
+///     let config = wrap_pyfunction_bound!(config, py).unwrap();
+///     let obj = config.call0().unwrap();
+///     assert_eq!(obj.to_string(), "{'name': 'app', 'retries': 3}");
+/// })
+/// ```
+///
+/// # Panics
+///
+/// `IntoPy` and `ToPyObject` can't return errors, so converting a value which
+/// [`pythonize`] fails on panics, which PyO3 raises as a `pyo3_runtime.PanicException`
+/// at the boundary of a `#[pyfunction]`. Call [`pythonize`] directly for values which
+/// may fail, such as maps whose keys aren't strings or numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pythonized<T>(pub T);
+
+impl<T> Pythonized<T> {
+    /// Unwraps the value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Pythonized<T> {
+    fn from(value: T) -> Self {
+        Pythonized(value)
+    }
+}
+
+impl<T> Deref for Pythonized<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Pythonized<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Serialize> ToPyObject for Pythonized<T> {
+    fn to_object(&self, py: Python<'_>) -> PyObject {
+        match pythonize(py, &self.0) {
+            Ok(obj) => obj.unbind(),
+            Err(err) => panic!("failed to pythonize value: {}", err),
+        }
+    }
+}
+
+impl<T: Serialize> IntoPy<PyObject> for Pythonized<T> {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.to_object(py)
+    }
+}
+
+impl<'py, T: DeserializeOwned> FromPyObject<'py> for Pythonized<T> {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(Pythonized(depythonize(obj)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use pyo3::exceptions::PyTypeError;
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::Pythonized;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Limits {
+        depth: u8,
+        labels: Vec<String>,
+    }
+
+    #[pyclass]
+    struct Job {
+        #[pyo3(get, set)]
+        limits: Pythonized<Limits>,
+    }
+
+    #[pyfunction]
+    fn widen(limits: Pythonized<Limits>) -> Pythonized<Limits> {
+        Pythonized(Limits {
+            depth: limits.depth + 1,
+            ..limits.into_inner()
+        })
+    }
+
+    #[test]
+    fn test_pyfunction() {
+        Python::with_gil(|py| {
+            let widen = wrap_pyfunction_bound!(widen, py).unwrap();
+            let limits = py
+                .eval_bound("{'depth': 1, 'labels': ['a']}", None, None)
+                .unwrap();
+            let obj = widen.call1((limits,)).unwrap();
+            assert_eq!(obj.to_string(), "{'depth': 2, 'labels': ['a']}");
+
+            let err = widen.call1((1,)).unwrap_err();
+            assert!(err.is_instance_of::<PyTypeError>(py));
+        })
+    }
+
+    #[test]
+    fn test_pyclass_field() {
+        Python::with_gil(|py| {
+            let job = Bound::new(
+                py,
+                Job {
+                    limits: Pythonized(Limits {
+                        depth: 3,
+                        labels: vec![],
+                    }),
+                },
+            )
+            .unwrap();
+            let limits = job.getattr("limits").unwrap();
+            assert_eq!(limits.to_string(), "{'depth': 3, 'labels': []}");
+
+            limits.set_item("labels", vec!["x"]).unwrap();
+            job.setattr("limits", limits).unwrap();
+            assert_eq!(job.borrow().limits.labels, ["x"]);
+        })
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to pythonize value")]
+    fn test_pythonize_error_panics() {
+        Python::with_gil(|py| {
+            let map: HashMap<Vec<u8>, u8> = [(vec![1], 1)].into_iter().collect();
+            Pythonized(map).into_py(py);
+        })
+    }
+}
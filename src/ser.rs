@@ -1,13 +1,21 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::{Mutex, PoisonError};
 
+use pyo3::sync::GILOnceCell;
+use pyo3::types::IntoPyDict;
 use pyo3::types::{
-    PyAnyMethods, PyDict, PyDictMethods, PyList, PyMapping, PySequence, PyString, PyTuple,
-    PyTupleMethods,
+    PyAnyMethods, PyBool, PyBoolMethods, PyByteArray, PyBytes, PyDict, PyDictMethods, PyFrozenSet,
+    PyFrozenSetBuilder, PyInt, PyList, PyListMethods, PyMapping, PyMemoryView, PySequence,
+    PySequenceMethods, PySet, PySetMethods, PyString, PyTuple, PyTupleMethods, PyType,
 };
-use pyo3::{Bound, IntoPy, PyAny, PyResult, Python, ToPyObject};
+use pyo3::{Bound, IntoPy, Py, PyAny, PyObject, PyResult, Python, ToPyObject};
 use serde::{ser, Serialize};
 
 use crate::error::{PythonizeError, Result};
+use crate::native;
 
 // TODO: move 'py lifetime into builder once GATs are available in MSRV
 /// Trait for types which can represent a Python mapping
@@ -25,6 +33,21 @@ pub trait PythonizeMappingType<'py> {
         value: Bound<'py, PyAny>,
     ) -> PyResult<()>;
 
+    /// Adds all of the key-value items of a serialized map to the mapping being built
+    ///
+    /// By default, each item is passed to [`push_item`](Self::push_item). Implementations
+    /// which can add many items in one call, such as by passing a list of pairs to the
+    /// constructor, override this.
+    fn push_items(
+        builder: &mut Self::Builder,
+        items: impl ExactSizeIterator<Item = (Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+    ) -> PyResult<()> {
+        for (key, value) in items {
+            Self::push_item(builder, key, value)?;
+        }
+        Ok(())
+    }
+
     /// Build the Python mapping
     fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>>;
 }
@@ -49,6 +72,29 @@ pub trait PythonizeNamedMappingType<'py> {
     fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>>;
 }
 
+// TODO: move 'py lifetime into builder once GATs are available in MSRV
+/// Trait for types which can represent a Rust struct in Python
+///
+/// This is implemented for every [`PythonizeNamedMappingType`], as well as for
+/// [`PythonizeDataclass`].
+pub trait PythonizeStructType<'py> {
+    /// Builder type for Python objects representing structs
+    type Builder;
+
+    /// Create a builder for the struct with the given name
+    fn builder(py: Python<'py>, len: usize, name: &'static str) -> PyResult<Self::Builder>;
+
+    /// Adds the field to the struct being built
+    fn push_field(
+        builder: &mut Self::Builder,
+        name: Bound<'py, PyString>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()>;
+
+    /// Build the Python object
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyAny>>;
+}
+
 /// Trait for types which can represent a Python sequence
 pub trait PythonizeListType: Sized {
     /// Constructor
@@ -59,6 +105,57 @@ pub trait PythonizeListType: Sized {
     where
         T: ToPyObject,
         U: ExactSizeIterator<Item = T>;
+
+    /// Constructor from a `list` of the elements, which the serializer fills in place
+    /// when the length of the sequence is known up front
+    ///
+    /// By default, the elements of the list are passed to
+    /// [`create_sequence`](Self::create_sequence).
+    fn create_sequence_from_list<'py>(
+        list: Bound<'py, PyList>,
+    ) -> PyResult<Bound<'py, PySequence>> {
+        Self::create_sequence(list.py(), list.iter())
+    }
+}
+
+/// Trait for types which can represent a Python set
+pub trait PythonizeSetType: Sized {
+    /// Constructor
+    fn create_set<T, U>(
+        py: Python,
+        elements: impl IntoIterator<Item = T, IntoIter = U>,
+    ) -> PyResult<Bound<PyAny>>
+    where
+        T: ToPyObject,
+        U: ExactSizeIterator<Item = T>;
+}
+
+/// Trait for types which can represent a Python tuple
+pub trait PythonizeTupleType {
+    /// Constructor, given the name of the tuple struct or tuple variant being serialized,
+    /// or `None` for a tuple
+    fn create_tuple<'py>(
+        py: Python<'py>,
+        name: Option<&'static str>,
+        elements: impl ExactSizeIterator<Item = Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>>;
+}
+
+/// Trait for types which can represent a unit variant of an enum in Python
+pub trait PythonizeUnitVariantType {
+    /// Constructor, given the names of the enum and variant and the index of the variant
+    fn create_unit_variant<'py>(
+        py: Python<'py>,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> PyResult<Bound<'py, PyAny>>;
+}
+
+/// Trait for types which can represent Python bytes
+pub trait PythonizeBytesType {
+    /// Constructor
+    fn create_bytes<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, PyAny>>;
 }
 
 // TODO: remove 'py lifetime once GATs are available in MSRV
@@ -68,15 +165,60 @@ pub trait PythonizeTypes<'py> {
     type Map: PythonizeMappingType<'py>;
     /// Python (struct-like) named map type (should be representable as python mapping)
     type NamedMap: PythonizeNamedMappingType<'py>;
+    /// Python type for structs and the contents of struct variants, usually the same as
+    /// [`NamedMap`](PythonizeTypes::NamedMap)
+    type Struct: PythonizeStructType<'py>;
     /// Python sequence type (should be representable as python sequence)
     type List: PythonizeListType;
+    /// Python set type, used for Rust sets serialized with [`native`](crate::native)
+    type Set: PythonizeSetType;
+    /// Python tuple type, used for tuples, tuple structs and tuple variants
+    type Tuple: PythonizeTupleType;
+    /// Python representation of unit variants of enums
+    type UnitVariant: PythonizeUnitVariantType;
+    /// Python bytes type, used for byte arrays such as `serde_bytes::ByteBuf`
+    type Bytes: PythonizeBytesType;
+}
+
+/// Dicts with at most this many items fit in the initial allocation, so are not presized
+const PRESIZE_THRESHOLD: usize = 5;
+
+/// The most items preallocated from a length hint, as with serde's `size_hint::cautious`,
+/// since the hint comes from the `Serialize` impl and may be overstated
+const MAX_PREALLOCATED_ELEMENTS: usize = 4096;
+
+/// Creates an empty `dict` with room for `len` items without resizing
+///
+/// `_PyDict_NewPresized` is private to CPython, so unlike the public API it may change or
+/// be removed in any release; the build then fails here rather than misbehaving, and this
+/// can fall back to `PyDict::new_bound` like the limited API does.
+#[cfg(not(any(Py_LIMITED_API, PyPy, GraalPy)))]
+fn new_presized_dict(py: Python<'_>, len: usize) -> PyResult<Bound<'_, PyDict>> {
+    use pyo3::ffi;
+
+    // cannot overflow, since the cap fits in a `Py_ssize_t`
+    let len = len.min(MAX_PREALLOCATED_ELEMENTS) as ffi::Py_ssize_t;
+    // SAFETY: returns a new reference to an empty dict, or null with an exception set
+    unsafe {
+        Bound::from_owned_ptr_or_err(py, ffi::_PyDict_NewPresized(len))
+            .map(|dict| dict.downcast_into_unchecked())
+    }
+}
+
+/// The interpreter does not expose presized dicts, so they grow as items are added
+#[cfg(any(Py_LIMITED_API, PyPy, GraalPy))]
+fn new_presized_dict(py: Python<'_>, _len: usize) -> PyResult<Bound<'_, PyDict>> {
+    Ok(PyDict::new_bound(py))
 }
 
 impl<'py> PythonizeMappingType<'py> for PyDict {
     type Builder = Bound<'py, Self>;
 
-    fn builder(py: Python<'py>, _len: Option<usize>) -> PyResult<Self::Builder> {
-        Ok(Self::new_bound(py))
+    fn builder(py: Python<'py>, len: Option<usize>) -> PyResult<Self::Builder> {
+        match len {
+            Some(len) if len > PRESIZE_THRESHOLD => new_presized_dict(py, len),
+            _ => Ok(Self::new_bound(py)),
+        }
     }
 
     fn push_item(
@@ -92,6 +234,233 @@ impl<'py> PythonizeMappingType<'py> for PyDict {
     }
 }
 
+/// Creates read-only `types.MappingProxyType` views of new `dict`s
+pub struct PythonizeMappingProxy;
+
+static MAPPING_PROXY: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl<'py> PythonizeMappingType<'py> for PythonizeMappingProxy {
+    type Builder = Bound<'py, PyDict>;
+
+    fn builder(py: Python<'py>, len: Option<usize>) -> PyResult<Self::Builder> {
+        PyDict::builder(py, len)
+    }
+
+    fn push_item(
+        builder: &mut Self::Builder,
+        key: Bound<'py, PyAny>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        PyDict::push_item(builder, key, value)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        let py = builder.py();
+        let proxy = native::import(&MAPPING_PROXY, py, "types", "MappingProxyType")?;
+        Ok(proxy.call1((builder,))?.downcast_into()?)
+    }
+}
+
+/// Creates `collections.OrderedDict`s
+///
+/// This is also a preset for [`PythonizeTypes`], using `OrderedDict`s for both mappings
+/// and structs and the default types otherwise.
+pub struct PythonizeOrderedDict;
+
+static ORDERED_DICT: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl<'py> PythonizeMappingType<'py> for PythonizeOrderedDict {
+    type Builder = Bound<'py, PyAny>;
+
+    fn builder(py: Python<'py>, _len: Option<usize>) -> PyResult<Self::Builder> {
+        native::import(&ORDERED_DICT, py, "collections", "OrderedDict")?.call0()
+    }
+
+    fn push_item(
+        builder: &mut Self::Builder,
+        key: Bound<'py, PyAny>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        // not `PyDict::set_item`, which would bypass the order kept by `OrderedDict`
+        builder.set_item(key, value)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        Ok(builder.downcast_into()?)
+    }
+}
+
+impl<'py> PythonizeNamedMappingType<'py> for PythonizeOrderedDict {
+    type Builder = Bound<'py, PyAny>;
+
+    fn builder(py: Python<'py>, len: usize, _name: &'static str) -> PyResult<Self::Builder> {
+        <Self as PythonizeMappingType>::builder(py, Some(len))
+    }
+
+    fn push_field(
+        builder: &mut Self::Builder,
+        name: Bound<'py, PyString>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        <Self as PythonizeMappingType>::push_item(builder, name.into_any(), value)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        <Self as PythonizeMappingType>::finish(builder)
+    }
+}
+
+impl<'py> PythonizeTypes<'py> for PythonizeOrderedDict {
+    type Map = Self;
+    type NamedMap = Self;
+    type Struct = Self;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+/// Creates `frozendict`s, from the third-party `frozendict` package, by freezing a new
+/// `dict`
+///
+/// This is also a preset for [`PythonizeTypes`], using `frozendict`s for both mappings
+/// and structs and the default types otherwise.
+pub struct PythonizeFrozenDict;
+
+static FROZENDICT: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl<'py> PythonizeMappingType<'py> for PythonizeFrozenDict {
+    type Builder = Bound<'py, PyDict>;
+
+    fn builder(py: Python<'py>, len: Option<usize>) -> PyResult<Self::Builder> {
+        PyDict::builder(py, len)
+    }
+
+    fn push_item(
+        builder: &mut Self::Builder,
+        key: Bound<'py, PyAny>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        PyDict::push_item(builder, key, value)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        let py = builder.py();
+        let frozendict = native::import(&FROZENDICT, py, "frozendict", "frozendict")?;
+        Ok(frozendict.call1((builder,))?.downcast_into()?)
+    }
+}
+
+impl<'py> PythonizeTypes<'py> for PythonizeFrozenDict {
+    type Map = Self;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, Self>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, Self>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+/// Appends the items to a list of `(key, value)` tuples, which is created in one go if
+/// it is still empty
+fn extend_pairs<'py>(
+    pairs: &mut Bound<'py, PyList>,
+    mut items: impl ExactSizeIterator<Item = (Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+) -> PyResult<()> {
+    if pairs.is_empty() {
+        *pairs = PyList::new_bound(pairs.py(), items);
+        return Ok(());
+    }
+    items.try_for_each(|item| pairs.append(item))
+}
+
+/// Creates `immutables.Map`s, from the third-party `immutables` package, from the
+/// collected items
+pub struct PythonizeImmutablesMap;
+
+static IMMUTABLES_MAP: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl<'py> PythonizeMappingType<'py> for PythonizeImmutablesMap {
+    type Builder = Bound<'py, PyList>;
+
+    fn builder(py: Python<'py>, _len: Option<usize>) -> PyResult<Self::Builder> {
+        Ok(PyList::empty_bound(py))
+    }
+
+    fn push_item(
+        builder: &mut Self::Builder,
+        key: Bound<'py, PyAny>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        builder.append((key, value))
+    }
+
+    fn push_items(
+        builder: &mut Self::Builder,
+        items: impl ExactSizeIterator<Item = (Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+    ) -> PyResult<()> {
+        extend_pairs(builder, items)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        let py = builder.py();
+        let map = native::import(&IMMUTABLES_MAP, py, "immutables", "Map")?;
+        Ok(map.call1((builder,))?.downcast_into()?)
+    }
+}
+
+/// Creates `SortedDict`s, from the third-party `sortedcontainers` package, from the
+/// collected items
+///
+/// This is also a preset for [`PythonizeTypes`], using `SortedDict`s for mappings so
+/// that their keys are in order, and the default types otherwise. Structs keep the
+/// order of their fields.
+pub struct PythonizeSortedDict;
+
+static SORTED_DICT: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl<'py> PythonizeMappingType<'py> for PythonizeSortedDict {
+    type Builder = Bound<'py, PyList>;
+
+    fn builder(py: Python<'py>, _len: Option<usize>) -> PyResult<Self::Builder> {
+        Ok(PyList::empty_bound(py))
+    }
+
+    fn push_item(
+        builder: &mut Self::Builder,
+        key: Bound<'py, PyAny>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        builder.append((key, value))
+    }
+
+    fn push_items(
+        builder: &mut Self::Builder,
+        items: impl ExactSizeIterator<Item = (Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+    ) -> PyResult<()> {
+        extend_pairs(builder, items)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        let py = builder.py();
+        let sorted_dict = native::import(&SORTED_DICT, py, "sortedcontainers", "SortedDict")?;
+        Ok(sorted_dict.call1((builder,))?.downcast_into()?)
+    }
+}
+
+impl<'py> PythonizeTypes<'py> for PythonizeSortedDict {
+    type Map = Self;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
 /// Adapter type to use an unnamed mapping type, i.e. one that implements
 /// [`PythonizeMappingType`], as a named mapping type, i.e. one that implements
 /// [`PythonizeNamedMappingType`]. The adapter simply drops the provided name.
@@ -126,6 +495,87 @@ impl<'py, T: PythonizeMappingType<'py>> PythonizeNamedMappingType<'py>
     }
 }
 
+impl<'py, T: PythonizeNamedMappingType<'py>> PythonizeStructType<'py> for T {
+    type Builder = <T as PythonizeNamedMappingType<'py>>::Builder;
+
+    fn builder(py: Python<'py>, len: usize, name: &'static str) -> PyResult<Self::Builder> {
+        <T as PythonizeNamedMappingType>::builder(py, len, name)
+    }
+
+    fn push_field(
+        builder: &mut Self::Builder,
+        name: Bound<'py, PyString>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        <T as PythonizeNamedMappingType>::push_field(builder, name, value)
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyAny>> {
+        Ok(<T as PythonizeNamedMappingType>::finish(builder)?.into_any())
+    }
+}
+
+/// Creates instances of `dataclasses.dataclass` classes named after structs, with a
+/// field for each serialized struct field
+///
+/// A class is created for each name and set of fields the first time it is serialized,
+/// and reused from then on. Fields skipped with `skip_serializing_if` therefore result in
+/// a different class.
+pub struct PythonizeDataclass;
+
+#[doc(hidden)]
+pub struct PythonizeDataclassBuilder<'py> {
+    py: Python<'py>,
+    name: &'static str,
+    fields: Vec<Bound<'py, PyString>>,
+    values: Vec<Bound<'py, PyAny>>,
+}
+
+static MAKE_DATACLASS: GILOnceCell<PyObject> = GILOnceCell::new();
+static DATACLASS_CLASSES: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+
+impl<'py> PythonizeStructType<'py> for PythonizeDataclass {
+    type Builder = PythonizeDataclassBuilder<'py>;
+
+    fn builder(py: Python<'py>, len: usize, name: &'static str) -> PyResult<Self::Builder> {
+        Ok(PythonizeDataclassBuilder {
+            py,
+            name,
+            fields: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    fn push_field(
+        builder: &mut Self::Builder,
+        name: Bound<'py, PyString>,
+        value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        builder.fields.push(name);
+        builder.values.push(value);
+        Ok(())
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyAny>> {
+        let py = builder.py;
+        let classes = DATACLASS_CLASSES
+            .get_or_init(py, || PyDict::new_bound(py).unbind())
+            .bind(py);
+        let fields = PyTuple::new_bound(py, builder.fields);
+        let key = (builder.name, &fields);
+        let class = match classes.get_item(key)? {
+            Some(class) => class,
+            None => {
+                let class = native::import(&MAKE_DATACLASS, py, "dataclasses", "make_dataclass")?
+                    .call1((builder.name, &fields))?;
+                classes.set_item(key, &class)?;
+                class
+            }
+        };
+        class.call1(PyTuple::new_bound(py, builder.values))
+    }
+}
+
 impl PythonizeListType for PyList {
     fn create_sequence<T, U>(
         py: Python,
@@ -135,10 +585,13 @@ impl PythonizeListType for PyList {
         T: ToPyObject,
         U: ExactSizeIterator<Item = T>,
     {
-        Ok(PyList::new_bound(py, elements)
-            .into_any()
-            .downcast_into()
-            .unwrap())
+        Ok(PyList::new_bound(py, elements).into_sequence())
+    }
+
+    fn create_sequence_from_list<'py>(
+        list: Bound<'py, PyList>,
+    ) -> PyResult<Bound<'py, PySequence>> {
+        Ok(list.into_sequence())
     }
 }
 
@@ -153,6 +606,155 @@ impl PythonizeListType for PyTuple {
     {
         Ok(PyTuple::new_bound(py, elements).into_sequence())
     }
+
+    fn create_sequence_from_list<'py>(
+        list: Bound<'py, PyList>,
+    ) -> PyResult<Bound<'py, PySequence>> {
+        Ok(list.to_tuple().into_sequence())
+    }
+}
+
+impl PythonizeSetType for PySet {
+    fn create_set<T, U>(
+        py: Python,
+        elements: impl IntoIterator<Item = T, IntoIter = U>,
+    ) -> PyResult<Bound<PyAny>>
+    where
+        T: ToPyObject,
+        U: ExactSizeIterator<Item = T>,
+    {
+        let set = PySet::empty_bound(py)?;
+        for element in elements {
+            set.add(element)?;
+        }
+        Ok(set.into_any())
+    }
+}
+
+impl PythonizeSetType for PyFrozenSet {
+    fn create_set<T, U>(
+        py: Python,
+        elements: impl IntoIterator<Item = T, IntoIter = U>,
+    ) -> PyResult<Bound<PyAny>>
+    where
+        T: ToPyObject,
+        U: ExactSizeIterator<Item = T>,
+    {
+        let mut builder = PyFrozenSetBuilder::new(py)?;
+        for element in elements {
+            builder.add(element)?;
+        }
+        Ok(builder.finalize_bound().into_any())
+    }
+}
+
+impl PythonizeTupleType for PyTuple {
+    fn create_tuple<'py>(
+        py: Python<'py>,
+        _name: Option<&'static str>,
+        elements: impl ExactSizeIterator<Item = Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Ok(PyTuple::new_bound(py, elements).into_any())
+    }
+}
+
+/// Creates `collections.namedtuple` instances named after tuple structs and tuple
+/// variants, with fields `_0`, `_1`, etc. like their Rust counterparts
+///
+/// A class is created for each name and number of fields the first time it is
+/// serialized, and reused from then on. Tuples have no name, so they are still
+/// serialized as `tuple`s.
+pub struct PythonizeNamedTuple;
+
+static NAMEDTUPLE: GILOnceCell<PyObject> = GILOnceCell::new();
+static NAMEDTUPLE_CLASSES: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+
+impl PythonizeTupleType for PythonizeNamedTuple {
+    fn create_tuple<'py>(
+        py: Python<'py>,
+        name: Option<&'static str>,
+        elements: impl ExactSizeIterator<Item = Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let name = match name {
+            Some(name) => name,
+            None => return PyTuple::create_tuple(py, name, elements),
+        };
+        let classes = NAMEDTUPLE_CLASSES
+            .get_or_init(py, || PyDict::new_bound(py).unbind())
+            .bind(py);
+        let key = (name, elements.len());
+        let class = match classes.get_item(key)? {
+            Some(class) => class,
+            None => {
+                let fields: Vec<String> = (0..elements.len()).map(|i| format!("_{}", i)).collect();
+                // `rename` permits field names which start with an underscore
+                let kwargs = [("rename", true)].into_py_dict_bound(py);
+                let class = native::import(&NAMEDTUPLE, py, "collections", "namedtuple")?
+                    .call((name, fields), Some(&kwargs))?;
+                classes.set_item(key, &class)?;
+                class
+            }
+        };
+        class.call1(PyTuple::new_bound(py, elements))
+    }
+}
+
+/// Creates the name of the variant, e.g. `"Red"`
+impl PythonizeUnitVariantType for PyString {
+    fn create_unit_variant<'py>(
+        py: Python<'py>,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Ok(static_name(py, variant).into_any())
+    }
+}
+
+/// Creates the index of the variant, e.g. `0`
+impl PythonizeUnitVariantType for PyInt {
+    fn create_unit_variant<'py>(
+        py: Python<'py>,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Ok(variant_index.into_py(py).into_bound(py))
+    }
+}
+
+/// Creates a dict mapping the name of the enum to the name of the variant, e.g.
+/// `{"Color": "Red"}`
+impl PythonizeUnitVariantType for PyDict {
+    fn create_unit_variant<'py>(
+        py: Python<'py>,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item(static_name(py, name), static_name(py, variant))?;
+        Ok(dict.into_any())
+    }
+}
+
+impl PythonizeBytesType for PyBytes {
+    fn create_bytes<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        Ok(PyBytes::new_bound(py, bytes).into_any())
+    }
+}
+
+impl PythonizeBytesType for PyByteArray {
+    fn create_bytes<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        Ok(PyByteArray::new_bound(py, bytes).into_any())
+    }
+}
+
+/// Creates a read-only `memoryview` over a new `bytes` object
+impl PythonizeBytesType for PyMemoryView {
+    fn create_bytes<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        Ok(PyMemoryView::from_bound(&PyBytes::new_bound(py, bytes))?.into_any())
+    }
 }
 
 pub struct PythonizeDefault;
@@ -160,7 +762,28 @@ pub struct PythonizeDefault;
 impl<'py> PythonizeTypes<'py> for PythonizeDefault {
     type Map = PyDict;
     type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
     type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+/// Types which are immutable, so that pythonized values can be shared safely: `tuple`s
+/// for sequences, `frozenset`s for sets, and `types.MappingProxyType` for mappings and
+/// structs
+pub struct PythonizeImmutable;
+
+impl<'py> PythonizeTypes<'py> for PythonizeImmutable {
+    type Map = PythonizeMappingProxy;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PythonizeMappingProxy>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PythonizeMappingProxy>;
+    type List = PyTuple;
+    type Set = PyFrozenSet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
 }
 
 /// Attempt to convert the given data into a Python object
@@ -171,6 +794,48 @@ where
     value.serialize(Pythonizer::new(py))
 }
 
+/// Convert the fields of a struct, or the entries of a map, and write them into an
+/// existing `target`
+///
+/// They are set as items of a mapping, such as a module-level config dict, and as
+/// attributes of any other object, such as a live instance whose `__dict__` is updated in
+/// place. Items or attributes of `target` which the value doesn't have are left as they
+/// are. All of the fields are converted before any is written.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pythonize::pythonize_into;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     debug: bool,
+/// }
+///
+/// Python::with_gil(|py| {
+///     let target = py.eval_bound("{'debug': False, 'name': 'app'}", None, None).unwrap();
+///     pythonize_into(py, &Config { debug: true }, &target).unwrap();
+///     assert_eq!(target.to_string(), "{'debug': True, 'name': 'app'}");
+/// })
+/// ```
+pub fn pythonize_into<T>(py: Python<'_>, value: &T, target: &Bound<'_, PyAny>) -> Result<()>
+where
+    T: ?Sized + Serialize,
+{
+    let obj = pythonize(py, value)?;
+    let items = obj.downcast::<PyDict>()?;
+    if target.downcast::<PyMapping>().is_ok() {
+        for (key, value) in items.iter() {
+            target.set_item(key, value)?;
+        }
+    } else {
+        for (key, value) in items.iter() {
+            target.setattr(key.downcast::<PyString>()?, value)?;
+        }
+    }
+    Ok(())
+}
+
 /// Attempt to convert the given data into a Python object.
 /// Also uses custom mapping python class for serialization.
 pub fn pythonize_custom<'py, P, T>(py: Python<'py>, value: &T) -> Result<Bound<'py, PyAny>>
@@ -181,17 +846,213 @@ where
     value.serialize(Pythonizer::custom::<P>(py))
 }
 
+/// How [`Pythonizer`] treats the keys of maps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Keys are converted like any other value
+    #[default]
+    Preserve,
+    /// Keys which are not converted into a `str` are an error
+    Strict,
+    /// Keys which are not converted into a `str` are replaced by their `str()`
+    Stringify,
+}
+
+/// What [`Pythonizer`] converts unit structs into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnitStructPolicy {
+    /// `None`, like `()` and `Option::None`
+    #[default]
+    None,
+    /// An empty mapping created by [`PythonizeTypes::Map`]
+    EmptyDict,
+    /// An empty tuple created by [`PythonizeTypes::Tuple`]
+    EmptyTuple,
+    /// The only instance of a generated class named after the struct, which can be
+    /// compared with `is`
+    Sentinel,
+}
+
+static SENTINELS: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+static B64ENCODE: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// The sentinel for unit structs named `name`, created the first time it is needed
+fn unit_struct_sentinel<'py>(py: Python<'py>, name: &'static str) -> PyResult<Bound<'py, PyAny>> {
+    let sentinels = SENTINELS
+        .get_or_init(py, || PyDict::new_bound(py).unbind())
+        .bind(py);
+    if let Some(sentinel) = sentinels.get_item(name)? {
+        return Ok(sentinel);
+    }
+    let namespace = [("__slots__", PyTuple::empty_bound(py))].into_py_dict_bound(py);
+    let class = py
+        .get_type_bound::<PyType>()
+        .call1((name, PyTuple::empty_bound(py), namespace))?;
+    let sentinel = class.call0()?;
+    sentinels.set_item(name, &sentinel)?;
+    Ok(sentinel)
+}
+
+/// Whether `obj` is the sentinel for unit structs named `name`
+pub(crate) fn is_unit_struct_sentinel(obj: &Bound<'_, PyAny>, name: &str) -> PyResult<bool> {
+    let py = obj.py();
+    match SENTINELS.get(py) {
+        Some(sentinels) => Ok(sentinels
+            .bind(py)
+            .get_item(name)?
+            .map_or(false, |sentinel| sentinel.is(obj))),
+        None => Ok(false),
+    }
+}
+
+/// How [`Pythonizer`] treats NaN and infinite floats
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Converted into `float('nan')`, `float('inf')` or `float('-inf')`, unless the output
+    /// must be JSON compatible
+    #[default]
+    Float,
+    /// An error
+    Error,
+    /// Converted into `None`
+    None,
+}
+
+/// A naming convention for the fields of structs, which [`Pythonizer`] converts them into
+/// and [`Depythonizer`](crate::Depythonizer) converts them from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldCase {
+    /// `user_name`
+    Snake,
+    /// `userName`
+    Camel,
+    /// `user-name`
+    Kebab,
+}
+
+impl FieldCase {
+    /// Converts `name` into this case, splitting it into words at `_` and `-` and where
+    /// the case of its letters changes
+    pub(crate) fn apply(self, name: &str) -> String {
+        let chars: Vec<char> = name.chars().collect();
+        let mut words = Vec::new();
+        let mut word = String::new();
+        for (index, &c) in chars.iter().enumerate() {
+            if c == '_' || c == '-' {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            if c.is_uppercase() && !word.is_empty() {
+                let previous = chars[index - 1];
+                let next_lowercase = chars.get(index + 1).map_or(false, |c| c.is_lowercase());
+                // `userName` and `HTTPServer` both have two words
+                if !previous.is_uppercase() || next_lowercase {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            word.push(c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        match self {
+            FieldCase::Snake => words.join("_").to_lowercase(),
+            FieldCase::Kebab => words.join("-").to_lowercase(),
+            FieldCase::Camel => {
+                let mut camel = String::with_capacity(name.len());
+                for (index, word) in words.iter().enumerate() {
+                    let mut chars = word.chars();
+                    if index > 0 {
+                        camel.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+                    }
+                    camel.extend(chars.flat_map(char::to_lowercase));
+                }
+                camel
+            }
+        }
+    }
+}
+
+/// Options chosen at runtime with a [`PythonizerBuilder`]
+#[derive(Default)]
+struct Options<'py> {
+    keys: KeyPolicy,
+    non_finite: NonFinitePolicy,
+    unit_struct: UnitStructPolicy,
+    skip_none: bool,
+    sort_keys: bool,
+    json_compatible: bool,
+    max_depth: Option<usize>,
+    bytes_base64: bool,
+    datetime_iso: bool,
+    share_empty: bool,
+    field_case: Option<FieldCase>,
+    map_class: Option<Bound<'py, PyType>>,
+    named_map_class: Option<Bound<'py, PyType>>,
+    list_class: Option<Bound<'py, PyType>>,
+}
+
 /// A structure that serializes Rust values into Python objects
-#[derive(Clone, Copy)]
+///
+/// Clones of a `Pythonizer` share the buffers which the items of tuples, maps and structs
+/// are collected into, so serializing many values with clones of one `Pythonizer`
+/// reuses them rather than allocating new ones for every value.
 pub struct Pythonizer<'py, P> {
     py: Python<'py>,
+    options: Rc<Options<'py>>,
+    /// How deeply the value being serialized is nested in Python containers
+    depth: usize,
+    buffers: Rc<Buffers<'py>>,
     _types: PhantomData<P>,
 }
 
+/// Vecs which serializers collect items into, shared by a [`Pythonizer`] and its clones
+/// so that they are reused rather than allocated for every tuple, map or struct
+#[derive(Default)]
+struct Buffers<'py> {
+    elements: Pool<Bound<'py, PyAny>>,
+    items: Pool<(Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+    fields: Pool<(&'static str, Bound<'py, PyAny>)>,
+}
+
+/// Empty buffers which are ready to be reused
+type Pool<T> = RefCell<Vec<Vec<T>>>;
+
+/// Takes an empty buffer from `pool` with room for at least `len` items
+fn take_buffer<T>(pool: &Pool<T>, len: usize) -> Vec<T> {
+    let mut buffer = pool.borrow_mut().pop().unwrap_or_default();
+    buffer.reserve(len);
+    buffer
+}
+
+/// Returns a buffer to `pool` once its items have been taken out
+fn recycle_buffer<T>(pool: &Pool<T>, mut buffer: Vec<T>) {
+    buffer.clear();
+    pool.borrow_mut().push(buffer);
+}
+
+// not derived, which would require `P: Clone`
+impl<P> Clone for Pythonizer<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            py: self.py,
+            options: self.options.clone(),
+            depth: self.depth,
+            buffers: self.buffers.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
 impl<'py, P> From<Python<'py>> for Pythonizer<'py, P> {
     fn from(py: Python<'py>) -> Self {
         Self {
             py,
+            options: Rc::default(),
+            depth: 0,
+            buffers: Rc::default(),
             _types: PhantomData,
         }
     }
@@ -203,17 +1064,317 @@ impl<'py> Pythonizer<'py, PythonizeDefault> {
         Self::from(py)
     }
 
-    /// Creates a serializer to convert data into a Python object using a custom mapping class
-    pub fn custom<P>(py: Python<'py>) -> Pythonizer<'py, P> {
-        Pythonizer::from(py)
+    /// Creates a serializer to convert data into a Python object using a custom mapping class
+    pub fn custom<P>(py: Python<'py>) -> Pythonizer<'py, P> {
+        Pythonizer::from(py)
+    }
+
+    /// Creates a builder to configure a serializer with runtime options
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// use pyo3::prelude::*;
+    /// use pythonize::{KeyPolicy, Pythonizer};
+    /// use serde::Serialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let pythonizer = Pythonizer::builder(py).keys(KeyPolicy::Stringify).build();
+    ///     let map = BTreeMap::from([(1, "one"), (2, "two")]);
+    ///     let obj = map.serialize(pythonizer).unwrap();
+    ///     assert_eq!(obj.to_string(), "{'1': 'one', '2': 'two'}");
+    /// })
+    /// ```
+    pub fn builder(py: Python<'py>) -> PythonizerBuilder<'py, PythonizeDefault> {
+        PythonizerBuilder {
+            py,
+            options: Options::default(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<'py, P> Pythonizer<'py, P> {
+    /// Creates a serializer for the contents of a Python container, checking the
+    /// `max_depth` option
+    fn nested(&self) -> Result<Self> {
+        let depth = self.depth + 1;
+        match self.options.max_depth {
+            Some(max_depth) if depth > max_depth => Err(PythonizeError::msg(format!(
+                "maximum depth of {} exceeded",
+                max_depth
+            ))),
+            _ => Ok(Self {
+                depth,
+                ..self.clone()
+            }),
+        }
+    }
+
+    /// The Python string for the name of a struct field, in the case of the `field_case`
+    /// option
+    fn field_name(&self, name: &'static str) -> Bound<'py, PyString> {
+        cased_name(self.py, name, self.options.field_case)
+    }
+
+    /// Passes a mapping created for a map to the runtime map class, if any
+    fn finish_map(&self, map: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        match &self.options.map_class {
+            Some(class) => class.call1((map,)),
+            None => Ok(map),
+        }
+    }
+
+    /// Passes a mapping created for a struct or enum variant to the runtime named map
+    /// class, if any
+    fn finish_named_map(
+        &self,
+        name: &'static str,
+        map: Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        match &self.options.named_map_class {
+            Some(class) => class.call1((name, map)),
+            None => Ok(map),
+        }
+    }
+
+    /// Passes a sequence created for a list to the runtime list class, if any
+    fn finish_list(&self, list: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        match &self.options.list_class {
+            Some(class) => class.call1((list,)),
+            None => Ok(list),
+        }
+    }
+
+    /// Replaces a collection of `len` items with the shared empty instance of its type,
+    /// if it is empty and immutable and the `share_empty` option is set
+    fn share_empty(
+        &self,
+        collection: Bound<'py, PyAny>,
+        len: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if !self.options.share_empty || len != 0 {
+            return Ok(collection);
+        }
+        shared_empty(collection)
+    }
+}
+
+/// A builder for a [`Pythonizer`] configured with runtime options, created by
+/// [`Pythonizer::builder`]
+///
+/// The options apply to nested values as well as the value being serialized.
+pub struct PythonizerBuilder<'py, P> {
+    py: Python<'py>,
+    options: Options<'py>,
+    _types: PhantomData<P>,
+}
+
+impl<'py, P> PythonizerBuilder<'py, P> {
+    /// Use the Python types of `Q` instead of `P`
+    pub fn types<Q>(self) -> PythonizerBuilder<'py, Q> {
+        PythonizerBuilder {
+            py: self.py,
+            options: self.options,
+            _types: PhantomData,
+        }
+    }
+
+    /// Set how the keys of maps are converted, [`KeyPolicy::Preserve`] by default
+    pub fn keys(mut self, keys: KeyPolicy) -> Self {
+        self.options.keys = keys;
+        self
+    }
+
+    /// Set how NaN and infinite floats are converted, [`NonFinitePolicy::Float`] by
+    /// default
+    pub fn non_finite(mut self, non_finite: NonFinitePolicy) -> Self {
+        self.options.non_finite = non_finite;
+        self
+    }
+
+    /// Set what unit structs are converted into, [`UnitStructPolicy::None`] by default
+    ///
+    /// All of the representations are accepted when deserializing unit structs.
+    pub fn unit_struct(mut self, unit_struct: UnitStructPolicy) -> Self {
+        self.options.unit_struct = unit_struct;
+        self
+    }
+
+    /// Set whether byte arrays are converted into base64-encoded `str`s instead of the
+    /// [`PythonizeTypes::Bytes`], `false` by default
+    ///
+    /// This also lets byte arrays be serialized when the output must be JSON compatible.
+    pub fn bytes_base64(mut self, bytes_base64: bool) -> Self {
+        self.options.bytes_base64 = bytes_base64;
+        self
+    }
+
+    /// Set whether the datetimes, dates and times of the [`native`](crate::native)
+    /// adapters are converted into ISO 8601 `str`s instead of `datetime` objects, `false`
+    /// by default
+    ///
+    /// The strings are created by `isoformat()`, so time zones are kept only as offsets
+    /// from UTC. They are parsed with `fromisoformat()` when deserializing.
+    pub fn datetime_iso(mut self, datetime_iso: bool) -> Self {
+        self.options.datetime_iso = datetime_iso;
+        self
+    }
+
+    /// Set whether empty immutable collections are shared, `false` by default
+    ///
+    /// Each empty `tuple`, `frozenset`, `types.MappingProxyType`, `frozendict` or
+    /// `immutables.Map` is replaced with one instance of its type, created the first time
+    /// it is needed, rather than creating a new one for every empty value. This suits the
+    /// [`PythonizeImmutable`] and [`PythonizeFrozenDict`] presets; mutable collections
+    /// such as `list`s and `dict`s are never shared.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{PythonizeImmutable, Pythonizer};
+    /// use serde::Serialize;
+    ///
+    /// Python::with_gil(|py| {
+    ///     let pythonizer = Pythonizer::builder(py)
+    ///         .types::<PythonizeImmutable>()
+    ///         .share_empty(true)
+    ///         .build();
+    ///     let empty: Vec<Vec<u8>> = vec![vec![], vec![]];
+    ///     let obj = empty.serialize(pythonizer).unwrap();
+    ///     assert!(obj.get_item(0).unwrap().is(&obj.get_item(1).unwrap()));
+    /// })
+    /// ```
+    pub fn share_empty(mut self, share_empty: bool) -> Self {
+        self.options.share_empty = share_empty;
+        self
+    }
+
+    /// Set the naming convention which the fields of structs are converted into, by
+    /// default none
+    ///
+    /// Field names are split into words at `_` and `-` and where the case of their
+    /// letters changes, so the names of fields renamed with serde attributes are
+    /// converted as well. Names are converted once and reused from then on.
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pythonize::{FieldCase, Pythonizer};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     user_name: String,
+    /// }
+    ///
+    /// Python::with_gil(|py| {
+    ///     let pythonizer = Pythonizer::builder(py).field_case(FieldCase::Camel).build();
+    ///     let user = User {
+    ///         user_name: "alice".to_string(),
+    ///     };
+    ///     let obj = user.serialize(pythonizer).unwrap();
+    ///     assert_eq!(obj.to_string(), "{'userName': 'alice'}");
+    /// })
+    /// ```
+    pub fn field_case(mut self, case: FieldCase) -> Self {
+        self.options.field_case = Some(case);
+        self
+    }
+
+    /// Set whether struct fields which are converted into `None` are left out, like
+    /// `exclude_none=True` in pydantic, `false` by default
+    ///
+    /// This saves annotating every `Option` field with `#[serde(skip_serializing_if)]`.
+    pub fn skip_none(mut self, skip_none: bool) -> Self {
+        self.options.skip_none = skip_none;
+        self
+    }
+
+    /// Set whether the keys of maps and the fields of structs are inserted in sorted
+    /// order, like `sort_keys=True` in `json.dumps`, `false` by default
+    ///
+    /// This makes the output deterministic, e.g. for `HashMap`s. Map keys are compared
+    /// after conversion by Python, so they must be of comparable types.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.options.sort_keys = sort_keys;
+        self
+    }
+
+    /// Set whether the output is restricted to what `json.dumps` accepts, `false` by
+    /// default
+    ///
+    /// Map keys are converted into `str`s, with `null`, `true` and `false` for `None` and
+    /// booleans like `json.dumps`. Tuples and sets become lists, values of the
+    /// [`native`](crate::native) adapters keep their serde representation, and bytes are
+    /// an error unless [`bytes_base64`](Self::bytes_base64) is set, as are non-finite
+    /// floats unless [`NonFinitePolicy::None`] is set. Unit struct sentinels become
+    /// `None`. The [`PythonizeTypes`] must also create types which `json.dumps` accepts,
+    /// as [`PythonizeDefault`] does.
+    pub fn json_compatible(mut self, json_compatible: bool) -> Self {
+        self.options.json_compatible = json_compatible;
+        self
+    }
+
+    /// Set how deeply Python containers such as lists, dicts and structs may be nested,
+    /// unlimited by default
+    ///
+    /// Deeper values are an error, which guards against pathological object graphs and
+    /// running out of stack. Scalars can still be serialized with a maximum depth of 0.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Set a Python class to create maps with, which is called with the mapping created
+    /// by [`PythonizeTypes::Map`]
+    ///
+    /// This lets the callers of a Python extension choose the class, where
+    /// `PythonizeTypes` would have to be implemented in Rust.
+    pub fn map_class(mut self, class: &Bound<'py, PyType>) -> Self {
+        self.options.map_class = Some(class.clone());
+        self
+    }
+
+    /// Set a Python class to create structs and enum variants with, which is called with
+    /// the name of the struct or enum and the mapping created by [`PythonizeTypes::Struct`]
+    /// or [`PythonizeTypes::NamedMap`]
+    pub fn named_map_class(mut self, class: &Bound<'py, PyType>) -> Self {
+        self.options.named_map_class = Some(class.clone());
+        self
+    }
+
+    /// Set a Python class to create sequences with, which is called with the sequence
+    /// created by [`PythonizeTypes::List`]
+    pub fn list_class(mut self, class: &Bound<'py, PyType>) -> Self {
+        self.options.list_class = Some(class.clone());
+        self
+    }
+
+    /// Creates the configured serializer
+    pub fn build(self) -> Pythonizer<'py, P> {
+        Pythonizer {
+            py: self.py,
+            options: Rc::new(self.options),
+            depth: 0,
+            buffers: Rc::default(),
+            _types: PhantomData,
+        }
     }
 }
 
+#[doc(hidden)]
+pub struct PythonListSerializer<'py, P> {
+    /// The list of elements, created with the length hint and filled in place
+    list: Bound<'py, PyList>,
+    /// The number of elements serialized so far
+    len: usize,
+    pythonizer: Pythonizer<'py, P>,
+}
+
 #[doc(hidden)]
 pub struct PythonCollectionSerializer<'py, P> {
+    name: Option<&'static str>,
     items: Vec<Bound<'py, PyAny>>,
-    py: Python<'py>,
-    _types: PhantomData<P>,
+    pythonizer: Pythonizer<'py, P>,
 }
 
 #[doc(hidden)]
@@ -232,23 +1393,27 @@ pub struct PythonStructVariantSerializer<'py, P: PythonizeTypes<'py>> {
 
 #[doc(hidden)]
 pub struct PythonStructDictSerializer<'py, P: PythonizeTypes<'py>> {
-    py: Python<'py>,
-    builder: <P::NamedMap as PythonizeNamedMappingType<'py>>::Builder,
-    _types: PhantomData<P>,
+    name: &'static str,
+    pythonizer: Pythonizer<'py, P>,
+    builder: <P::Struct as PythonizeStructType<'py>>::Builder,
+    /// Fields held back to be sorted, with the `sort_keys` option
+    fields: Vec<(&'static str, Bound<'py, PyAny>)>,
 }
 
 #[doc(hidden)]
 pub struct PythonMapSerializer<'py, P: PythonizeTypes<'py>> {
-    py: Python<'py>,
+    pythonizer: Pythonizer<'py, P>,
     builder: <P::Map as PythonizeMappingType<'py>>::Builder,
     key: Option<Bound<'py, PyAny>>,
-    _types: PhantomData<P>,
+    /// Items collected to be added to the builder in one go, sorted with the `sort_keys`
+    /// option
+    items: Vec<(Bound<'py, PyAny>, Bound<'py, PyAny>)>,
 }
 
 impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
     type Ok = Bound<'py, PyAny>;
     type Error = PythonizeError;
-    type SerializeSeq = PythonCollectionSerializer<'py, P>;
+    type SerializeSeq = PythonListSerializer<'py, P>;
     type SerializeTuple = PythonCollectionSerializer<'py, P>;
     type SerializeTupleStruct = PythonCollectionSerializer<'py, P>;
     type SerializeTupleVariant = PythonTupleVariantSerializer<'py, P>;
@@ -276,6 +1441,10 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         Ok(v.into_py(self.py).into_bound(self.py))
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Bound<'py, PyAny>> {
+        Ok(v.into_py(self.py).into_bound(self.py))
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Bound<'py, PyAny>> {
         Ok(v.into_py(self.py).into_bound(self.py))
     }
@@ -292,11 +1461,30 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         Ok(v.into_py(self.py).into_bound(self.py))
     }
 
-    fn serialize_f32(self, v: f32) -> Result<Bound<'py, PyAny>> {
+    fn serialize_u128(self, v: u128) -> Result<Bound<'py, PyAny>> {
         Ok(v.into_py(self.py).into_bound(self.py))
     }
 
+    fn serialize_f32(self, v: f32) -> Result<Bound<'py, PyAny>> {
+        self.serialize_f64(f64::from(v))
+    }
+
     fn serialize_f64(self, v: f64) -> Result<Bound<'py, PyAny>> {
+        if !v.is_finite() {
+            match self.options.non_finite {
+                NonFinitePolicy::Float if !self.options.json_compatible => {}
+                NonFinitePolicy::Float => {
+                    return Err(PythonizeError::msg(format!(
+                        "float {} is not JSON compatible",
+                        v
+                    )))
+                }
+                NonFinitePolicy::Error => {
+                    return Err(PythonizeError::msg(format!("float {} is not finite", v)))
+                }
+                NonFinitePolicy::None => return self.serialize_none(),
+            }
+        }
         Ok(v.into_py(self.py).into_bound(self.py))
     }
 
@@ -309,7 +1497,15 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Bound<'py, PyAny>> {
-        Ok(v.into_py(self.py).into_bound(self.py))
+        if self.options.bytes_base64 {
+            let encoded = native::import(&B64ENCODE, self.py, "base64", "b64encode")?
+                .call1((PyBytes::new_bound(self.py, v),))?;
+            return Ok(encoded.call_method1("decode", ("ascii",))?);
+        }
+        if self.options.json_compatible {
+            return Err(PythonizeError::unsupported_type("bytes"));
+        }
+        Ok(P::Bytes::create_bytes(self.py, v)?)
     }
 
     fn serialize_none(self) -> Result<Bound<'py, PyAny>> {
@@ -327,28 +1523,91 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         self.serialize_none()
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Bound<'py, PyAny>> {
-        self.serialize_none()
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Bound<'py, PyAny>> {
+        match self.options.unit_struct {
+            UnitStructPolicy::None => self.serialize_none(),
+            UnitStructPolicy::EmptyDict => {
+                let map = P::Map::finish(P::Map::builder(self.py, Some(0))?)?;
+                Ok(self.finish_map(map.into_any())?)
+            }
+            UnitStructPolicy::EmptyTuple => {
+                ser::SerializeTupleStruct::end(self.serialize_tuple_struct(name, 0)?)
+            }
+            UnitStructPolicy::Sentinel if self.options.json_compatible => self.serialize_none(),
+            UnitStructPolicy::Sentinel => Ok(unit_struct_sentinel(self.py, name)?),
+        }
     }
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<Bound<'py, PyAny>> {
-        self.serialize_str(variant)
+        Ok(P::UnitVariant::create_unit_variant(
+            self.py,
+            name,
+            variant_index,
+            variant,
+        )?)
     }
 
-    fn serialize_newtype_struct<T>(
-        self,
-        _name: &'static str,
-        value: &T,
-    ) -> Result<Bound<'py, PyAny>>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Bound<'py, PyAny>>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        let py = self.py;
+        let kind = native::Kind::from_token(name);
+        if let Some(kind) = kind.filter(|kind| self.options.datetime_iso && kind.is_datetime()) {
+            // the payload of the adapter is plain data, whatever the `PythonizeTypes` are
+            let obj = kind.create(&value.serialize(Pythonizer::new(py))?)?;
+            return Ok(obj.call_method0("isoformat")?);
+        }
+        if self.options.json_compatible {
+            // values of the `native` adapters are left as their serde representation
+            return value.serialize(self);
+        }
+        if name == native::SET_TOKEN {
+            // the items are only copied into a list if `P::List` is not one already
+            let items = match value.serialize(self.clone())?.downcast_into::<PyList>() {
+                Ok(items) => items,
+                Err(err) => err.into_inner().downcast_into::<PySequence>()?.to_list()?,
+            };
+            let set = P::Set::create_set(py, items.iter())?;
+            return Ok(self.share_empty(set, items.len())?);
+        }
+        if name == native::PACKED_TOKEN {
+            if let Some(elements) = native::packed::take_elements() {
+                if let Some(bytes) = elements.as_bytes() {
+                    return self.serialize_bytes(bytes);
+                }
+                // otherwise the elements go through `serialize_f64` to apply the policy
+                if self.options.non_finite == NonFinitePolicy::Float || elements.all_finite() {
+                    let pythonizer = self.nested()?;
+                    let list = elements.to_list(py);
+                    let len = list.len();
+                    let sequence = P::List::create_sequence_from_list(list)?.into_any();
+                    let sequence = pythonizer.finish_list(sequence)?;
+                    return Ok(pythonizer.share_empty(sequence, len)?);
+                }
+            }
+            return value.serialize(self);
+        }
+        #[cfg(feature = "polars")]
+        if name == native::DATAFRAME_TOKEN || name == native::SERIES_TOKEN {
+            if let Some(frame) = native::polars::take_frame(py, name)? {
+                return Ok(frame);
+            }
+        }
+        if name == native::ENUM_TOKEN {
+            // members are looked up by variant name, whatever `P::UnitVariant` is
+            let variant = value.serialize(Pythonizer::new(py))?;
+            return Ok(native::enum_member::enum_member_to_python(&variant)?);
+        }
+        match kind {
+            Some(kind) => Ok(kind.create(&value.serialize(self)?)?),
+            None => value.serialize(self),
+        }
     }
 
     fn serialize_newtype_variant<T>(
@@ -361,41 +1620,46 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
     where
         T: ?Sized + Serialize,
     {
-        let mut m = P::NamedMap::builder(self.py, 1, name)?;
-        P::NamedMap::push_field(
+        let mut m = <P::NamedMap as PythonizeNamedMappingType>::builder(self.py, 1, name)?;
+        <P::NamedMap as PythonizeNamedMappingType>::push_field(
             &mut m,
-            PyString::new_bound(self.py, variant),
-            value.serialize(self)?,
+            static_name(self.py, variant),
+            value.serialize(self.nested()?)?,
         )?;
-        Ok(P::NamedMap::finish(m)?.into_any())
-    }
-
-    fn serialize_seq(self, len: Option<usize>) -> Result<PythonCollectionSerializer<'py, P>> {
-        let items = match len {
-            Some(len) => Vec::with_capacity(len),
-            None => Vec::new(),
-        };
-        Ok(PythonCollectionSerializer {
-            items,
-            py: self.py,
-            _types: PhantomData,
+        let v = <P::NamedMap as PythonizeNamedMappingType>::finish(m)?.into_any();
+        Ok(self.finish_named_map(name, v)?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<PythonListSerializer<'py, P>> {
+        // the slots are filled with `None` rather than left empty, because Python code
+        // may run while the elements are serialized; elements beyond the capped hint
+        // are appended
+        let len = len.map_or(0, |len| len.min(MAX_PREALLOCATED_ELEMENTS));
+        let list = PyList::new_bound(self.py, (0..len).map(|_| self.py.None()));
+        Ok(PythonListSerializer {
+            list,
+            len: 0,
+            pythonizer: self.nested()?,
         })
     }
 
     fn serialize_tuple(self, len: usize) -> Result<PythonCollectionSerializer<'py, P>> {
+        let pythonizer = self.nested()?;
         Ok(PythonCollectionSerializer {
-            items: Vec::with_capacity(len),
-            py: self.py,
-            _types: PhantomData,
+            name: None,
+            items: take_buffer(&pythonizer.buffers.elements, len),
+            pythonizer,
         })
     }
 
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
     ) -> Result<PythonCollectionSerializer<'py, P>> {
-        self.serialize_tuple(len)
+        let mut inner = self.serialize_tuple(len)?;
+        inner.name = Some(name);
+        Ok(inner)
     }
 
     fn serialize_tuple_variant(
@@ -405,7 +1669,8 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         variant: &'static str,
         len: usize,
     ) -> Result<PythonTupleVariantSerializer<'py, P>> {
-        let inner = self.serialize_tuple(len)?;
+        let mut inner = self.nested()?.serialize_tuple(len)?;
+        inner.name = Some(variant);
         Ok(PythonTupleVariantSerializer {
             name,
             variant,
@@ -414,11 +1679,12 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<PythonMapSerializer<'py, P>> {
+        let pythonizer = self.nested()?;
         Ok(PythonMapSerializer {
             builder: P::Map::builder(self.py, len)?,
             key: None,
-            py: self.py,
-            _types: PhantomData,
+            items: take_buffer(&pythonizer.buffers.items, len.unwrap_or(0)),
+            pythonizer,
         })
     }
 
@@ -427,10 +1693,12 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         name: &'static str,
         len: usize,
     ) -> Result<PythonStructDictSerializer<'py, P>> {
+        let pythonizer = self.nested()?;
         Ok(PythonStructDictSerializer {
-            py: self.py,
-            builder: P::NamedMap::builder(self.py, len, name)?,
-            _types: PhantomData,
+            name,
+            builder: P::Struct::builder(self.py, len, name)?,
+            fields: take_buffer(&pythonizer.buffers.fields, 0),
+            pythonizer,
         })
     }
 
@@ -441,19 +1709,21 @@ impl<'py, P: PythonizeTypes<'py>> ser::Serializer for Pythonizer<'py, P> {
         variant: &'static str,
         len: usize,
     ) -> Result<PythonStructVariantSerializer<'py, P>> {
+        let pythonizer = self.nested()?.nested()?;
         Ok(PythonStructVariantSerializer {
             name,
             variant,
             inner: PythonStructDictSerializer {
-                py: self.py,
-                builder: P::NamedMap::builder(self.py, len, variant)?,
-                _types: PhantomData,
+                name: variant,
+                builder: P::Struct::builder(self.py, len, variant)?,
+                fields: take_buffer(&pythonizer.buffers.fields, 0),
+                pythonizer,
             },
         })
     }
 }
 
-impl<'py, P: PythonizeTypes<'py>> ser::SerializeSeq for PythonCollectionSerializer<'py, P> {
+impl<'py, P: PythonizeTypes<'py>> ser::SerializeSeq for PythonListSerializer<'py, P> {
     type Ok = Bound<'py, PyAny>;
     type Error = PythonizeError;
 
@@ -461,13 +1731,24 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeSeq for PythonCollectionSerializ
     where
         T: ?Sized + Serialize,
     {
-        self.items.push(pythonize_custom::<P, _>(self.py, value)?);
+        let element = value.serialize(self.pythonizer.clone())?;
+        if self.len < self.list.len() {
+            self.list.set_item(self.len, element)?;
+        } else {
+            self.list.append(element)?;
+        }
+        self.len += 1;
         Ok(())
     }
 
     fn end(self) -> Result<Bound<'py, PyAny>> {
-        let instance = P::List::create_sequence(self.py, self.items)?;
-        Ok(instance.to_object(self.py).into_bound(self.py))
+        // the length hint may have been too long
+        if self.len < self.list.len() {
+            self.list.del_slice(self.len, self.list.len())?;
+        }
+        let instance = P::List::create_sequence_from_list(self.list)?;
+        let list = self.pythonizer.finish_list(instance.into_any())?;
+        Ok(self.pythonizer.share_empty(list, self.len)?)
     }
 }
 
@@ -479,11 +1760,20 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeTuple for PythonCollectionSerial
     where
         T: ?Sized + Serialize,
     {
-        ser::SerializeSeq::serialize_element(self, value)
+        self.items.push(value.serialize(self.pythonizer.clone())?);
+        Ok(())
     }
 
-    fn end(self) -> Result<Bound<'py, PyAny>> {
-        Ok(PyTuple::new_bound(self.py, self.items).into_any())
+    fn end(mut self) -> Result<Bound<'py, PyAny>> {
+        let py = self.pythonizer.py;
+        let len = self.items.len();
+        let tuple = if self.pythonizer.options.json_compatible {
+            PyList::new_bound(py, self.items.drain(..)).into_any()
+        } else {
+            P::Tuple::create_tuple(py, self.name, self.items.drain(..))?
+        };
+        recycle_buffer(&self.pythonizer.buffers.elements, self.items);
+        Ok(self.pythonizer.share_empty(tuple, len)?)
     }
 }
 
@@ -495,7 +1785,7 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeTupleStruct for PythonCollection
     where
         T: ?Sized + Serialize,
     {
-        ser::SerializeSeq::serialize_element(self, value)
+        ser::SerializeTuple::serialize_element(self, value)
     }
 
     fn end(self) -> Result<Bound<'py, PyAny>> {
@@ -513,17 +1803,20 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeTupleVariant
     where
         T: ?Sized + Serialize,
     {
-        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+        ser::SerializeTuple::serialize_element(&mut self.inner, value)
     }
 
     fn end(self) -> Result<Bound<'py, PyAny>> {
-        let mut m = P::NamedMap::builder(self.inner.py, 1, self.name)?;
-        P::NamedMap::push_field(
+        let pythonizer = self.inner.pythonizer.clone();
+        let mut m =
+            <P::NamedMap as PythonizeNamedMappingType>::builder(pythonizer.py, 1, self.name)?;
+        <P::NamedMap as PythonizeNamedMappingType>::push_field(
             &mut m,
-            PyString::new_bound(self.inner.py, self.variant),
+            static_name(pythonizer.py, self.variant),
             ser::SerializeTuple::end(self.inner)?,
         )?;
-        Ok(P::NamedMap::finish(m)?.into_any())
+        let v = <P::NamedMap as PythonizeNamedMappingType>::finish(m)?.into_any();
+        Ok(pythonizer.finish_named_map(self.name, v)?)
     }
 }
 
@@ -535,7 +1828,17 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeMap for PythonMapSerializer<'py,
     where
         T: ?Sized + Serialize,
     {
-        self.key = Some(pythonize_custom::<P, _>(self.py, key)?);
+        let key = key.serialize(self.pythonizer.clone())?;
+        if self.pythonizer.options.json_compatible {
+            self.key = Some(json_key(key)?);
+            return Ok(());
+        }
+        self.key = Some(match self.pythonizer.options.keys {
+            KeyPolicy::Preserve => key,
+            _ if key.is_instance_of::<PyString>() => key,
+            KeyPolicy::Strict => return Err(PythonizeError::dict_key_not_string()),
+            KeyPolicy::Stringify => key.str()?.into_any(),
+        });
         Ok(())
     }
 
@@ -543,18 +1846,123 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeMap for PythonMapSerializer<'py,
     where
         T: ?Sized + Serialize,
     {
-        P::Map::push_item(
-            &mut self.builder,
-            self.key
-                .take()
-                .expect("serialize_value should always be called after serialize_key"),
-            pythonize_custom::<P, _>(self.py, value)?,
-        )?;
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value should always be called after serialize_key");
+        let value = value.serialize(self.pythonizer.clone())?;
+        self.items.push((key, value));
         Ok(())
     }
 
-    fn end(self) -> Result<Bound<'py, PyAny>> {
-        Ok(P::Map::finish(self.builder)?.into_any())
+    fn end(mut self) -> Result<Bound<'py, PyAny>> {
+        if self.pythonizer.options.sort_keys {
+            sort_items(self.pythonizer.py, &mut self.items)?;
+        }
+        let len = self.items.len();
+        P::Map::push_items(&mut self.builder, self.items.drain(..))?;
+        recycle_buffer(&self.pythonizer.buffers.items, self.items);
+        let map = P::Map::finish(self.builder)?.into_any();
+        let map = self.pythonizer.finish_map(map)?;
+        Ok(self.pythonizer.share_empty(map, len)?)
+    }
+}
+
+/// Sorts map items by key with Python's own `list.sort`, since keys of different types
+/// may not be comparable and a fallible comparison cannot be given to `sort_by`
+fn sort_items<'py>(
+    py: Python<'py>,
+    items: &mut Vec<(Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+) -> PyResult<()> {
+    let keys = PyList::new_bound(py, items.iter().map(|(key, _)| key));
+    let order = PyList::new_bound(py, 0..items.len());
+    let kwargs = [("key", keys.getattr("__getitem__")?)].into_py_dict_bound(py);
+    order.call_method("sort", (), Some(&kwargs))?;
+    let mut unsorted: Vec<_> = items.drain(..).map(Some).collect();
+    for index in order.iter() {
+        let item = unsorted[index.extract::<usize>()?].take();
+        items.extend(item);
+    }
+    Ok(())
+}
+
+/// Python strings for the `&'static str` names of fields and enum variants, keyed by
+/// address and length
+type Names = HashMap<(usize, usize, Option<FieldCase>), Py<PyString>>;
+
+static NAMES: GILOnceCell<Mutex<Names>> = GILOnceCell::new();
+
+/// Creates an interned Python string for `name` once, and reuses it from then on, so
+/// that serializing many structs or enums does not create as many identical strings
+fn static_name<'py>(py: Python<'py>, name: &'static str) -> Bound<'py, PyString> {
+    cased_name(py, name, None)
+}
+
+/// Like [`static_name`], for `name` converted into `case`, if any
+fn cased_name<'py>(
+    py: Python<'py>,
+    name: &'static str,
+    case: Option<FieldCase>,
+) -> Bound<'py, PyString> {
+    let names = NAMES.get_or_init(py, Default::default);
+    let lock = || names.lock().unwrap_or_else(PoisonError::into_inner);
+    let key = (name.as_ptr() as usize, name.len(), case);
+    if let Some(cached) = lock().get(&key) {
+        return cached.bind(py).clone();
+    }
+    let interned = match case {
+        Some(case) => PyString::intern_bound(py, &case.apply(name)),
+        None => PyString::intern_bound(py, name),
+    };
+    lock().insert(key, interned.clone().unbind());
+    interned
+}
+
+/// The shared empty instances of immutable collection types, keyed by the address of the
+/// type
+static EMPTY: GILOnceCell<Mutex<HashMap<usize, PyObject>>> = GILOnceCell::new();
+
+/// The shared empty instance of the type of `empty`, which becomes the shared instance if
+/// there is none yet, or `empty` itself if its type is mutable or unknown
+fn shared_empty(empty: Bound<'_, PyAny>) -> PyResult<Bound<'_, PyAny>> {
+    let py = empty.py();
+    let class = empty.get_type();
+    // only exact types are shared, as subclasses may be mutable
+    let immutable = class.is(&py.get_type_bound::<PyTuple>())
+        || class.is(&py.get_type_bound::<PyFrozenSet>())
+        || class.is(native::import(
+            &MAPPING_PROXY,
+            py,
+            "types",
+            "MappingProxyType",
+        )?)
+        || [&FROZENDICT, &IMMUTABLES_MAP]
+            .iter()
+            .any(|cell| cell.get(py).map_or(false, |imported| class.is(imported)));
+    if !immutable {
+        return Ok(empty);
+    }
+    let shared = EMPTY.get_or_init(py, Default::default);
+    let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+    let instance = shared
+        .entry(class.as_ptr() as usize)
+        .or_insert_with(|| empty.unbind());
+    Ok(instance.bind(py).clone())
+}
+
+/// Coerces a map key into a `str` the way `json.dumps` does, or with `str()` for keys
+/// which `json.dumps` rejects
+fn json_key(key: Bound<'_, PyAny>) -> PyResult<Bound<'_, PyAny>> {
+    let py = key.py();
+    if key.is_instance_of::<PyString>() {
+        Ok(key)
+    } else if key.is_none() {
+        Ok(PyString::new_bound(py, "null").into_any())
+    } else if let Ok(key) = key.downcast::<PyBool>() {
+        let key = if key.is_true() { "true" } else { "false" };
+        Ok(PyString::new_bound(py, key).into_any())
+    } else {
+        Ok(key.str()?.into_any())
     }
 }
 
@@ -566,16 +1974,29 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeStruct for PythonStructDictSeria
     where
         T: ?Sized + Serialize,
     {
-        P::NamedMap::push_field(
-            &mut self.builder,
-            PyString::new_bound(self.py, key),
-            pythonize_custom::<P, _>(self.py, value)?,
-        )?;
+        let value = value.serialize(self.pythonizer.clone())?;
+        if self.pythonizer.options.skip_none && value.is_none() {
+            return Ok(());
+        }
+        if self.pythonizer.options.sort_keys {
+            self.fields.push((key, value));
+            return Ok(());
+        }
+        P::Struct::push_field(&mut self.builder, self.pythonizer.field_name(key), value)?;
         Ok(())
     }
 
-    fn end(self) -> Result<Bound<'py, PyAny>> {
-        Ok(P::NamedMap::finish(self.builder)?.into_any())
+    fn end(mut self) -> Result<Bound<'py, PyAny>> {
+        match self.pythonizer.options.field_case {
+            Some(case) => self.fields.sort_by_cached_key(|&(key, _)| case.apply(key)),
+            None => self.fields.sort_by_key(|&(key, _)| key),
+        }
+        for (key, value) in self.fields.drain(..) {
+            P::Struct::push_field(&mut self.builder, self.pythonizer.field_name(key), value)?;
+        }
+        recycle_buffer(&self.pythonizer.buffers.fields, self.fields);
+        let v = P::Struct::finish(self.builder)?;
+        Ok(self.pythonizer.finish_named_map(self.name, v)?)
     }
 }
 
@@ -589,34 +2010,38 @@ impl<'py, P: PythonizeTypes<'py>> ser::SerializeStructVariant
     where
         T: ?Sized + Serialize,
     {
-        P::NamedMap::push_field(
-            &mut self.inner.builder,
-            PyString::new_bound(self.inner.py, key),
-            pythonize_custom::<P, _>(self.inner.py, value)?,
-        )?;
-        Ok(())
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
     }
 
     fn end(self) -> Result<Bound<'py, PyAny>> {
-        let v = P::NamedMap::finish(self.inner.builder)?;
-        let mut m = P::NamedMap::builder(self.inner.py, 1, self.name)?;
-        P::NamedMap::push_field(
+        let pythonizer = self.inner.pythonizer.clone();
+        let v = ser::SerializeStruct::end(self.inner)?;
+        let mut m =
+            <P::NamedMap as PythonizeNamedMappingType>::builder(pythonizer.py, 1, self.name)?;
+        <P::NamedMap as PythonizeNamedMappingType>::push_field(
             &mut m,
-            PyString::new_bound(self.inner.py, self.variant),
-            v.into_any(),
+            static_name(pythonizer.py, self.variant),
+            v,
         )?;
-        Ok(P::NamedMap::finish(m)?.into_any())
+        let v = <P::NamedMap as PythonizeNamedMappingType>::finish(m)?.into_any();
+        Ok(pythonizer.finish_named_map(self.name, v)?)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::pythonize;
-    use maplit::hashmap;
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use super::{
+        pythonize, pythonize_into, FieldCase, KeyPolicy, NonFinitePolicy, Pythonizer,
+        UnitStructPolicy,
+    };
+    use crate::{depythonize, PythonizeImmutable, PythonizeMappingType};
+    use maplit::{btreemap, hashmap};
     use pyo3::prelude::*;
     use pyo3::pybacked::PyBackedStr;
-    use pyo3::types::{PyBytes, PyDict};
-    use serde::Serialize;
+    use pyo3::types::{PyBytes, PyDict, PyList};
+    use serde::{Deserialize, Serialize};
 
     fn test_ser<T>(src: T, expected: &str)
     where
@@ -791,6 +2216,25 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_128bit_integers() {
+        #[derive(Serialize)]
+        struct Integers {
+            a: i128,
+            b: i128,
+            c: u128,
+        }
+
+        test_ser(
+            Integers {
+                a: i128::MIN,
+                b: 1,
+                c: u128::MAX,
+            },
+            r#"{"a":-170141183460469231731687303715884105728,"b":1,"c":340282366920938463463374607431768211455}"#,
+        )
+    }
+
     #[test]
     fn test_floats() {
         #[derive(Serialize)]
@@ -838,8 +2282,648 @@ mod test {
         Python::with_gil(|py| {
             assert!(pythonize(py, serde_bytes::Bytes::new(b"foo"))
                 .expect("bytes will always serialize successfully")
-                .eq(&PyBytes::new_bound(py, b"foo"))
+                .eq(PyBytes::new_bound(py, b"foo"))
                 .expect("bytes will always compare successfully"));
         });
     }
+
+    #[test]
+    fn test_key_policy() {
+        Python::with_gil(|py| {
+            // keys are nested in a list to check that options reach nested values
+            let value = vec![btreemap! { (1, 2) => "a" }, btreemap! { (3, 4) => "b" }];
+
+            let obj = value.serialize(Pythonizer::new(py)).unwrap();
+            assert_eq!(obj.to_string(), "[{(1, 2): 'a'}, {(3, 4): 'b'}]");
+
+            let pythonizer = Pythonizer::builder(py)
+                .keys(KeyPolicy::Stringify)
+                .types::<PythonizeImmutable>()
+                .build();
+            let obj = value.serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "(mappingproxy({'(1, 2)': 'a'}), mappingproxy({'(3, 4)': 'b'}))"
+            );
+
+            let pythonizer = Pythonizer::builder(py).keys(KeyPolicy::Strict).build();
+            let err = value.serialize(pythonizer.clone()).unwrap_err();
+            assert_eq!(err.to_string(), "dict keys must have type str");
+            let obj = btreemap! { "a" => 1 }.serialize(pythonizer).unwrap();
+            assert_eq!(obj.to_string(), "{'a': 1}");
+        });
+    }
+
+    #[test]
+    fn test_skip_none() {
+        #[derive(Serialize)]
+        enum Event {
+            Login { user: Option<&'static str> },
+        }
+
+        #[derive(Serialize)]
+        struct Record {
+            id: u32,
+            note: Option<&'static str>,
+            tags: Vec<Option<u32>>,
+            unit: (),
+            event: Event,
+        }
+
+        Python::with_gil(|py| {
+            let record = Record {
+                id: 1,
+                note: None,
+                tags: vec![None, Some(2)],
+                unit: (),
+                event: Event::Login { user: None },
+            };
+
+            let obj = record.serialize(Pythonizer::new(py)).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'id': 1, 'note': None, 'tags': [None, 2], 'unit': None, 'event': {'Login': {'user': None}}}"
+            );
+
+            // only struct fields are skipped, not items of sequences
+            let pythonizer = Pythonizer::builder(py).skip_none(true).build();
+            let obj = record.serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'id': 1, 'tags': [None, 2], 'event': {'Login': {}}}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_json_compatible() {
+        #[derive(Serialize)]
+        struct Point(i32, i32);
+
+        #[derive(Serialize)]
+        struct Survey {
+            counts: BTreeMap<Option<bool>, u32>,
+            grid: BTreeMap<(u8, u8), Point>,
+            #[serde(with = "crate::native")]
+            ids: BTreeSet<u32>,
+            score: f64,
+        }
+
+        Python::with_gil(|py| {
+            let survey = Survey {
+                counts: btreemap! { None => 0, Some(false) => 1, Some(true) => 2 },
+                grid: btreemap! { (0, 1) => Point(2, 3) },
+                ids: BTreeSet::from([7, 5]),
+                score: 0.5,
+            };
+            let pythonizer = Pythonizer::builder(py).json_compatible(true).build();
+            let obj = survey.serialize(pythonizer.clone()).unwrap();
+
+            let locals = PyDict::new_bound(py);
+            locals.set_item("obj", obj).unwrap();
+            py.run_bound(
+                "import json; result = json.dumps(obj, separators=(',', ':'), allow_nan=False)",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let result = locals.get_item("result").unwrap().unwrap();
+            assert_eq!(
+                result.to_string(),
+                r#"{"counts":{"null":0,"false":1,"true":2},"grid":{"[0, 1]":[2,3]},"ids":[5,7],"score":0.5}"#
+            );
+
+            let err = f64::NAN.serialize(pythonizer.clone()).unwrap_err();
+            assert_eq!(err.to_string(), "float NaN is not JSON compatible");
+            let bytes = serde_bytes::Bytes::new(b"foo");
+            let err = bytes.serialize(pythonizer).unwrap_err();
+            assert_eq!(err.to_string(), "unsupported type bytes");
+        });
+    }
+
+    #[test]
+    fn test_non_finite() {
+        Python::with_gil(|py| {
+            let values = vec![1.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+
+            let obj = values.serialize(Pythonizer::new(py)).unwrap();
+            assert_eq!(obj.to_string(), "[1.5, nan, inf, -inf]");
+
+            let pythonizer = Pythonizer::builder(py)
+                .non_finite(NonFinitePolicy::Error)
+                .build();
+            let err = values.serialize(pythonizer.clone()).unwrap_err();
+            assert_eq!(err.to_string(), "float NaN is not finite");
+            let err = f32::INFINITY.serialize(pythonizer).unwrap_err();
+            assert_eq!(err.to_string(), "float inf is not finite");
+
+            let pythonizer = Pythonizer::builder(py)
+                .non_finite(NonFinitePolicy::None)
+                .json_compatible(true)
+                .build();
+            let obj = values.serialize(pythonizer).unwrap();
+            assert_eq!(obj.to_string(), "[1.5, None, None, None]");
+        });
+    }
+
+    #[test]
+    fn test_max_depth() {
+        #[derive(Serialize)]
+        enum Tree {
+            Leaf(u32),
+            Node(Box<Tree>, Box<Tree>),
+            Branch { children: Vec<Tree> },
+        }
+
+        fn serialize<T: Serialize>(value: &T, max_depth: usize) -> crate::Result<String> {
+            Python::with_gil(|py| {
+                let pythonizer = Pythonizer::builder(py).max_depth(max_depth).build();
+                value.serialize(pythonizer).map(|obj| obj.to_string())
+            })
+        }
+
+        assert_eq!(serialize(&1, 0).unwrap(), "1");
+        assert_eq!(serialize(&Some(1), 0).unwrap(), "1");
+        assert_eq!(serialize(&vec![1], 1).unwrap(), "[1]");
+        let err = serialize(&vec![1], 0).unwrap_err();
+        assert_eq!(err.to_string(), "maximum depth of 0 exceeded");
+        assert!(serialize(&vec![vec![1]], 1).is_err());
+        assert!(serialize(&btreemap! { 1 => vec![1] }, 1).is_err());
+
+        // enum variants are wrapped in a dict, which is a level of its own
+        assert_eq!(serialize(&Tree::Leaf(1), 1).unwrap(), "{'Leaf': 1}");
+        let node = Tree::Node(Box::new(Tree::Leaf(1)), Box::new(Tree::Leaf(2)));
+        assert!(serialize(&node, 2).is_err());
+        assert_eq!(
+            serialize(&node, 3).unwrap(),
+            "{'Node': ({'Leaf': 1}, {'Leaf': 2})}"
+        );
+        let branch = Tree::Branch {
+            children: vec![Tree::Leaf(1)],
+        };
+        assert!(serialize(&branch, 3).is_err());
+        assert_eq!(
+            serialize(&branch, 4).unwrap(),
+            "{'Branch': {'children': [{'Leaf': 1}]}}"
+        );
+    }
+
+    #[test]
+    fn test_unit_struct() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Missing;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Field {
+            value: Option<Missing>,
+        }
+
+        Python::with_gil(|py| {
+            let serialize = |policy, value: &Field| {
+                let pythonizer = Pythonizer::builder(py).unit_struct(policy).build();
+                value.serialize(pythonizer).unwrap()
+            };
+            let missing = Field {
+                value: Some(Missing),
+            };
+
+            // `None` is ambiguous for an `Option` of a unit struct
+            let obj = serialize(UnitStructPolicy::None, &missing);
+            assert_eq!(obj.to_string(), "{'value': None}");
+            let actual: Field = depythonize(&obj).unwrap();
+            assert_eq!(actual, Field { value: None });
+
+            let obj = serialize(UnitStructPolicy::EmptyDict, &missing);
+            assert_eq!(obj.to_string(), "{'value': {}}");
+            assert_eq!(depythonize::<Field>(&obj).unwrap(), missing);
+
+            let obj = serialize(UnitStructPolicy::EmptyTuple, &missing);
+            assert_eq!(obj.to_string(), "{'value': ()}");
+            assert_eq!(depythonize::<Field>(&obj).unwrap(), missing);
+
+            let obj = serialize(UnitStructPolicy::Sentinel, &missing);
+            let sentinel = obj.get_item("value").unwrap();
+            assert_eq!(sentinel.get_type().name().unwrap(), "Missing");
+            let other = serialize(UnitStructPolicy::Sentinel, &missing);
+            assert!(other.get_item("value").unwrap().is(&sentinel));
+            assert_eq!(depythonize::<Field>(&obj).unwrap(), missing);
+
+            // other values are still rejected
+            let obj = pythonize(py, &hashmap! { "value" => 1 }).unwrap();
+            assert!(depythonize::<Field>(&obj).is_err());
+        });
+    }
+
+    #[test]
+    fn test_bytes_base64() {
+        Python::with_gil(|py| {
+            let bytes = serde_bytes::Bytes::new(b"pythonize\xff");
+            let pythonizer = Pythonizer::builder(py).bytes_base64(true).build();
+            let obj = bytes.serialize(pythonizer).unwrap();
+            assert_eq!(obj.repr().unwrap().to_string(), "'cHl0aG9uaXpl/w=='");
+
+            let pythonizer = Pythonizer::builder(py)
+                .bytes_base64(true)
+                .json_compatible(true)
+                .build();
+            let obj = vec![bytes].serialize(pythonizer).unwrap();
+            assert_eq!(obj.to_string(), "['cHl0aG9uaXpl/w==']");
+        });
+    }
+
+    #[test]
+    fn test_field_names_cached() {
+        #[derive(Serialize)]
+        struct Row {
+            id: u32,
+        }
+
+        Python::with_gil(|py| {
+            let rows = pythonize(py, &[Row { id: 1 }, Row { id: 2 }]).unwrap();
+            let key = |i: usize| -> Bound<'_, PyAny> {
+                let row = rows.get_item(i).unwrap();
+                let keys = row.call_method0("keys").unwrap();
+                keys.iter().unwrap().next().unwrap().unwrap()
+            };
+            assert_eq!(key(0).to_string(), "id");
+            assert!(key(0).is(&key(1)));
+        });
+    }
+
+    #[test]
+    fn test_variant_names_cached() {
+        #[derive(Serialize)]
+        enum Op {
+            Nop,
+            Push(u32),
+        }
+
+        Python::with_gil(|py| {
+            let ops = pythonize(py, &[Op::Nop, Op::Push(1), Op::Nop, Op::Push(2)]).unwrap();
+            let op = |i: usize| ops.get_item(i).unwrap();
+            assert_eq!(op(0).to_string(), "Nop");
+            assert!(op(0).is(&op(2)));
+            let key = |i: usize| -> Bound<'_, PyAny> {
+                let keys = op(i).call_method0("keys").unwrap();
+                keys.iter().unwrap().next().unwrap().unwrap()
+            };
+            assert_eq!(key(1).to_string(), "Push");
+            assert!(key(1).is(&key(3)));
+        });
+    }
+
+    #[test]
+    fn test_seq_length_hint() {
+        /// A sequence whose length hint is wrong
+        struct Hinted {
+            hint: usize,
+            items: Vec<u32>,
+        }
+
+        impl Serialize for Hinted {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeSeq;
+                let mut seq = serializer.serialize_seq(Some(self.hint))?;
+                for item in &self.items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+        }
+
+        Python::with_gil(|py| {
+            for hint in [0, 2, 3, 5, usize::MAX] {
+                let hinted = Hinted {
+                    hint,
+                    items: vec![1, 2, 3],
+                };
+                let obj = pythonize(py, &hinted).unwrap();
+                assert_eq!(obj.to_string(), "[1, 2, 3]");
+                let obj = hinted
+                    .serialize(Pythonizer::custom::<PythonizeImmutable>(py))
+                    .unwrap();
+                assert_eq!(obj.to_string(), "(1, 2, 3)");
+            }
+
+            // elements beyond the preallocated slots are appended
+            let hinted = Hinted {
+                hint: usize::MAX,
+                items: (0..10_000).collect(),
+            };
+            let obj = pythonize(py, &hinted).unwrap();
+            let list = obj.downcast::<PyList>().unwrap();
+            assert_eq!(list.len(), 10_000);
+            assert_eq!(
+                list.get_item(9_999).unwrap().extract::<u32>().unwrap(),
+                9_999
+            );
+        });
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        #[derive(Serialize)]
+        #[serde(untagged)]
+        enum Key {
+            Int(u32),
+            Str(String),
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            zone: &'static str,
+            limits: HashMap<&'static str, u32>,
+            alpha: bool,
+        }
+
+        Python::with_gil(|py| {
+            let config = Config {
+                zone: "eu",
+                limits: HashMap::from([("memory", 2), ("cpu", 1), ("disk", 3)]),
+                alpha: true,
+            };
+            let pythonizer = Pythonizer::builder(py).sort_keys(true).build();
+            let obj = config.serialize(pythonizer.clone()).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'alpha': True, 'limits': {'cpu': 1, 'disk': 3, 'memory': 2}, 'zone': 'eu'}"
+            );
+
+            let obj = HashMap::from([(3, 'c'), (1, 'a'), (2, 'b')])
+                .serialize(pythonizer.clone())
+                .unwrap();
+            assert_eq!(obj.to_string(), "{1: 'a', 2: 'b', 3: 'c'}");
+
+            // keys which Python cannot compare are an error
+            let keys = btreemap! { Some(1) => 1, None => 0 };
+            let err = keys.serialize(pythonizer.clone()).unwrap_err();
+            assert!(err.to_string().starts_with("TypeError: "), "{}", err);
+
+            // enough keys, out of order, for an inconsistent ordering to be detected by Rust's sort
+            struct Mixed;
+            impl Serialize for Mixed {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.collect_map((0..64u32).map(|i| {
+                        let i = i * 37 % 64;
+                        match i % 3 {
+                            0 => (Key::Int(i), i),
+                            _ => (Key::Str(i.to_string()), i),
+                        }
+                    }))
+                }
+            }
+            let err = Mixed.serialize(pythonizer).unwrap_err();
+            assert!(err.to_string().starts_with("TypeError: "), "{}", err);
+        });
+    }
+
+    #[test]
+    fn test_presized_dict() {
+        Python::with_gil(|py| {
+            let wide: BTreeMap<u32, u32> = (0..100).map(|i| (i, i * i)).collect();
+            let obj = pythonize(py, &wide).unwrap();
+            let dict = obj.downcast::<PyDict>().unwrap();
+            assert_eq!(dict.len(), 100);
+            for (i, (key, value)) in dict.iter().enumerate() {
+                assert_eq!(key.extract::<usize>().unwrap(), i);
+                assert_eq!(value.extract::<usize>().unwrap(), i * i);
+            }
+            for hint in [100, usize::MAX] {
+                let dict = <PyDict as PythonizeMappingType>::builder(py, Some(hint)).unwrap();
+                assert!(dict.is_empty());
+            }
+        });
+    }
+
+    #[test]
+    fn test_field_case_apply() {
+        let cases = [
+            ("user_name", "user_name", "userName", "user-name"),
+            ("userName", "user_name", "userName", "user-name"),
+            (
+                "Content-Type",
+                "content_type",
+                "contentType",
+                "content-type",
+            ),
+            ("HTTPServer2", "http_server2", "httpServer2", "http-server2"),
+            ("x", "x", "x", "x"),
+        ];
+        for (name, snake, camel, kebab) in cases {
+            assert_eq!(FieldCase::Snake.apply(name), snake);
+            assert_eq!(FieldCase::Camel.apply(name), camel);
+            assert_eq!(FieldCase::Kebab.apply(name), kebab);
+        }
+    }
+
+    #[test]
+    fn test_field_case() {
+        #[derive(Serialize)]
+        enum Event {
+            Login {
+                user_name: &'static str,
+                remote_addr: &'static str,
+            },
+        }
+
+        #[derive(Serialize)]
+        struct Log {
+            last_event: Event,
+            event_count: BTreeMap<&'static str, u8>,
+        }
+
+        let log = Log {
+            last_event: Event::Login {
+                user_name: "alice",
+                remote_addr: "::1",
+            },
+            event_count: btreemap! { "login_ok" => 1 },
+        };
+        Python::with_gil(|py| {
+            let pythonizer = Pythonizer::builder(py).field_case(FieldCase::Kebab).build();
+            let obj = log.serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'last-event': {'Login': {'user-name': 'alice', 'remote-addr': '::1'}}, \
+                 'event-count': {'login_ok': 1}}"
+            );
+
+            // keys are sorted after they are converted
+            let pythonizer = Pythonizer::builder(py)
+                .field_case(FieldCase::Camel)
+                .sort_keys(true)
+                .build();
+            let obj = log.serialize(pythonizer).unwrap();
+            assert_eq!(
+                obj.to_string(),
+                "{'eventCount': {'login_ok': 1}, \
+                 'lastEvent': {'Login': {'remoteAddr': '::1', 'userName': 'alice'}}}"
+            );
+        })
+    }
+
+    #[test]
+    fn test_share_empty() {
+        #[derive(Serialize)]
+        struct Empty {
+            list: Vec<u8>,
+            map: HashMap<u8, u8>,
+            #[serde(with = "crate::native")]
+            set: BTreeSet<u8>,
+        }
+
+        Python::with_gil(|py| {
+            let empty = || Empty {
+                list: vec![],
+                map: HashMap::new(),
+                set: BTreeSet::new(),
+            };
+            let pythonizer = Pythonizer::builder(py)
+                .types::<PythonizeImmutable>()
+                .share_empty(true)
+                .build();
+            let first = empty().serialize(pythonizer.clone()).unwrap();
+            let second = empty().serialize(pythonizer).unwrap();
+            for field in ["list", "map", "set"] {
+                let shared = first.get_item(field).unwrap();
+                assert!(shared.is(&second.get_item(field).unwrap()), "{}", field);
+            }
+            assert_eq!(
+                first.get_item("map").unwrap().repr().unwrap().to_string(),
+                "mappingproxy({})"
+            );
+
+            // without the option, each empty collection is a new instance
+            let pythonizer = Pythonizer::builder(py)
+                .types::<PythonizeImmutable>()
+                .build();
+            let first = empty().serialize(pythonizer.clone()).unwrap();
+            let second = empty().serialize(pythonizer).unwrap();
+            let map = first.get_item("map").unwrap();
+            assert!(!map.is(&second.get_item("map").unwrap()));
+
+            // mutable collections are never shared
+            let pythonizer = Pythonizer::builder(py).share_empty(true).build();
+            let first = empty().serialize(pythonizer.clone()).unwrap();
+            let second = empty().serialize(pythonizer).unwrap();
+            for field in ["list", "map", "set"] {
+                let mutable = first.get_item(field).unwrap();
+                assert!(!mutable.is(&second.get_item(field).unwrap()), "{}", field);
+            }
+        });
+    }
+
+    #[test]
+    fn test_buffers_reused() {
+        #[derive(Serialize)]
+        struct Row {
+            key: (u8, u8),
+            values: BTreeMap<u8, u8>,
+        }
+
+        Python::with_gil(|py| {
+            let row = |i: u8| Row {
+                key: (i, i),
+                values: (0..i).map(|j| (j, j)).collect(),
+            };
+            let pythonizer = Pythonizer::builder(py).sort_keys(true).build();
+            let obj = row(10).serialize(pythonizer.clone()).unwrap();
+            assert_eq!(obj.get_item("key").unwrap().to_string(), "(10, 10)");
+            let buffers = &pythonizer.buffers;
+            let capacity = buffers.items.borrow()[0].capacity();
+            assert!(capacity >= 10);
+
+            // later values take the same buffers, which hold no objects in between
+            for i in 0..10 {
+                let obj = row(i).serialize(pythonizer.clone()).unwrap();
+                assert_eq!(
+                    obj.get_item("values").unwrap().len().unwrap(),
+                    usize::from(i)
+                );
+            }
+            for pool in [
+                buffers.elements.borrow().len(),
+                buffers.items.borrow().len(),
+                buffers.fields.borrow().len(),
+            ] {
+                assert_eq!(pool, 1);
+            }
+            assert_eq!(buffers.items.borrow()[0].capacity(), capacity);
+            assert!(buffers.items.borrow()[0].is_empty());
+        });
+    }
+
+    #[test]
+    fn test_pythonize_into() {
+        #[derive(Serialize)]
+        struct Config {
+            debug: bool,
+            level: u8,
+            hosts: Vec<&'static str>,
+        }
+
+        let config = Config {
+            debug: true,
+            level: 2,
+            hosts: vec!["a"],
+        };
+        Python::with_gil(|py| {
+            let locals = PyDict::new_bound(py);
+            py.run_bound(
+                "import types\n\
+                 config = {'debug': False, 'name': 'app'}\n\
+                 class Settings:\n    \
+                     def __init__(self):\n        \
+                         self.level = 0\n        \
+                         self.name = 'app'\n\
+                 settings = Settings()\n\
+                 frozen = types.MappingProxyType({})\n\
+                 point = (1, 2)",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let get = |name: &str| locals.get_item(name).unwrap().unwrap();
+
+            // a mapping is updated in place
+            let target = get("config");
+            pythonize_into(py, &config, &target).unwrap();
+            assert_eq!(
+                target.to_string(),
+                "{'debug': True, 'name': 'app', 'level': 2, 'hosts': ['a']}"
+            );
+
+            // other objects have their attributes assigned
+            let target = get("settings");
+            pythonize_into(py, &config, &target).unwrap();
+            assert_eq!(
+                target.getattr("__dict__").unwrap().to_string(),
+                "{'level': 2, 'name': 'app', 'debug': True, 'hosts': ['a']}"
+            );
+
+            let map: BTreeMap<&str, i32> = btreemap! { "level" => 5 };
+            pythonize_into(py, &map, &target).unwrap();
+            assert_eq!(target.getattr("level").unwrap().to_string(), "5");
+
+            let err = pythonize_into(py, &config, &get("frozen")).unwrap_err();
+            assert!(err.to_string().starts_with("TypeError: "), "{}", err);
+            let err = pythonize_into(py, &config, &get("point")).unwrap_err();
+            assert!(err.to_string().starts_with("AttributeError: "), "{}", err);
+            // the value must have fields
+            let err = pythonize_into(py, &vec![1, 2], &get("config")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "unexpected type: 'list' object cannot be converted to 'PyDict'"
+            );
+            let map: BTreeMap<i32, i32> = btreemap! { 1 => 2 };
+            let err = pythonize_into(py, &map, &get("settings")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "unexpected type: 'int' object cannot be converted to 'PyString'"
+            );
+        })
+    }
 }
@@ -0,0 +1,720 @@
+use pyo3::{Bound, PyAny, Python};
+use serde::{ser, Serialize, Serializer};
+
+use crate::error::{PythonizeError, Result};
+use crate::native::enum_member::{self, Definition};
+use crate::native::ENUM_TOKEN;
+use crate::ser::pythonize;
+
+/// A value serialized into plain Rust data, to be converted into Python objects later
+///
+/// Running a value's `Serialize` implementation can take much longer than creating the
+/// Python objects. [`Staged::new`] does the serde traversal without needing the GIL,
+/// such as on a worker thread, and [`Staged::pythonize`] then creates the Python
+/// objects in a single pass while holding it.
+///
+/// A `Staged` value is itself `Serialize`, replaying the value it was created from, so
+/// it can also be serialized by a [`Pythonizer`](crate::Pythonizer) with runtime options
+/// or custom [`PythonizeTypes`](crate::PythonizeTypes).
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use std::thread;
+///
+/// use pyo3::prelude::*;
+/// use pythonize::Staged;
+///
+/// let table: BTreeMap<u32, Vec<String>> = (0..100)
+///     .map(|i| (i, vec![i.to_string(); 3]))
+///     .collect();
+/// let staged = thread::spawn(move || Staged::new(&table).unwrap())
+///     .join()
+///     .unwrap();
+///
+/// Python::with_gil(|py| {
+///     let obj = staged.pythonize(py).unwrap();
+///     assert_eq!(obj.get_item(7).unwrap().to_string(), "['7', '7', '7']");
+/// })
+/// ```
+#[derive(Clone, Debug)]
+pub struct Staged(Node);
+
+impl Staged {
+    /// Serializes `value` without touching Python
+    pub fn new<T>(value: &T) -> Result<Self>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(Stager).map(Staged)
+    }
+
+    /// Creates the Python objects for the staged value, as [`pythonize`] does for the
+    /// value it was created from
+    pub fn pythonize<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyAny>> {
+        pythonize(py, self)
+    }
+}
+
+impl Serialize for Staged {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The name of an enum, with the index and name of one of its variants
+#[derive(Clone, Copy, Debug)]
+struct Variant {
+    name: &'static str,
+    index: u32,
+    variant: &'static str,
+}
+
+/// A node of the serde data model
+///
+/// Integers and floats are widened, since the `Pythonizer` converts them all the same.
+#[derive(Clone, Debug)]
+enum Node {
+    Bool(bool),
+    I64(i64),
+    I128(i128),
+    U64(u64),
+    U128(u128),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Node>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(Variant),
+    NewtypeStruct(&'static str, Box<Node>),
+    /// A unit variant serialized by [`enum_member`], with the enum it belongs to
+    EnumMember(Option<Definition>, Box<Node>),
+    NewtypeVariant(Variant, Box<Node>),
+    Seq(Option<usize>, Vec<Node>),
+    Tuple(Vec<Node>),
+    TupleStruct(&'static str, Vec<Node>),
+    TupleVariant(Variant, Vec<Node>),
+    Map(Option<usize>, Vec<(Node, Node)>),
+    Struct(&'static str, Vec<(&'static str, Node)>),
+    StructVariant(Variant, Vec<(&'static str, Node)>),
+}
+
+impl Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::{
+            SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+            SerializeTupleStruct, SerializeTupleVariant,
+        };
+
+        match self {
+            Node::Bool(v) => serializer.serialize_bool(*v),
+            Node::I64(v) => serializer.serialize_i64(*v),
+            Node::I128(v) => serializer.serialize_i128(*v),
+            Node::U64(v) => serializer.serialize_u64(*v),
+            Node::U128(v) => serializer.serialize_u128(*v),
+            Node::F64(v) => serializer.serialize_f64(*v),
+            Node::Char(v) => serializer.serialize_char(*v),
+            Node::Str(v) => serializer.serialize_str(v),
+            Node::Bytes(v) => serializer.serialize_bytes(v),
+            Node::None => serializer.serialize_none(),
+            Node::Some(value) => serializer.serialize_some(value),
+            Node::Unit => serializer.serialize_unit(),
+            Node::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Node::UnitVariant(v) => serializer.serialize_unit_variant(v.name, v.index, v.variant),
+            Node::NewtypeStruct(name, value) => serializer.serialize_newtype_struct(name, value),
+            Node::EnumMember(definition, value) => {
+                enum_member::serialize_with_definition(*definition, serializer, value)
+            }
+            Node::NewtypeVariant(v, value) => {
+                serializer.serialize_newtype_variant(v.name, v.index, v.variant, value)
+            }
+            Node::Seq(len, elements) => {
+                let mut seq = serializer.serialize_seq(*len)?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Node::Tuple(elements) => {
+                let mut tuple = serializer.serialize_tuple(elements.len())?;
+                for element in elements {
+                    tuple.serialize_element(element)?;
+                }
+                tuple.end()
+            }
+            Node::TupleStruct(name, fields) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Node::TupleVariant(v, fields) => {
+                let mut tuple =
+                    serializer.serialize_tuple_variant(v.name, v.index, v.variant, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Node::Map(len, entries) => {
+                let mut map = serializer.serialize_map(*len)?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Node::Struct(name, fields) => {
+                let mut s = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    s.serialize_field(key, value)?;
+                }
+                s.end()
+            }
+            Node::StructVariant(v, fields) => {
+                let mut s = serializer.serialize_struct_variant(
+                    v.name,
+                    v.index,
+                    v.variant,
+                    fields.len(),
+                )?;
+                for (key, value) in fields {
+                    s.serialize_field(key, value)?;
+                }
+                s.end()
+            }
+        }
+    }
+}
+
+/// A serializer which records the serde data model calls as a [`Node`]
+struct Stager;
+
+/// The kinds of [`Node`] made of elements
+enum Elements {
+    Seq(Option<usize>),
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(Variant),
+}
+
+/// Collects the elements of sequences, tuples and tuple structs and variants
+struct StageElements {
+    kind: Elements,
+    elements: Vec<Node>,
+}
+
+impl StageElements {
+    fn new(kind: Elements, len: usize) -> Self {
+        Self {
+            kind,
+            elements: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.elements.push(value.serialize(Stager)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node> {
+        Ok(match self.kind {
+            Elements::Seq(len) => Node::Seq(len, self.elements),
+            Elements::Tuple => Node::Tuple(self.elements),
+            Elements::TupleStruct(name) => Node::TupleStruct(name, self.elements),
+            Elements::TupleVariant(variant) => Node::TupleVariant(variant, self.elements),
+        })
+    }
+}
+
+/// Collects the entries of a map
+struct StageMap {
+    len: Option<usize>,
+    entries: Vec<(Node, Node)>,
+    key: Option<Node>,
+}
+
+/// Collects the fields of a struct, or of a struct variant if there is a variant
+struct StageFields {
+    name: &'static str,
+    variant: Option<Variant>,
+    fields: Vec<(&'static str, Node)>,
+}
+
+impl ser::Serializer for Stager {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    type SerializeSeq = StageElements;
+    type SerializeTuple = StageElements;
+    type SerializeTupleStruct = StageElements;
+    type SerializeTupleVariant = StageElements;
+    type SerializeMap = StageMap;
+    type SerializeStruct = StageFields;
+    type SerializeStructVariant = StageFields;
+
+    fn serialize_bool(self, v: bool) -> Result<Node> {
+        Ok(Node::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node> {
+        Ok(Node::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Node> {
+        Ok(Node::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node> {
+        Ok(Node::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Node> {
+        Ok(Node::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node> {
+        Ok(Node::F64(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Node> {
+        Ok(Node::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node> {
+        Ok(Node::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node> {
+        Ok(Node::Str(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node> {
+        Ok(Node::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Node> {
+        Ok(Node::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Node>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Node::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Node> {
+        Ok(Node::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Node> {
+        Ok(Node::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Node> {
+        Ok(Node::UnitVariant(Variant {
+            name,
+            index,
+            variant,
+        }))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Node>
+    where
+        T: ?Sized + Serialize,
+    {
+        if name == ENUM_TOKEN {
+            // the enum is only known while `enum_member::serialize` is running
+            let definition = enum_member::take_definition();
+            return Ok(Node::EnumMember(
+                definition,
+                Box::new(value.serialize(self)?),
+            ));
+        }
+        Ok(Node::NewtypeStruct(name, Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node>
+    where
+        T: ?Sized + Serialize,
+    {
+        let variant = Variant {
+            name,
+            index,
+            variant,
+        };
+        Ok(Node::NewtypeVariant(
+            variant,
+            Box::new(value.serialize(self)?),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<StageElements> {
+        Ok(StageElements::new(Elements::Seq(len), len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<StageElements> {
+        Ok(StageElements::new(Elements::Tuple, len))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<StageElements> {
+        Ok(StageElements::new(Elements::TupleStruct(name), len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StageElements> {
+        let variant = Variant {
+            name,
+            index,
+            variant,
+        };
+        Ok(StageElements::new(Elements::TupleVariant(variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<StageMap> {
+        Ok(StageMap {
+            len,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<StageFields> {
+        Ok(StageFields {
+            name,
+            variant: None,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StageFields> {
+        let variant = Variant {
+            name,
+            index,
+            variant,
+        };
+        Ok(StageFields {
+            name,
+            variant: Some(variant),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+impl ser::SerializeSeq for StageElements {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for StageElements {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for StageElements {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for StageElements {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for StageMap {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(Stager)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value should always be called after serialize_key");
+        self.entries.push((key, value.serialize(Stager)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node> {
+        Ok(Node::Map(self.len, self.entries))
+    }
+}
+
+impl StageFields {
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.fields.push((key, value.serialize(Stager)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node> {
+        Ok(match self.variant {
+            Some(variant) => Node::StructVariant(variant, self.fields),
+            None => Node::Struct(self.name, self.fields),
+        })
+    }
+}
+
+impl ser::SerializeStruct for StageFields {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StageFields {
+    type Ok = Node;
+    type Error = PythonizeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Node> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use pyo3::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::Staged;
+    use crate::{depythonize, pythonize, KeyPolicy, PythonizeImmutable, Pythonizer};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Point,
+        Circle(f32),
+        Line(i8, i8),
+        Rect { width: u16, height: u16 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Unit;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Meters(f64);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Scene {
+        name: String,
+        shapes: Vec<Shape>,
+        depth: Option<Meters>,
+        unit: Unit,
+        origin: (i64, u64),
+        big: (i128, u128),
+        initial: char,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        labels: BTreeMap<u8, Option<String>>,
+        #[serde(with = "crate::native")]
+        tags: BTreeSet<String>,
+        #[serde(with = "crate::native::enum_member")]
+        level: Level,
+        #[serde(with = "crate::native::packed")]
+        samples: Vec<f64>,
+    }
+
+    fn scene() -> Scene {
+        Scene {
+            name: "demo".to_string(),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(0.5),
+                Shape::Line(-1, 1),
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            ],
+            depth: Some(Meters(2.5)),
+            unit: Unit,
+            origin: (-7, 7),
+            big: (i128::MIN, u128::MAX),
+            initial: 'd',
+            data: vec![0, 1],
+            labels: BTreeMap::from([(1, Some("one".to_string())), (2, None)]),
+            tags: BTreeSet::from(["a".to_string()]),
+            level: Level::High,
+            samples: vec![1.0, 2.0],
+        }
+    }
+
+    #[test]
+    fn test_staged_matches_pythonize() {
+        let staged = std::thread::spawn(|| Staged::new(&scene()).unwrap())
+            .join()
+            .unwrap();
+        Python::with_gil(|py| {
+            let expected = pythonize(py, &scene()).unwrap();
+            let actual = staged.pythonize(py).unwrap();
+            assert_eq!(
+                actual.repr().unwrap().to_string(),
+                expected.repr().unwrap().to_string()
+            );
+            assert!(actual.eq(&expected).unwrap());
+            assert_eq!(depythonize::<Scene>(&actual).unwrap(), scene());
+
+            // the enum member is created for the staged value too
+            assert!(actual
+                .get_item("level")
+                .unwrap()
+                .is(&expected.get_item("level").unwrap()));
+        })
+    }
+
+    #[test]
+    fn test_staged_pythonizer() {
+        let staged = Staged::new(&scene()).unwrap();
+        Python::with_gil(|py| {
+            let pythonizer = || {
+                Pythonizer::builder(py)
+                    .types::<PythonizeImmutable>()
+                    .keys(KeyPolicy::Stringify)
+                    .build()
+            };
+            let expected = scene().serialize(pythonizer()).unwrap();
+            let actual = staged.serialize(pythonizer()).unwrap();
+            assert_eq!(
+                actual.repr().unwrap().to_string(),
+                expected.repr().unwrap().to_string()
+            );
+        })
+    }
+}
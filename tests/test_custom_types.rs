@@ -1,15 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use pyo3::{
     exceptions::{PyIndexError, PyKeyError},
     prelude::*,
-    types::{PyDict, PyMapping, PySequence, PyTuple},
+    types::{
+        PyByteArray, PyBytes, PyDict, PyInt, PyList, PyMapping, PySequence, PySet, PyString,
+        PyTuple,
+    },
 };
 use pythonize::{
-    depythonize, pythonize_custom, PythonizeListType, PythonizeMappingType,
-    PythonizeNamedMappingType, PythonizeTypes, PythonizeUnnamedMappingAdapter, Pythonizer,
+    depythonize, pythonize_custom, PythonizeDataclass, PythonizeFrozenDict, PythonizeImmutable,
+    PythonizeImmutablesMap, PythonizeListType, PythonizeMappingType, PythonizeNamedMappingType,
+    PythonizeNamedTuple, PythonizeOrderedDict, PythonizeSortedDict, PythonizeTypes,
+    PythonizeUnitVariantType, PythonizeUnnamedMappingAdapter, Pythonizer,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 #[pyclass(sequence)]
@@ -59,7 +64,12 @@ struct PythonizeCustomList;
 impl<'py> PythonizeTypes<'py> for PythonizeCustomList {
     type Map = PyDict;
     type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
     type List = CustomList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
 }
 
 #[test]
@@ -134,7 +144,12 @@ struct PythonizeCustomDict;
 impl<'py> PythonizeTypes<'py> for PythonizeCustomDict {
     type Map = CustomDict;
     type NamedMap = PythonizeUnnamedMappingAdapter<'py, CustomDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, CustomDict>;
     type List = PyTuple;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
 }
 
 #[test]
@@ -243,7 +258,12 @@ struct PythonizeNamedCustomDict;
 impl<'py> PythonizeTypes<'py> for PythonizeNamedCustomDict {
     type Map = CustomDict;
     type NamedMap = NamedCustomDict;
+    type Struct = NamedCustomDict;
     type List = PyTuple;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
 }
 
 #[derive(Serialize)]
@@ -279,3 +299,432 @@ fn test_custom_named_dict() {
         assert_eq!(deserialized, json!({ "hello": 1, "world": 2 }));
     })
 }
+
+struct PythonizeByteArray;
+impl<'py> PythonizeTypes<'py> for PythonizeByteArray {
+    type Map = PyDict;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyByteArray;
+}
+
+#[test]
+fn test_custom_bytes() {
+    Python::with_gil(|py| {
+        let bytes = serde_bytes::ByteBuf::from(b"hello".to_vec());
+        let serialized = pythonize_custom::<PythonizeByteArray, _>(py, &bytes).unwrap();
+        assert!(serialized.is_instance_of::<PyByteArray>());
+
+        let deserialized: serde_bytes::ByteBuf = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, bytes);
+    })
+}
+
+struct PythonizeNamedTuples;
+impl<'py> PythonizeTypes<'py> for PythonizeNamedTuples {
+    type Map = PyDict;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PythonizeNamedTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point(f64, f64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Segment(Point, Point),
+}
+
+#[test]
+fn test_custom_named_tuple() {
+    Python::with_gil(|py| {
+        let point = pythonize_custom::<PythonizeNamedTuples, _>(py, &Point(1.0, 2.0)).unwrap();
+        assert!(point.is_instance_of::<PyTuple>());
+        assert_eq!(point.repr().unwrap().to_string(), "Point(_0=1.0, _1=2.0)");
+        assert_eq!(point.getattr("_1").unwrap().extract::<f64>().unwrap(), 2.0);
+
+        // classes are reused for each name
+        let other = pythonize_custom::<PythonizeNamedTuples, _>(py, &Point(3.0, 4.0)).unwrap();
+        assert!(point.get_type().is(&other.get_type()));
+
+        let shape = Shape::Segment(Point(0.0, 0.0), Point(1.0, 1.0));
+        let serialized = pythonize_custom::<PythonizeNamedTuples, _>(py, &shape).unwrap();
+        assert_eq!(
+            serialized.repr().unwrap().to_string(),
+            "{'Segment': Segment(_0=Point(_0=0.0, _1=0.0), _1=Point(_0=1.0, _1=1.0))}"
+        );
+        let deserialized: Shape = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, shape);
+
+        // tuples have no name to give a class
+        let pair = pythonize_custom::<PythonizeNamedTuples, _>(py, &(1, 2)).unwrap();
+        assert!(pair.is_exact_instance_of::<PyTuple>());
+    })
+}
+
+struct PythonizeDataclasses;
+impl<'py> PythonizeTypes<'py> for PythonizeDataclasses {
+    type Map = PyDict;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeDataclass;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+#[derive(Serialize)]
+struct Sample {
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<f64>,
+}
+
+#[derive(Serialize)]
+enum Event {
+    Measured { sample: Sample },
+}
+
+#[test]
+fn test_custom_dataclass() {
+    Python::with_gil(|py| {
+        let sample = Sample {
+            name: "a",
+            weight: Some(1.5),
+        };
+        let serialized = pythonize_custom::<PythonizeDataclasses, _>(py, &sample).unwrap();
+        assert_eq!(
+            serialized.repr().unwrap().to_string(),
+            "Sample(name='a', weight=1.5)"
+        );
+        let dataclasses = py.import_bound("dataclasses").unwrap();
+        assert!(dataclasses
+            .call_method1("is_dataclass", (&serialized,))
+            .unwrap()
+            .is_truthy()
+            .unwrap());
+
+        // classes are reused for each name and set of fields
+        let other = pythonize_custom::<PythonizeDataclasses, _>(py, &sample).unwrap();
+        assert!(serialized.get_type().is(&other.get_type()));
+        let unweighed = Sample {
+            name: "b",
+            weight: None,
+        };
+        let other = pythonize_custom::<PythonizeDataclasses, _>(py, &unweighed).unwrap();
+        assert_eq!(other.repr().unwrap().to_string(), "Sample(name='b')");
+        assert!(!serialized.get_type().is(&other.get_type()));
+
+        // enum variants are still wrapped in a mapping
+        let event = Event::Measured { sample };
+        let serialized = pythonize_custom::<PythonizeDataclasses, _>(py, &event).unwrap();
+        assert_eq!(
+            serialized.repr().unwrap().to_string(),
+            "{'Measured': Measured(sample=Sample(name='a', weight=1.5))}"
+        );
+    })
+}
+
+struct PythonizeUnitVariants<U>(U);
+impl<'py, U: PythonizeUnitVariantType> PythonizeTypes<'py> for PythonizeUnitVariants<U> {
+    type Map = PyDict;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = U;
+    type Bytes = PyBytes;
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Color {
+    Red,
+    Green,
+    Rgb(u8, u8, u8),
+}
+
+#[test]
+fn test_custom_unit_variant() {
+    Python::with_gil(|py| {
+        let colors = vec![Color::Green, Color::Rgb(1, 2, 3)];
+
+        let serialized = pythonize_custom::<PythonizeUnitVariants<PyInt>, _>(py, &colors).unwrap();
+        assert_eq!(serialized.to_string(), "[1, {'Rgb': (1, 2, 3)}]");
+        let deserialized: Vec<Color> = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, colors);
+
+        let serialized = pythonize_custom::<PythonizeUnitVariants<PyDict>, _>(py, &colors).unwrap();
+        assert_eq!(
+            serialized.to_string(),
+            "[{'Color': 'Green'}, {'Rgb': (1, 2, 3)}]"
+        );
+        let deserialized: Vec<Color> = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, colors);
+
+        let serialized =
+            pythonize_custom::<PythonizeUnitVariants<PyString>, _>(py, &colors).unwrap();
+        assert_eq!(serialized.to_string(), "['Green', {'Rgb': (1, 2, 3)}]");
+    })
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    ports: Vec<u16>,
+    #[serde(with = "pythonize::native")]
+    tags: BTreeSet<String>,
+    limits: HashMap<String, u32>,
+}
+
+#[test]
+fn test_immutable() {
+    Python::with_gil(|py| {
+        let config = Config {
+            name: "server".to_string(),
+            ports: vec![80, 443],
+            tags: BTreeSet::from(["web".to_string()]),
+            limits: HashMap::from([("connections".to_string(), 100)]),
+        };
+        let serialized = pythonize_custom::<PythonizeImmutable, _>(py, &config).unwrap();
+        assert_eq!(
+            serialized.repr().unwrap().to_string(),
+            "mappingproxy({'name': 'server', 'ports': (80, 443), 'tags': frozenset({'web'}), \
+             'limits': mappingproxy({'connections': 100})})"
+        );
+        assert!(serialized.set_item("name", "client").is_err());
+
+        let deserialized: Config = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, config);
+    })
+}
+
+#[derive(Serialize)]
+enum Request {
+    Get { path: &'static str, retries: u8 },
+}
+
+#[test]
+fn test_ordered_dict() {
+    Python::with_gil(|py| {
+        let request = Request::Get {
+            path: "/",
+            retries: 2,
+        };
+        let serialized = pythonize_custom::<PythonizeOrderedDict, _>(py, &request).unwrap();
+        assert_eq!(serialized.get_type().name().unwrap(), "OrderedDict");
+        let get = serialized.get_item("Get").unwrap();
+        assert_eq!(get.get_type().name().unwrap(), "OrderedDict");
+        assert_eq!(
+            get.call_method0("items").unwrap().to_string(),
+            "odict_items([('path', '/'), ('retries', 2)])"
+        );
+
+        let map = HashMap::from([("a", 1)]);
+        let serialized = pythonize_custom::<PythonizeOrderedDict, _>(py, &map).unwrap();
+        assert_eq!(serialized.get_type().name().unwrap(), "OrderedDict");
+        let deserialized: HashMap<String, i32> = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, HashMap::from([("a".to_string(), 1)]));
+    })
+}
+
+#[test]
+#[ignore = "needs the frozendict Python package"]
+fn test_frozendict() {
+    Python::with_gil(|py| {
+        let serialized =
+            pythonize_custom::<PythonizeFrozenDict, _>(py, &Struct { hello: 1, world: 2 }).unwrap();
+        assert_eq!(serialized.get_type().name().unwrap(), "frozendict");
+        assert!(serialized.set_item("hello", 3).is_err());
+
+        let deserialized: Value = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, json!({ "hello": 1, "world": 2 }));
+    })
+}
+
+struct PythonizeHamt;
+impl<'py> PythonizeTypes<'py> for PythonizeHamt {
+    type Map = PythonizeImmutablesMap;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PythonizeImmutablesMap>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PythonizeImmutablesMap>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+#[test]
+#[ignore = "needs the immutables Python package"]
+fn test_immutables_map() {
+    Python::with_gil(|py| {
+        let map = HashMap::from([("a", Struct { hello: 1, world: 2 })]);
+        let serialized = pythonize_custom::<PythonizeHamt, _>(py, &map).unwrap();
+        assert_eq!(serialized.get_type().name().unwrap(), "Map");
+        let inner = serialized.get_item("a").unwrap();
+        assert_eq!(inner.get_type().name().unwrap(), "Map");
+        let updated = inner.call_method1("set", ("hello", 3)).unwrap();
+        assert_eq!(updated.get_item("hello").unwrap().to_string(), "3");
+        assert_eq!(inner.get_item("hello").unwrap().to_string(), "1");
+
+        let deserialized: Value = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, json!({ "a": { "hello": 1, "world": 2 } }));
+    })
+}
+
+#[test]
+#[ignore = "needs the sortedcontainers Python package"]
+fn test_sorted_dict() {
+    Python::with_gil(|py| {
+        let map = HashMap::from([("b", 2), ("c", 3), ("a", 1)]);
+        let serialized = pythonize_custom::<PythonizeSortedDict, _>(py, &map).unwrap();
+        assert_eq!(
+            serialized.to_string(),
+            "SortedDict({'a': 1, 'b': 2, 'c': 3})"
+        );
+
+        let deserialized: HashMap<String, i32> = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized.len(), 3);
+    })
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Stroke {
+    Circle { radius: f64 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Drawing {
+    tags: HashMap<String, i32>,
+    points: Vec<(i32, i32)>,
+    stroke: Stroke,
+}
+
+#[test]
+fn test_runtime_classes() {
+    Python::with_gil(|py| {
+        let locals = PyDict::new_bound(py);
+        py.run_bound(
+            r#"
+class Tags(dict):
+    pass
+
+class Points(list):
+    pass
+
+class Record(dict):
+    def __init__(self, name, fields):
+        super().__init__(fields)
+        self.name = name
+"#,
+            None,
+            Some(&locals),
+        )
+        .unwrap();
+        let class = |name| {
+            locals
+                .get_item(name)
+                .unwrap()
+                .unwrap()
+                .downcast_into()
+                .unwrap()
+        };
+
+        let drawing = Drawing {
+            tags: HashMap::from([("a".to_string(), 1)]),
+            points: vec![(0, 0), (1, 2)],
+            stroke: Stroke::Circle { radius: 1.5 },
+        };
+        let pythonizer = Pythonizer::builder(py)
+            .map_class(&class("Tags"))
+            .list_class(&class("Points"))
+            .named_map_class(&class("Record"))
+            .build();
+        let serialized = drawing.serialize(pythonizer).unwrap();
+
+        let type_name = |obj: &Bound<PyAny>| obj.get_type().name().unwrap().to_string();
+        assert_eq!(type_name(&serialized), "Record");
+        assert_eq!(serialized.getattr("name").unwrap().to_string(), "Drawing");
+        let tags = serialized.get_item("tags").unwrap();
+        assert_eq!(type_name(&tags), "Tags");
+        assert_eq!(tags.to_string(), "{'a': 1}");
+        let points = serialized.get_item("points").unwrap();
+        assert_eq!(type_name(&points), "Points");
+        assert_eq!(points.to_string(), "[(0, 0), (1, 2)]");
+        let stroke = serialized.get_item("stroke").unwrap();
+        assert_eq!(stroke.getattr("name").unwrap().to_string(), "Stroke");
+        let circle = stroke.get_item("Circle").unwrap();
+        assert_eq!(circle.getattr("name").unwrap().to_string(), "Circle");
+
+        let deserialized: Drawing = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, drawing);
+    })
+}
+
+/// Creates `dict`s from a list of all the key-value pairs at once
+struct DictFromPairs;
+
+impl<'py> PythonizeMappingType<'py> for DictFromPairs {
+    type Builder = Bound<'py, PyList>;
+
+    fn builder(py: Python<'py>, _len: Option<usize>) -> PyResult<Self::Builder> {
+        Ok(PyList::empty_bound(py))
+    }
+
+    fn push_item(
+        _builder: &mut Self::Builder,
+        _key: Bound<'py, PyAny>,
+        _value: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        unreachable!("maps are built from all of their items at once")
+    }
+
+    fn push_items(
+        builder: &mut Self::Builder,
+        items: impl ExactSizeIterator<Item = (Bound<'py, PyAny>, Bound<'py, PyAny>)>,
+    ) -> PyResult<()> {
+        *builder = PyList::new_bound(builder.py(), items);
+        Ok(())
+    }
+
+    fn finish(builder: Self::Builder) -> PyResult<Bound<'py, PyMapping>> {
+        Ok(PyDict::from_sequence_bound(&builder)?.into_mapping())
+    }
+}
+
+struct PythonizeDictFromPairs;
+impl<'py> PythonizeTypes<'py> for PythonizeDictFromPairs {
+    type Map = DictFromPairs;
+    type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
+}
+
+#[test]
+fn test_bulk_mapping() {
+    Python::with_gil(|py| {
+        let value = json!({ "a": { "b": 1, "c": [{}, { "d": null }] }, "e": "f" });
+        let serialized = pythonize_custom::<PythonizeDictFromPairs, _>(py, &value).unwrap();
+        assert_eq!(
+            serialized.to_string(),
+            "{'a': {'b': 1, 'c': [{}, {'d': None}]}, 'e': 'f'}"
+        );
+
+        let deserialized: Value = depythonize(&serialized).unwrap();
+        assert_eq!(deserialized, value);
+    })
+}
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use pyo3::{
     prelude::*,
-    types::{PyDict, PyList},
+    types::{PyBytes, PyDict, PyList, PySet, PyString, PyTuple},
 };
 use pythonize::{PythonizeTypes, PythonizeUnnamedMappingAdapter};
 use serde::{Deserialize, Serialize};
@@ -16,7 +16,12 @@ struct Root<T> {
 impl<'py, T> PythonizeTypes<'py> for Root<T> {
     type Map = PyDict;
     type NamedMap = PythonizeUnnamedMappingAdapter<'py, PyDict>;
+    type Struct = PythonizeUnnamedMappingAdapter<'py, PyDict>;
     type List = PyList;
+    type Set = PySet;
+    type Tuple = PyTuple;
+    type UnitVariant = PyString;
+    type Bytes = PyBytes;
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]